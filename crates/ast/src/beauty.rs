@@ -0,0 +1,119 @@
+use std::fmt::Write;
+
+use crate::token::NumberLiteral;
+use crate::TinyString;
+
+/// A tree that is printed with one node per line, so syntax trees can be
+/// inspected and compared in tests. It is used by the `ToBeauty` traits of the
+/// parser and of the HIR.
+///
+/// Nodes that contain only one printable node are printed in a single line,
+/// e.g. `Ident: foo`.
+#[derive(Debug, Clone)]
+pub struct Beauty {
+    data: BeautyData,
+    // 0, 1 or many
+    num: u8,
+}
+
+#[derive(Debug, Clone)]
+enum BeautyData {
+    List(Vec<Beauty>),
+    Str(&'static str),
+    Text(TinyString),
+    Char(char),
+    Number(NumberLiteral),
+    Kv { key: &'static str, value: Box<Beauty> },
+    Empty,
+}
+
+impl Beauty {
+    pub fn str(s: &'static str) -> Self { Beauty { data: BeautyData::Str(s), num: 1 } }
+
+    pub fn text(s: impl Into<TinyString>) -> Self {
+        Beauty { data: BeautyData::Text(s.into()), num: 1 }
+    }
+
+    pub fn char(c: char) -> Self { Beauty { data: BeautyData::Char(c), num: 1 } }
+
+    /// A number, which is written directly into the output without allocating
+    pub fn number(n: NumberLiteral) -> Self {
+        Beauty { data: BeautyData::Number(n), num: 1 }
+    }
+
+    /// A node that isn't printed, e.g. for `None`
+    pub fn empty() -> Self { Beauty { data: BeautyData::Empty, num: 0 } }
+
+    pub fn kv(key: &'static str, value: Beauty) -> Self {
+        let num = value.num;
+        let data = BeautyData::Kv { key, value: Box::new(value) };
+        Beauty { data, num }
+    }
+
+    pub fn kvs(key: &'static str, values: Vec<Beauty>) -> Self {
+        Beauty::kv(key, Beauty::list(values))
+    }
+
+    pub fn list(values: Vec<Beauty>) -> Self {
+        let mut num = 0;
+        for b in &values {
+            num += b.num;
+            if num > 1 {
+                break;
+            }
+        }
+        Beauty { data: BeautyData::List(values), num }
+    }
+
+    /// Appends the printed tree to `buf`
+    pub fn write_to(&self, buf: &mut String) { self.write(buf, 0) }
+
+    fn write(&self, buf: &mut String, indent: u32) {
+        if self.num == 0 {
+            return;
+        }
+        match &self.data {
+            BeautyData::List(l) => {
+                if self.num == 1 {
+                    l.iter().find(|&x| x.num > 0).unwrap().write(buf, indent);
+                } else {
+                    for (i, x) in l.iter().filter(|&x| x.num > 0).enumerate() {
+                        if i > 0 {
+                            do_indent(buf, indent);
+                        }
+                        x.write(buf, indent);
+                        if x.num == 1 {
+                            buf.push('\n');
+                        }
+                    }
+                }
+            }
+            BeautyData::Str(s) => buf.push_str(s),
+            BeautyData::Text(s) => buf.push_str(s),
+            BeautyData::Char(c) => buf.push(*c),
+            // writing into a `String` can't fail
+            BeautyData::Number(n) => write!(buf, "{}", n).unwrap(),
+            BeautyData::Kv { key, value } => {
+                buf.push_str(key);
+                if self.num == 1 {
+                    buf.push_str(": ");
+                    value.write(buf, indent);
+                } else {
+                    buf.push('\n');
+                    do_indent(buf, indent + 3);
+                    value.write(buf, indent + 3);
+                    if value.num == 1 {
+                        buf.push('\n');
+                    }
+                }
+            }
+            BeautyData::Empty => {}
+        }
+    }
+}
+
+fn do_indent(buf: &mut String, indent: u32) { buf.extend((0..indent).map(|_| ' ')); }
+
+impl From<Vec<Beauty>> for Beauty {
+    fn from(values: Vec<Beauty>) -> Self { Beauty::list(values) }
+}
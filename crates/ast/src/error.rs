@@ -1,18 +1,45 @@
+use crate::token::NumberSuffix;
+
 #[derive(thiserror::Error, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LexError {
-    #[error("Unexpected token")]
-    Unexpected,
+    /// A character that can't start any token, e.g. a control character or `$`
+    #[error("Unexpected character {0:?}")]
+    UnexpectedChar(char),
+    /// A word that starts with an operator character and contains digits
+    #[error("Operators can't contain digits")]
+    DigitInOperator,
     #[error("Missing whitespace")]
     NoWs,
     #[error("Unexpected whitespace")]
     Ws,
 
+    /// A number literal without digits after the radix prefix or sign, e.g. `0x`
+    #[error("Missing digits in number literal")]
+    MissingDigits,
+    /// An exponent without digits, e.g. `1e+`, or a hexadecimal or binary float
+    /// without exponent, e.g. `0x1.8`
+    #[error("Missing exponent in number literal")]
+    MissingExponent,
+    /// A number literal that is malformed in another way
     #[error("Invalid number token")]
     InvalidNum,
     #[error("Number too large")]
     NumberOverflow,
+    /// An integer without suffix that doesn't fit into an `i64`, or into a `u64`
+    /// if it has a `+` sign
+    #[error("Integer too large for {}", if *signed { "i64" } else { "u64" })]
+    IntOverflow { radix: u32, signed: bool },
     #[error("Invalid char {0:?} in number literal")]
     InvalidCharInNum(char),
+    #[error("The suffix `{0}` is not allowed on this number literal")]
+    InvalidNumSuffix(NumberSuffix),
+
+    #[error("Invalid escape sequence `\\{0}` in string literal")]
+    InvalidEscape(char),
+    #[error("Unterminated string literal")]
+    UnterminatedString,
+    #[error("Char literal must contain exactly one character")]
+    InvalidChar,
 }
 
 #[cfg(feature = "fuzz")]
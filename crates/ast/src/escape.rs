@@ -0,0 +1,97 @@
+use std::str::Chars;
+
+use tinystring::TinyString;
+
+use crate::LexError;
+
+/// Resolves the escape sequences in the content of a string or char literal
+/// (i.e. the text between the quotes).
+///
+/// Supported escapes are `\n`, `\r`, `\t`, `\0`, `\\`, `\"`, `\'`, `\xHH`, where
+/// `HH` is an ASCII character code (at most `7F`), and `\u{...}`, where the
+/// braces contain 1 to 6 hexadecimal digits.
+///
+/// If an escape sequence is invalid, the returned error contains the character
+/// after the backslash, or the backslash itself if it is the last character.
+pub fn unescape(raw: &str) -> Result<TinyString, LexError> {
+    if !raw.contains('\\') {
+        return Ok(TinyString::from(raw));
+    }
+    let mut result = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('x') => hex_escape(&mut chars).ok_or(LexError::InvalidEscape('x'))?,
+            Some('u') => unicode_escape(&mut chars).ok_or(LexError::InvalidEscape('u'))?,
+            Some(c) => return Err(LexError::InvalidEscape(c)),
+            None => return Err(LexError::InvalidEscape('\\')),
+        };
+        result.push(escaped);
+    }
+    Ok(TinyString::from(result))
+}
+
+/// Parses the `HH` part of a `\xHH` escape
+fn hex_escape(chars: &mut Chars<'_>) -> Option<char> {
+    let mut code = 0;
+    for _ in 0..2 {
+        code = code * 16 + chars.next()?.to_digit(16)?;
+    }
+    if code > 0x7F {
+        return None;
+    }
+    Some(code as u8 as char)
+}
+
+/// Parses the `{...}` part of a `\u{...}` escape
+fn unicode_escape(chars: &mut Chars<'_>) -> Option<char> {
+    if chars.next() != Some('{') {
+        return None;
+    }
+    let mut code = 0u32;
+    let mut digits = 0;
+    loop {
+        match chars.next()? {
+            '}' if digits > 0 => break,
+            c if digits < 6 => {
+                code = code * 16 + c.to_digit(16)?;
+                digits += 1;
+            }
+            _ => return None,
+        }
+    }
+    std::char::from_u32(code)
+}
+
+/// The inverse of [`unescape`]: Escapes backslashes, quotes and the control
+/// characters that have a short escape sequence
+pub(crate) fn escape(value: &str) -> TinyString {
+    if !value.contains(['\\', '"', '\n', '\r', '\t', '\0']) {
+        return TinyString::from(value);
+    }
+    let mut result = String::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' => result.push_str("\\\""),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            '\0' => result.push_str("\\0"),
+            c => result.push(c),
+        }
+    }
+    TinyString::from(result)
+}
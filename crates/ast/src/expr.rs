@@ -11,19 +11,33 @@ pub enum Expr {
     Invokable(Invokable),
     Literal(Literal),
     ParenCall(ParenCall),
+    Index(IndexExpr),
     MemberCall(MemberCall),
     Operation(Operation),
     ShortcircuitingOp(ScOperation),
+    UnaryOp(UnaryOp),
     Assignment(Assignment),
     TypeAscription(TypeAscription),
     Statement(Box<Spanned<Expr>>),
+    /// Error propagation with a postfix `?`, e.g. `foo()?`
+    Try(Box<Spanned<Expr>>),
     Lambda(Lambda),
     Block(Block),
     Tuple(Parens),
+    ArrayLit(ArrayLit),
     Empty(Empty),
 
     Declaration(Declaration),
     Match(Match),
+    While(While),
+    For(For),
+    IfExpr(IfExpr),
+    /// `return`, optionally followed by the returned value
+    Return(Option<Box<Spanned<Expr>>>),
+    /// `break`, optionally followed by the label of the loop to exit
+    Break(Option<Spanned<Ident>>),
+    /// `continue`, optionally followed by the label of the loop to continue
+    Continue(Option<Spanned<Ident>>),
 }
 
 impl Expr {
@@ -42,18 +56,28 @@ impl Expr {
             Expr::Invokable(_) => ExprKind::Invokable,
             Expr::Literal(_) => ExprKind::Literal,
             Expr::ParenCall(_) => ExprKind::ParenCall,
+            Expr::Index(_) => ExprKind::Index,
             Expr::MemberCall(_) => ExprKind::MemberCall,
             Expr::Operation(_) => ExprKind::Operation,
             Expr::ShortcircuitingOp(_) => ExprKind::ShortcircuitingOp,
+            Expr::UnaryOp(_) => ExprKind::UnaryOp,
             Expr::Assignment(_) => ExprKind::Assignment,
             Expr::TypeAscription(_) => ExprKind::TypeAscription,
             Expr::Statement(_) => ExprKind::Statement,
+            Expr::Try(_) => ExprKind::Try,
             Expr::Lambda(_) => ExprKind::Lambda,
             Expr::Block(_) => ExprKind::Block,
             Expr::Tuple(_) => ExprKind::Tuple,
+            Expr::ArrayLit(_) => ExprKind::ArrayLit,
             Expr::Empty(_) => ExprKind::Empty,
             Expr::Declaration(_) => ExprKind::Declaration,
             Expr::Match(_) => ExprKind::Match,
+            Expr::While(_) => ExprKind::While,
+            Expr::For(_) => ExprKind::For,
+            Expr::IfExpr(_) => ExprKind::IfExpr,
+            Expr::Return(_) => ExprKind::Return,
+            Expr::Break(_) => ExprKind::Break,
+            Expr::Continue(_) => ExprKind::Continue,
         }
     }
 }
@@ -63,18 +87,28 @@ pub enum ExprKind {
     Invokable,
     Literal,
     ParenCall,
+    Index,
     MemberCall,
     Operation,
     ShortcircuitingOp,
+    UnaryOp,
     Assignment,
     TypeAscription,
     Statement,
+    Try,
     Lambda,
     Block,
     Tuple,
+    ArrayLit,
     Empty,
     Declaration,
     Match,
+    While,
+    For,
+    IfExpr,
+    Return,
+    Break,
+    Continue,
 }
 
 #[derive(Debug, Clone)]
@@ -87,6 +121,7 @@ pub struct Invokable {
 pub enum Literal {
     NumberLit(NumberLiteral),
     StringLit(StringLiteral),
+    CharLit(char),
 }
 
 impl fmt::Debug for Literal {
@@ -94,6 +129,7 @@ impl fmt::Debug for Literal {
         match self {
             Literal::NumberLit(x) => write!(f, "{:?}", x),
             Literal::StringLit(x) => write!(f, "{:?}", x),
+            Literal::CharLit(x) => write!(f, "CharLiteral {:?}", x),
         }
     }
 }
@@ -102,6 +138,21 @@ impl fmt::Debug for Literal {
 pub struct ParenCall {
     pub receiver: Box<Spanned<Expr>>,
     pub args: Option<SpannedList<FunCallArgument>>,
+    /// Whether the arguments end with a comma, e.g. `f(a,)`
+    pub trailing_comma: bool,
+}
+
+/// Indexing with square brackets, e.g. `a[i, j]`
+#[derive(Debug, Clone)]
+pub struct IndexExpr {
+    pub receiver: Box<Spanned<Expr>>,
+    pub indices: SpannedList<Expr>,
+}
+
+/// An array literal, e.g. `[1, 2, 3]`
+#[derive(Debug, Clone)]
+pub struct ArrayLit {
+    pub elements: SpannedList<Expr>,
 }
 
 #[derive(Debug, Clone)]
@@ -138,6 +189,18 @@ pub enum ScOperator {
     Or,
 }
 
+/// A prefix operation, e.g. `not a`
+#[derive(Debug, Clone)]
+pub struct UnaryOp {
+    pub operator: UnaryOperator,
+    pub operand: Box<Spanned<Expr>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOperator {
+    Not,
+}
+
 /// 56 bytes
 #[derive(Debug, Clone)]
 pub struct TypeAscription {
@@ -160,6 +223,9 @@ pub struct Block {
 #[derive(Debug, Clone)]
 pub struct Parens {
     pub exprs: SpannedList<FunCallArgument>,
+    /// Whether the list ends with a comma, e.g. `(a,)`. This distinguishes a
+    /// tuple with one element from an expression in parentheses.
+    pub trailing_comma: bool,
 }
 
 impl Parens {
@@ -182,12 +248,52 @@ pub struct Match {
     pub match_arms: SpannedList<MatchArm>,
 }
 
+#[derive(Debug, Clone)]
+pub struct While {
+    pub label: Option<Spanned<Ident>>,
+    pub condition: Box<Spanned<Expr>>,
+    pub body: Spanned<Block>,
+}
+
+#[derive(Debug, Clone)]
+pub struct For {
+    pub label: Option<Spanned<Ident>>,
+    pub binding: Box<Spanned<Invokable>>,
+    pub iterable: Box<Spanned<Expr>>,
+    pub body: Spanned<Block>,
+}
+
+/// `if condition { ... } else ...`, where the `else` branch is either a block or
+/// another `IfExpr`
+#[derive(Debug, Clone)]
+pub struct IfExpr {
+    pub condition: Box<Spanned<Expr>>,
+    pub then_branch: Spanned<Block>,
+    pub else_branch: Option<Box<Spanned<Expr>>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct FunCallArgument {
-    pub name: Option<Spanned<Ident>>,
+    pub name: Option<Spanned<ArgName>>,
     pub expr: Spanned<Expr>,
 }
 
+#[derive(Debug, Clone)]
+pub enum ArgName {
+    /// `name: expr`
+    Named(Ident),
+    /// `.field = expr`, used to construct records
+    RecordArg(Ident),
+}
+
+impl ArgName {
+    pub fn ident(&self) -> &Ident {
+        match self {
+            ArgName::Named(i) | ArgName::RecordArg(i) => i,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum DeclKind {
     Let,
@@ -3,7 +3,7 @@ use std::fmt;
 use crate::expr::{Block, Expr};
 use crate::name::{Ident, Operator};
 use crate::token::UpperIdent;
-use crate::{Spanned, SpannedList};
+use crate::{Spanned, SpannedList, TinyString};
 
 #[derive(Debug, Clone)]
 pub enum Item {
@@ -12,6 +12,10 @@ pub enum Item {
     Enum(Enum),
     Impl(Impl),
     Use(Use),
+    TypeAlias(TypeAlias),
+    /// A placeholder for tokens that couldn't be parsed as an item. It is only
+    /// produced by error recovery.
+    Error,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -21,6 +25,8 @@ pub enum ItemKind {
     Enum,
     Impl,
     Use,
+    TypeAlias,
+    Error,
 }
 
 impl Item {
@@ -31,12 +37,38 @@ impl Item {
             Item::Enum(_) => ItemKind::Enum,
             Item::Impl(_) => ItemKind::Impl,
             Item::Use(_) => ItemKind::Use,
+            Item::TypeAlias(_) => ItemKind::TypeAlias,
+            Item::Error => ItemKind::Error,
+        }
+    }
+
+    /// Returns the attributes of the item, e.g. `@cfg(test)`
+    pub fn attrs(&self) -> &[Spanned<Attribute>] {
+        match self {
+            Item::Function(f) => &f.attrs,
+            Item::Class(c) => &c.attrs,
+            Item::Enum(e) => &e.attrs,
+            Item::Impl(i) => &i.attrs,
+            Item::Use(u) => &u.attrs,
+            Item::TypeAlias(t) => &t.attrs,
+            Item::Error => &[],
         }
     }
 }
 
+/// An attribute before an item
+#[derive(Debug, Clone)]
+pub enum Attribute {
+    /// `@cfg(flag)`, which disables the item unless the flag is active
+    Cfg(Spanned<Ident>),
+}
+
+/// A type name with optional type arguments, e.g. `Map[K, V]`. It can be
+/// qualified with a module path, e.g. `std.collections.Map[K, V]`.
 #[derive(Debug, Clone)]
 pub struct NamedType {
+    /// The modules before the type name, e.g. `std` and `collections`
+    pub path: SpannedList<Ident>,
     pub name: Spanned<UpperIdent>,
     pub args: Spanned<SpannedList<TypeArgument>>,
 }
@@ -53,7 +85,40 @@ pub struct Function {
     pub generics: Spanned<SpannedList<GenericParam>>,
     pub args: Spanned<SpannedList<FunArgument>>,
     pub return_ty: Option<Spanned<NamedType>>,
+    pub where_clause: Option<Spanned<WhereClause>>,
     pub body: Option<Spanned<Block>>,
+    pub docs: Option<Vec<TinyString>>,
+    pub attrs: Vec<Spanned<Attribute>>,
+}
+
+impl Function {
+    /// Returns the bounds of a generic parameter, both from the parameter itself
+    /// and from the `where` clause
+    pub fn bounds_of<'a>(
+        &'a self,
+        param: &'a UpperIdent,
+    ) -> impl Iterator<Item = &'a Spanned<TypeBound>> {
+        let generics = self.generics.iter().filter(move |g| g.name.inner == *param);
+        let where_bounds = self.where_clause.iter().flat_map(|w| w.bounds.iter());
+        let where_bounds = where_bounds.filter(move |b| b.param.inner == *param);
+        generics
+            .flat_map(|g| g.bounds.iter())
+            .chain(where_bounds.flat_map(|b| b.bounds.iter()))
+    }
+}
+
+/// `where T: Clone, U: Debug`, which specifies bounds of generic parameters
+/// separately from their declaration
+#[derive(Debug, Clone)]
+pub struct WhereClause {
+    pub bounds: SpannedList<WhereBound>,
+}
+
+/// The bounds of a generic parameter in a `where` clause, e.g. `T: Clone + Debug`
+#[derive(Debug, Clone)]
+pub struct WhereBound {
+    pub param: Spanned<UpperIdent>,
+    pub bounds: SpannedList<TypeBound>,
 }
 
 #[derive(Clone)]
@@ -85,6 +150,8 @@ pub struct Class {
     pub name: Spanned<UpperIdent>,
     pub generics: Spanned<SpannedList<GenericParam>>,
     pub fields: Spanned<SpannedList<ClassField>>,
+    pub docs: Option<Vec<TinyString>>,
+    pub attrs: Vec<Spanned<Attribute>>,
 }
 
 #[derive(Debug, Clone)]
@@ -93,9 +160,10 @@ pub struct GenericParam {
     pub bounds: SpannedList<TypeBound>,
 }
 
+/// A type that a generic parameter must implement, e.g. `Clone` in `[T Clone]`
 #[derive(Debug, Clone)]
-pub enum TypeBound {
-    // TODO: Interface/trait/contract/superclass
+pub struct TypeBound {
+    pub ty: NamedType,
 }
 
 #[derive(Debug, Clone)]
@@ -110,6 +178,8 @@ pub struct Enum {
     pub name: Spanned<UpperIdent>,
     pub generics: Spanned<SpannedList<GenericParam>>,
     pub variants: Spanned<SpannedList<EnumVariant>>,
+    pub docs: Option<Vec<TinyString>>,
+    pub attrs: Vec<Spanned<Attribute>>,
 }
 
 #[derive(Debug, Clone)]
@@ -124,10 +194,23 @@ pub struct Impl {
     pub r#trait: Option<Spanned<NamedType>>,
     pub ty: Spanned<NamedType>,
     pub items: Spanned<SpannedList<Item>>,
+    pub docs: Option<Vec<TinyString>>,
+    pub attrs: Vec<Spanned<Attribute>>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Use {
     pub path: Spanned<SpannedList<Name>>,
     pub wildcard: Option<Spanned<()>>,
+    pub docs: Option<Vec<TinyString>>,
+    pub attrs: Vec<Spanned<Attribute>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeAlias {
+    pub name: Spanned<UpperIdent>,
+    pub generics: Spanned<SpannedList<GenericParam>>,
+    pub ty: Spanned<NamedType>,
+    pub docs: Option<Vec<TinyString>>,
+    pub attrs: Vec<Spanned<Attribute>>,
 }
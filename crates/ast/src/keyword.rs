@@ -10,6 +10,7 @@ pub enum Keyword {
     Enum,
     Impl,
     Use,
+    Where,
 
     // Expressions
     Let,
@@ -19,6 +20,13 @@ pub enum Keyword {
     Or,
     Not,
     For,
+    In,
+    While,
+    If,
+    Else,
+    Return,
+    Break,
+    Continue,
 }
 
 impl fmt::Display for Keyword {
@@ -30,6 +38,7 @@ impl fmt::Display for Keyword {
             Keyword::Enum => "enum",
             Keyword::Impl => "impl",
             Keyword::Use => "use",
+            Keyword::Where => "where",
             Keyword::Let => "let",
             Keyword::Var => "var",
             Keyword::Match => "match",
@@ -37,6 +46,13 @@ impl fmt::Display for Keyword {
             Keyword::Or => "or",
             Keyword::Not => "not",
             Keyword::For => "for",
+            Keyword::In => "in",
+            Keyword::While => "while",
+            Keyword::If => "if",
+            Keyword::Else => "else",
+            Keyword::Return => "return",
+            Keyword::Break => "break",
+            Keyword::Continue => "continue",
         })
     }
 }
@@ -1,8 +1,13 @@
+mod beauty;
 mod error;
+mod escape;
 mod keyword;
+mod line_index;
 mod literal;
 mod name;
 mod punct;
+#[cfg(feature = "serde")]
+mod serde_impls;
 mod spanned;
 mod text_range;
 
@@ -10,8 +15,15 @@ pub mod expr;
 pub mod item;
 pub mod pattern;
 pub mod token;
+pub mod visit;
 
+#[cfg(test)]
+mod tests;
+
+pub use beauty::Beauty;
 pub use error::LexError;
+pub use escape::unescape;
+pub use line_index::LineIndex;
 pub use spanned::Spanned;
 pub use text_range::TextRange;
 
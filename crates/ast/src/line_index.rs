@@ -0,0 +1,50 @@
+use crate::TextRange;
+
+/// Converts byte offsets in a source text to lines and columns. Only the line
+/// starts are stored, so the text is passed to each lookup. To get the text of a
+/// span, index the text with it, e.g. `&text[span]`.
+///
+/// Lines and columns start at 1. Columns are counted in characters, not bytes.
+/// Both `\n` and `\r\n` are recognized as line breaks.
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    /// The byte offset where each line starts; the first line starts at 0
+    line_starts: Vec<u32>,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(i, _)| i as u32 + 1));
+        LineIndex { line_starts }
+    }
+
+    /// Returns the line and column of a byte offset. An offset within a
+    /// multi-byte character has the column of that character. The end of the
+    /// text is a valid position. `text` must be the text this index was built
+    /// from.
+    ///
+    /// Panics if the offset is greater than the length of the text.
+    pub fn line_col(&self, text: &str, offset: u32) -> (u32, u32) {
+        assert!(offset as usize <= text.len(), "offset out of bounds");
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+        let line_start = self.line_starts[line] as usize;
+        let col = text[line_start..]
+            .char_indices()
+            .take_while(|&(i, c)| line_start + i + c.len_utf8() <= offset as usize)
+            .count();
+        (line as u32 + 1, col as u32 + 1)
+    }
+
+    /// Returns the lines and columns of the start and end of a range
+    pub fn range_to_line_cols(
+        &self,
+        text: &str,
+        range: TextRange,
+    ) -> ((u32, u32), (u32, u32)) {
+        (self.line_col(text, range.start()), self.line_col(text, range.end()))
+    }
+}
@@ -1,7 +1,11 @@
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
 use tinystring::TinyString;
 
+use crate::escape::{escape, unescape};
+use crate::LexError;
+
 /// Supported literals are
 ///
 /// * Signed integer (Int)
@@ -34,45 +38,193 @@ use tinystring::TinyString;
 /// FLOAT       := SIGN? DEC_SEQUENCE '.' DEC_SEQUENCE EXPONENT?
 ///              | SIGN? DEC_SEQUENCE EXPONENT
 ///              | '.' DEC_SEQUENCE EXPONENT?
+///
+/// INT_SUFFIX   := 'i8' | 'i16' | 'i32' | 'i64' | 'i128'
+///               | 'u8' | 'u16' | 'u32' | 'u64' | 'u128'
+/// FLOAT_SUFFIX := 'f32' | 'f64'
+/// LITERAL      := (BINARY | OCTAL | HEXADECIMAL | DECIMAL) INT_SUFFIX?
+///               | FLOAT FLOAT_SUFFIX?
 /// ```
-#[derive(Debug, Copy, Clone, PartialEq)]
+///
+/// Unsigned suffixes are not allowed on negative numbers, and signed suffixes
+/// are not allowed on numbers with a `+` sign. A decimal number with an
+/// unsigned suffix is a `UInt`.
+#[derive(Copy, Clone, PartialEq)]
 pub enum NumberLiteral {
-    Int(i64),
-    UInt(u64),
-    Float(f64),
+    Int(i64, Option<NumberSuffix>),
+    UInt(u64, Option<NumberSuffix>),
+    Float(f64, Option<NumberSuffix>),
+}
+
+impl NumberLiteral {
+    pub fn suffix(&self) -> Option<NumberSuffix> {
+        match *self {
+            NumberLiteral::Int(_, s) | NumberLiteral::UInt(_, s) => s,
+            NumberLiteral::Float(_, s) => s,
+        }
+    }
+}
+
+/// Omits the suffix if there is none, e.g. `Int(3)` or `Float(0.5, F32)`
+impl fmt::Debug for NumberLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, value): (_, &dyn fmt::Debug) = match self {
+            NumberLiteral::Int(i, _) => ("Int", i),
+            NumberLiteral::UInt(u, _) => ("UInt", u),
+            NumberLiteral::Float(n, _) => ("Float", n),
+        };
+        let mut t = f.debug_tuple(name);
+        t.field(value);
+        if let Some(suffix) = self.suffix() {
+            t.field(&suffix);
+        }
+        t.finish()
+    }
 }
 
 impl fmt::Display for NumberLiteral {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            NumberLiteral::Int(i) => fmt::Display::fmt(i, f),
-            NumberLiteral::UInt(u) => fmt::Display::fmt(u, f),
-            NumberLiteral::Float(n) => fmt::Display::fmt(n, f),
+            NumberLiteral::Int(i, _) => fmt::Display::fmt(i, f)?,
+            NumberLiteral::UInt(u, _) => fmt::Display::fmt(u, f)?,
+            NumberLiteral::Float(n, _) => fmt::Display::fmt(n, f)?,
+        }
+        match self.suffix() {
+            Some(suffix) => fmt::Display::fmt(&suffix, f),
+            None => Ok(()),
         }
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Hash)]
-pub struct StringLiteral(TinyString);
+/// The type suffix of a number literal, e.g. `u8` in `255u8`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NumberSuffix {
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+}
+
+impl NumberSuffix {
+    pub const ALL: [NumberSuffix; 12] = {
+        use NumberSuffix::*;
+        [I8, I16, I32, I64, I128, U8, U16, U32, U64, U128, F32, F64]
+    };
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NumberSuffix::I8 => "i8",
+            NumberSuffix::I16 => "i16",
+            NumberSuffix::I32 => "i32",
+            NumberSuffix::I64 => "i64",
+            NumberSuffix::I128 => "i128",
+            NumberSuffix::U8 => "u8",
+            NumberSuffix::U16 => "u16",
+            NumberSuffix::U32 => "u32",
+            NumberSuffix::U64 => "u64",
+            NumberSuffix::U128 => "u128",
+            NumberSuffix::F32 => "f32",
+            NumberSuffix::F64 => "f64",
+        }
+    }
+
+    pub fn is_signed(self) -> bool { self.as_str().starts_with('i') }
+
+    pub fn is_unsigned(self) -> bool { self.as_str().starts_with('u') }
+
+    pub fn is_float(self) -> bool { self.as_str().starts_with('f') }
+}
+
+impl fmt::Display for NumberSuffix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A string literal. It contains both the decoded value, where escape sequences
+/// are resolved, and the raw text between the quotes as it appears in the
+/// source code. Only the decoded value is compared and hashed.
+///
+/// Supported escape sequences are documented in [`unescape`](crate::unescape).
+/// Raw string literals, written as `r"..."`, don't support escape sequences, so
+/// their value is the same as their raw text.
+#[derive(Clone)]
+pub struct StringLiteral {
+    value: TinyString,
+    raw: TinyString,
+    is_raw: bool,
+}
 
 
 impl StringLiteral {
-    pub fn new(string: impl Into<TinyString>) -> Self { Self(string.into()) }
+    /// Creates a string literal from its value. The raw text is generated by
+    /// escaping backslashes, quotes and control characters.
+    pub fn new(string: impl Into<TinyString>) -> Self {
+        let value = string.into();
+        Self { raw: escape(&value), value, is_raw: false }
+    }
+
+    /// Creates a string literal from its decoded value and its raw text
+    pub fn with_raw(value: impl Into<TinyString>, raw: impl Into<TinyString>) -> Self {
+        Self { value: value.into(), raw: raw.into(), is_raw: false }
+    }
+
+    /// Creates a raw string literal, where backslashes have no special meaning
+    pub fn new_raw(string: impl Into<TinyString>) -> Self {
+        let value = string.into();
+        Self { raw: value.clone(), value, is_raw: true }
+    }
+
+    /// Returns the decoded value
+    pub fn get(&self) -> &str { &self.value }
+
+    /// Returns the text between the quotes, as it appears in the source code
+    pub fn raw(&self) -> &str { &self.raw }
+
+    /// Returns `true` if this is a raw string literal
+    pub fn is_raw(&self) -> bool { self.is_raw }
+
+    pub fn inner(&self) -> TinyString { self.value.clone() }
+
+    /// Resolves the escape sequences in the raw text, unless this is a raw
+    /// string literal. For literals produced by the lexer, this is the same as
+    /// [`StringLiteral::get`].
+    pub fn decoded(&self) -> Result<TinyString, LexError> {
+        if self.is_raw {
+            Ok(self.raw.clone())
+        } else {
+            unescape(&self.raw)
+        }
+    }
+}
+
+impl PartialEq for StringLiteral {
+    fn eq(&self, other: &Self) -> bool { self.value == other.value }
+}
 
-    pub fn get(&self) -> &str { &*self.0 }
+impl Eq for StringLiteral {}
 
-    pub fn inner(&self) -> TinyString { self.0.clone() }
+impl Hash for StringLiteral {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.value.hash(state) }
 }
 
 impl fmt::Display for StringLiteral {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Display::fmt(&self.0, f)
+        fmt::Display::fmt(&self.value, f)
     }
 }
 
 impl fmt::Debug for StringLiteral {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "StringLiteral {:?}", &self.0)
+        write!(f, "StringLiteral {:?}", &self.value)
     }
 }
 
@@ -80,7 +232,7 @@ impl fmt::Debug for StringLiteral {
 #[cfg(feature = "fuzz")]
 impl arbitrary::Arbitrary for StringLiteral {
     fn arbitrary(_: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
-        Ok(StringLiteral(TinyString::from("\"s\"")))
+        Ok(StringLiteral::new("s"))
     }
 }
 
@@ -95,9 +247,9 @@ impl arbitrary::Arbitrary for NumberLiteral {
         }
 
         Ok(match u.arbitrary::<ArbitraryNumerLit>()? {
-            ArbitraryNumerLit::Int => NumberLiteral::Int(42),
-            ArbitraryNumerLit::UInt => NumberLiteral::UInt(41),
-            ArbitraryNumerLit::Float => NumberLiteral::Float(40.0),
+            ArbitraryNumerLit::Int => NumberLiteral::Int(42, None),
+            ArbitraryNumerLit::UInt => NumberLiteral::UInt(41, None),
+            ArbitraryNumerLit::Float => NumberLiteral::Float(40.0, None),
         })
     }
 }
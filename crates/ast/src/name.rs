@@ -1,3 +1,4 @@
+use std::convert::TryFrom;
 use std::fmt;
 
 use tinystring::TinyString;
@@ -32,29 +33,95 @@ pub struct UpperIdent(TinyString);
 
 
 impl Ident {
+    /// Creates a new `Ident` without validating it. Use `Ident::try_from` if
+    /// the string isn't known to be valid.
     pub fn new(string: impl Into<TinyString>) -> Self { Self(string.into()) }
 
-    pub fn get(&self) -> &str { &*self.0 }
+    pub fn get(&self) -> &str { &self.0 }
 
     pub fn inner(&self) -> TinyString { self.0.clone() }
 }
 
 impl Operator {
+    /// Creates a new `Operator` without validating it. Use `Operator::try_from` if
+    /// the string isn't known to be valid.
     pub fn new(string: impl Into<TinyString>) -> Self { Self(string.into()) }
 
-    pub fn get(&self) -> &str { &*self.0 }
+    pub fn get(&self) -> &str { &self.0 }
 
     pub fn inner(&self) -> TinyString { self.0.clone() }
 }
 
 impl UpperIdent {
+    /// Creates a new `UpperIdent` without validating it. Use `UpperIdent::try_from` if
+    /// the string isn't known to be valid.
     pub fn new(string: impl Into<TinyString>) -> Self { Self(string.into()) }
 
-    pub fn get(&self) -> &str { &*self.0 }
+    pub fn get(&self) -> &str { &self.0 }
 
     pub fn inner(&self) -> TinyString { self.0.clone() }
 }
 
+/// The error returned when trying to create an [`Ident`], [`Operator`] or
+/// [`UpperIdent`] from a string that doesn't fulfill its criteria.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("{name:?} is not a valid {kind}")]
+pub struct InvalidName {
+    pub kind: &'static str,
+    pub name: TinyString,
+}
+
+fn is_operator_char(c: char) -> bool { "+-*/%~<>=?!".contains(c) }
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || is_operator_char(c)
+}
+
+fn validate(
+    s: &str,
+    kind: &'static str,
+    is_valid_start: impl Fn(char) -> bool,
+    is_valid_char: impl Fn(char) -> bool,
+) -> Result<TinyString, InvalidName> {
+    match s.chars().next() {
+        Some(c) if is_valid_start(c) && s.chars().all(is_valid_char) => {
+            Ok(TinyString::from(s))
+        }
+        _ => Err(InvalidName { kind, name: TinyString::from(s) }),
+    }
+}
+
+impl TryFrom<&str> for Ident {
+    type Error = InvalidName;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        validate(s, "identifier", |c| c.is_ascii_lowercase(), is_name_char).map(Ident)
+    }
+}
+
+impl TryFrom<&str> for UpperIdent {
+    type Error = InvalidName;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        validate(s, "type name", |c| c.is_ascii_uppercase(), is_name_char)
+            .map(UpperIdent)
+    }
+}
+
+impl TryFrom<&str> for Operator {
+    type Error = InvalidName;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        if s == "=" {
+            return Err(InvalidName { kind: "operator", name: TinyString::from(s) });
+        }
+        validate(s, "operator", is_operator_char, |c| {
+            c.is_ascii_alphabetic() || c == '_' || is_operator_char(c)
+        })
+        .map(Operator)
+    }
+}
+
 impl fmt::Debug for Ident {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Ident {}", &self.0)
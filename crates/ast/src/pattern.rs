@@ -6,6 +6,7 @@ pub enum Pattern {
     Wildcard,
     Binding(Ident),
     Literal(Literal),
+    Tuple(Vec<Pattern>),
     Range(RangePattern),
     RangeExclusive(RangePattern),
     Class(ClassPattern),
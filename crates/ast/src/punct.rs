@@ -13,6 +13,7 @@ pub enum Punctuation {
     Backslash,
     At,
     Underscore,
+    QuestionMark,
 
     OpenParen,
     CloseParen,
@@ -35,6 +36,7 @@ impl fmt::Display for Punctuation {
             Punctuation::Backslash => "\\",
             Punctuation::At => "@",
             Punctuation::Underscore => "_",
+            Punctuation::QuestionMark => "?",
             Punctuation::OpenParen => "(",
             Punctuation::CloseParen => ")",
             Punctuation::OpenBracket => "[",
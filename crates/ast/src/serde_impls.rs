@@ -0,0 +1,539 @@
+//! `Serialize` and `Deserialize` impls, enabled with the `serde` feature.
+//!
+//! Enums are externally tagged, e.g. `{"Ident": "foo"}` or `"Eof"`, like
+//! serde's derive macros would do. Punctuation, keywords and names are
+//! serialized as they appear in the source code.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess};
+use serde::ser::{SerializeStruct, SerializeStructVariant};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::token::{
+    Ident, Keyword, NumberLiteral, NumberSuffix, Operator, Punctuation, StringLiteral,
+    Token, UpperIdent,
+};
+use crate::{LexError, Spanned, TextRange, TinyString};
+
+impl Serialize for TextRange {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("TextRange", 2)?;
+        s.serialize_field("start", &self.start())?;
+        s.serialize_field("end", &self.end())?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for TextRange {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        const FIELDS: &[&str] = &["start", "end"];
+        let visitor = StructVisitor::<u32, u32>::new(FIELDS);
+        let (start, end) =
+            deserializer.deserialize_struct("TextRange", FIELDS, visitor)?;
+        if start > end {
+            return Err(de::Error::custom(
+                "the start of a range must not exceed its end",
+            ));
+        }
+        Ok(TextRange::new(start, end))
+    }
+}
+
+impl<T: Serialize> Serialize for Spanned<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Spanned", 2)?;
+        s.serialize_field("inner", &self.inner)?;
+        s.serialize_field("span", &self.span)?;
+        s.end()
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Spanned<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        const FIELDS: &[&str] = &["inner", "span"];
+        let visitor = StructVisitor::<T, TextRange>::new(FIELDS);
+        let (inner, span) =
+            deserializer.deserialize_struct("Spanned", FIELDS, visitor)?;
+        Ok(Spanned::new(inner, span))
+    }
+}
+
+/// Deserializes a struct with two fields
+struct StructVisitor<A, B> {
+    fields: &'static [&'static str],
+    marker: PhantomData<(A, B)>,
+}
+
+impl<A, B> StructVisitor<A, B> {
+    fn new(fields: &'static [&'static str]) -> Self {
+        StructVisitor { fields, marker: PhantomData }
+    }
+}
+
+impl<'de, A: Deserialize<'de>, B: Deserialize<'de>> de::Visitor<'de>
+    for StructVisitor<A, B>
+{
+    type Value = (A, B);
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a struct with the fields `{}` and `{}`",
+            self.fields[0], self.fields[1]
+        )
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<(A, B), S::Error> {
+        let a = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let b = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok((a, b))
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<(A, B), M::Error> {
+        let (mut a, mut b) = (None, None);
+        while let Some(key) = map.next_key::<TinyString>()? {
+            if *key == *self.fields[0] && a.is_none() {
+                a = Some(map.next_value()?);
+            } else if *key == *self.fields[1] && b.is_none() {
+                b = Some(map.next_value()?);
+            } else {
+                return Err(de::Error::unknown_field(&key, self.fields));
+            }
+        }
+        let a = a.ok_or_else(|| de::Error::missing_field(self.fields[0]))?;
+        let b = b.ok_or_else(|| de::Error::missing_field(self.fields[1]))?;
+        Ok((a, b))
+    }
+}
+
+/// Types that are serialized as a string, e.g. `Punctuation::Comma` as `","`
+trait TextRepr: Sized + fmt::Display {
+    const EXPECTING: &'static str;
+
+    fn from_text(text: &str) -> Option<Self>;
+}
+
+impl TextRepr for Punctuation {
+    const EXPECTING: &'static str = "a punctuation character";
+
+    fn from_text(text: &str) -> Option<Self> {
+        use Punctuation::*;
+        let all = [
+            Dot,
+            Comma,
+            Colon,
+            Semicolon,
+            Equals,
+            And,
+            Pipe,
+            Backslash,
+            At,
+            Underscore,
+            QuestionMark,
+            OpenParen,
+            CloseParen,
+            OpenBracket,
+            CloseBracket,
+            OpenBrace,
+            CloseBrace,
+        ];
+        all.iter().copied().find(|p| p.to_string() == text)
+    }
+}
+
+impl TextRepr for Keyword {
+    const EXPECTING: &'static str = "a keyword";
+
+    fn from_text(text: &str) -> Option<Self> {
+        use Keyword::*;
+        let all = [
+            Fun, Type, Class, Enum, Impl, Use, Where, Let, Var, Match, And, Or, Not, For,
+            In, While, If, Else, Return, Break, Continue,
+        ];
+        all.iter().copied().find(|k| k.to_string() == text)
+    }
+}
+
+impl TextRepr for Ident {
+    const EXPECTING: &'static str = "an identifier";
+
+    fn from_text(text: &str) -> Option<Self> { Ident::try_from(text).ok() }
+}
+
+impl TextRepr for UpperIdent {
+    const EXPECTING: &'static str = "a type name";
+
+    fn from_text(text: &str) -> Option<Self> { UpperIdent::try_from(text).ok() }
+}
+
+impl TextRepr for Operator {
+    const EXPECTING: &'static str = "an operator";
+
+    fn from_text(text: &str) -> Option<Self> { Operator::try_from(text).ok() }
+}
+
+impl TextRepr for NumberSuffix {
+    const EXPECTING: &'static str = "a number suffix";
+
+    fn from_text(text: &str) -> Option<Self> {
+        NumberSuffix::ALL.iter().copied().find(|s| s.as_str() == text)
+    }
+}
+
+macro_rules! serde_text_repr {
+    ($($t:ty),*) => {
+        $(
+            impl Serialize for $t {
+                fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                    s.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $t {
+                fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                    d.deserialize_str(TextVisitor(PhantomData))
+                }
+            }
+        )*
+    };
+}
+
+serde_text_repr!(Punctuation, Keyword, Ident, UpperIdent, Operator, NumberSuffix);
+
+struct TextVisitor<T>(PhantomData<T>);
+
+impl<'de, T: TextRepr> de::Visitor<'de> for TextVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(T::EXPECTING)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        T::from_text(v).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+/// The decoded value, the raw text and whether it is a raw string literal
+impl Serialize for StringLiteral {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.get(), self.raw(), self.is_raw()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for StringLiteral {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (value, raw, is_raw) =
+            <(TinyString, TinyString, bool)>::deserialize(deserializer)?;
+        Ok(if is_raw {
+            StringLiteral::new_raw(value)
+        } else {
+            StringLiteral::with_raw(value, raw)
+        })
+    }
+}
+
+/// Enums that are serialized like `{"Variant": content}`, or `"Variant"` if the
+/// variant has no content
+trait TaggedEnum: Sized {
+    const NAME: &'static str;
+    const VARIANTS: &'static [&'static str];
+
+    fn from_variant<'de, A: VariantAccess<'de>>(
+        variant: &'static str,
+        content: A,
+    ) -> Result<Self, A::Error>;
+}
+
+/// Serializes a variant with content
+fn newtype_variant<S: Serializer, E: TaggedEnum, T: Serialize>(
+    serializer: S,
+    variant: &'static str,
+    content: &T,
+) -> Result<S::Ok, S::Error> {
+    let index = E::VARIANTS.iter().position(|&v| v == variant).unwrap() as u32;
+    serializer.serialize_newtype_variant(E::NAME, index, variant, content)
+}
+
+/// Serializes a variant without content
+fn unit_variant<S: Serializer, E: TaggedEnum>(
+    serializer: S,
+    variant: &'static str,
+) -> Result<S::Ok, S::Error> {
+    let index = E::VARIANTS.iter().position(|&v| v == variant).unwrap() as u32;
+    serializer.serialize_unit_variant(E::NAME, index, variant)
+}
+
+fn deserialize_enum<'de, D: Deserializer<'de>, E: TaggedEnum>(
+    deserializer: D,
+) -> Result<E, D::Error> {
+    deserializer.deserialize_enum(E::NAME, E::VARIANTS, EnumVisitor(PhantomData))
+}
+
+struct EnumVisitor<E>(PhantomData<E>);
+
+impl<'de, E: TaggedEnum> de::Visitor<'de> for EnumVisitor<E> {
+    type Value = E;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a {} variant", E::NAME)
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<E, A::Error> {
+        let (variant, content) = data.variant_seed(VariantName(E::VARIANTS))?;
+        E::from_variant(variant, content)
+    }
+}
+
+/// Deserializes the name or index of a variant
+struct VariantName(&'static [&'static str]);
+
+impl<'de> DeserializeSeed<'de> for VariantName {
+    type Value = &'static str;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> de::Visitor<'de> for VariantName {
+    type Value = &'static str;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a variant name")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<&'static str, E> {
+        self.0
+            .iter()
+            .copied()
+            .find(|&name| name == v)
+            .ok_or_else(|| E::unknown_variant(v, self.0))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<&'static str, E> {
+        match self.0.get(v as usize) {
+            Some(&name) => Ok(name),
+            None => Err(E::invalid_value(de::Unexpected::Unsigned(v), &self)),
+        }
+    }
+}
+
+impl TaggedEnum for Token {
+    const NAME: &'static str = "Token";
+    const VARIANTS: &'static [&'static str] = &[
+        "Punct",
+        "StringLit",
+        "CharLit",
+        "NumberLit",
+        "Ident",
+        "Label",
+        "UpperIdent",
+        "Operator",
+        "Keyword",
+        "DocComment",
+        "Error",
+        "Eof",
+    ];
+
+    fn from_variant<'de, A: VariantAccess<'de>>(
+        variant: &'static str,
+        content: A,
+    ) -> Result<Self, A::Error> {
+        Ok(match variant {
+            "Punct" => Token::Punct(content.newtype_variant()?),
+            "StringLit" => Token::StringLit(content.newtype_variant()?),
+            "CharLit" => Token::CharLit(content.newtype_variant()?),
+            "NumberLit" => Token::NumberLit(content.newtype_variant()?),
+            "Ident" => Token::Ident(content.newtype_variant()?),
+            "Label" => Token::Label(content.newtype_variant()?),
+            "UpperIdent" => Token::UpperIdent(content.newtype_variant()?),
+            "Operator" => Token::Operator(content.newtype_variant()?),
+            "Keyword" => Token::Keyword(content.newtype_variant()?),
+            "DocComment" => Token::DocComment(content.newtype_variant()?),
+            "Error" => Token::Error(content.newtype_variant()?),
+            _ => {
+                content.unit_variant()?;
+                Token::Eof
+            }
+        })
+    }
+}
+
+impl Serialize for Token {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Token::Punct(p) => newtype_variant::<_, Self, _>(s, "Punct", p),
+            Token::StringLit(l) => newtype_variant::<_, Self, _>(s, "StringLit", l),
+            Token::CharLit(c) => newtype_variant::<_, Self, _>(s, "CharLit", c),
+            Token::NumberLit(n) => newtype_variant::<_, Self, _>(s, "NumberLit", n),
+            Token::Ident(i) => newtype_variant::<_, Self, _>(s, "Ident", i),
+            Token::Label(l) => newtype_variant::<_, Self, _>(s, "Label", l),
+            Token::UpperIdent(u) => newtype_variant::<_, Self, _>(s, "UpperIdent", u),
+            Token::Operator(o) => newtype_variant::<_, Self, _>(s, "Operator", o),
+            Token::Keyword(k) => newtype_variant::<_, Self, _>(s, "Keyword", k),
+            Token::DocComment(d) => newtype_variant::<_, Self, _>(s, "DocComment", d),
+            Token::Error(e) => newtype_variant::<_, Self, _>(s, "Error", e),
+            Token::Eof => unit_variant::<_, Self>(s, "Eof"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Token {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_enum(deserializer)
+    }
+}
+
+impl TaggedEnum for NumberLiteral {
+    const NAME: &'static str = "NumberLiteral";
+    const VARIANTS: &'static [&'static str] = &["Int", "UInt", "Float"];
+
+    fn from_variant<'de, A: VariantAccess<'de>>(
+        variant: &'static str,
+        content: A,
+    ) -> Result<Self, A::Error> {
+        Ok(match variant {
+            "Int" => {
+                let (i, suffix) = content.newtype_variant()?;
+                NumberLiteral::Int(i, suffix)
+            }
+            "UInt" => {
+                let (u, suffix) = content.newtype_variant()?;
+                NumberLiteral::UInt(u, suffix)
+            }
+            _ => {
+                let (f, suffix) = content.newtype_variant()?;
+                NumberLiteral::Float(f, suffix)
+            }
+        })
+    }
+}
+
+/// The value and the suffix, e.g. `{"Int": [3, "i8"]}` or `{"Float": [0.5, null]}`
+impl Serialize for NumberLiteral {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self {
+            NumberLiteral::Int(i, suffix) => {
+                newtype_variant::<_, Self, _>(s, "Int", &(i, suffix))
+            }
+            NumberLiteral::UInt(u, suffix) => {
+                newtype_variant::<_, Self, _>(s, "UInt", &(u, suffix))
+            }
+            NumberLiteral::Float(f, suffix) => {
+                newtype_variant::<_, Self, _>(s, "Float", &(f, suffix))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for NumberLiteral {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_enum(deserializer)
+    }
+}
+
+impl TaggedEnum for LexError {
+    const NAME: &'static str = "LexError";
+    const VARIANTS: &'static [&'static str] = &[
+        "UnexpectedChar",
+        "DigitInOperator",
+        "NoWs",
+        "Ws",
+        "MissingDigits",
+        "MissingExponent",
+        "InvalidNum",
+        "NumberOverflow",
+        "IntOverflow",
+        "InvalidCharInNum",
+        "InvalidNumSuffix",
+        "InvalidEscape",
+        "UnterminatedString",
+        "InvalidChar",
+    ];
+
+    fn from_variant<'de, A: VariantAccess<'de>>(
+        variant: &'static str,
+        content: A,
+    ) -> Result<Self, A::Error> {
+        Ok(match variant {
+            "InvalidCharInNum" => LexError::InvalidCharInNum(content.newtype_variant()?),
+            "InvalidNumSuffix" => LexError::InvalidNumSuffix(content.newtype_variant()?),
+            "IntOverflow" => {
+                const FIELDS: &[&str] = &["radix", "signed"];
+                let visitor = StructVisitor::<u32, bool>::new(FIELDS);
+                let (radix, signed) = content.struct_variant(FIELDS, visitor)?;
+                LexError::IntOverflow { radix, signed }
+            }
+            "InvalidEscape" => LexError::InvalidEscape(content.newtype_variant()?),
+            "UnexpectedChar" => LexError::UnexpectedChar(content.newtype_variant()?),
+            _ => {
+                content.unit_variant()?;
+                match variant {
+                    "DigitInOperator" => LexError::DigitInOperator,
+                    "NoWs" => LexError::NoWs,
+                    "Ws" => LexError::Ws,
+                    "MissingDigits" => LexError::MissingDigits,
+                    "MissingExponent" => LexError::MissingExponent,
+                    "InvalidNum" => LexError::InvalidNum,
+                    "NumberOverflow" => LexError::NumberOverflow,
+                    "UnterminatedString" => LexError::UnterminatedString,
+                    _ => LexError::InvalidChar,
+                }
+            }
+        })
+    }
+}
+
+impl Serialize for LexError {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let variant = match self {
+            LexError::InvalidCharInNum(c) => {
+                return newtype_variant::<_, Self, _>(s, "InvalidCharInNum", c)
+            }
+            LexError::IntOverflow { radix, signed } => {
+                let index = Self::VARIANTS.iter().position(|&v| v == "IntOverflow");
+                let mut v = s.serialize_struct_variant(
+                    Self::NAME,
+                    index.unwrap() as u32,
+                    "IntOverflow",
+                    2,
+                )?;
+                v.serialize_field("radix", radix)?;
+                v.serialize_field("signed", signed)?;
+                return v.end();
+            }
+            LexError::InvalidNumSuffix(suffix) => {
+                return newtype_variant::<_, Self, _>(s, "InvalidNumSuffix", suffix)
+            }
+            LexError::InvalidEscape(c) => {
+                return newtype_variant::<_, Self, _>(s, "InvalidEscape", c)
+            }
+            LexError::UnexpectedChar(c) => {
+                return newtype_variant::<_, Self, _>(s, "UnexpectedChar", c)
+            }
+            LexError::DigitInOperator => "DigitInOperator",
+            LexError::NoWs => "NoWs",
+            LexError::Ws => "Ws",
+            LexError::MissingDigits => "MissingDigits",
+            LexError::MissingExponent => "MissingExponent",
+            LexError::InvalidNum => "InvalidNum",
+            LexError::NumberOverflow => "NumberOverflow",
+            LexError::UnterminatedString => "UnterminatedString",
+            LexError::InvalidChar => "InvalidChar",
+        };
+        unit_variant::<_, Self>(s, variant)
+    }
+}
+
+impl<'de> Deserialize<'de> for LexError {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_enum(deserializer)
+    }
+}
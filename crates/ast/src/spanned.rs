@@ -14,6 +14,16 @@ impl<T> Spanned<T> {
 
     pub fn into_inner(self) -> (T, TextRange) { (self.inner, self.span) }
 
+    /// Returns the start and end offsets and the inner value, e.g. for FFI
+    pub fn into_parts(self) -> (u32, u32, T) {
+        (self.span.start(), self.span.end(), self.inner)
+    }
+
+    /// The inverse of [`Spanned::into_parts`]. Panics if `start > end`.
+    pub fn from_parts(start: u32, end: u32, inner: T) -> Self {
+        Spanned::new(inner, TextRange::new(start, end))
+    }
+
     pub fn map<F: FnMut(T) -> U, U>(self, mut f: F) -> Spanned<U> {
         Spanned::new(f(self.inner), self.span)
     }
@@ -0,0 +1,225 @@
+use std::convert::TryFrom;
+
+use crate::token::{Ident, Operator, StringLiteral, UpperIdent};
+use crate::{LexError, Spanned, TextRange};
+
+#[test]
+fn valid_names() {
+    for &s in &["x", "foo_bar", "x-y", "test!", "a1", "is-it-true?", "aBC"] {
+        assert_eq!(Ident::try_from(s).unwrap().get(), s);
+    }
+    for &s in &["T", "Int", "HashMap", "U8", "A_b+"] {
+        assert_eq!(UpperIdent::try_from(s).unwrap().get(), s);
+    }
+    for &s in &["+", "**", "->", "==", ">gt", "*cross", "+_x"] {
+        assert_eq!(Operator::try_from(s).unwrap().get(), s);
+    }
+}
+
+#[test]
+fn invalid_names() {
+    for &s in &["", "Foo", "1x", "_x", "+x", "ä", "x y", "x.y"] {
+        assert!(Ident::try_from(s).is_err(), "{:?}", s);
+    }
+    for &s in &["", "foo", "1X", "_X", "Ä", "X Y"] {
+        assert!(UpperIdent::try_from(s).is_err(), "{:?}", s);
+    }
+    for &s in &["", "=", "a+", "+1", "1+", "+ +", "+ä"] {
+        assert!(Operator::try_from(s).is_err(), "{:?}", s);
+    }
+}
+
+#[test]
+fn invalid_name_error() {
+    let err = Ident::try_from("Foo").unwrap_err();
+    assert_eq!(err.to_string(), "\"Foo\" is not a valid identifier");
+}
+
+#[test]
+fn decode_string_literals() {
+    let lit = StringLiteral::with_raw("", r"smile: \u{1F600}\n");
+    assert_eq!(lit.decoded().unwrap(), "smile: 😀\n");
+
+    let lit = StringLiteral::with_raw("", r"invalid: \q");
+    assert_eq!(lit.decoded(), Err(LexError::InvalidEscape('q')));
+
+    let lit = StringLiteral::with_raw("", r"trailing \");
+    assert_eq!(lit.decoded(), Err(LexError::InvalidEscape('\\')));
+
+    let lit = StringLiteral::new_raw(r"raw \q");
+    assert_eq!(lit.decoded().unwrap(), r"raw \q");
+}
+
+#[test]
+fn new_string_literal_escapes_raw_text() {
+    let lit = StringLiteral::new("say \"hi\"\n\tC:\\");
+    assert_eq!(lit.raw(), r#"say \"hi\"\n\tC:\\"#);
+    assert_eq!(lit.decoded().unwrap(), lit.get());
+}
+
+#[test]
+fn text_range_queries() {
+    let range = TextRange::new(3, 7);
+    assert_eq!(range.len(), 4);
+    assert!(!range.is_empty());
+    assert!(TextRange::new(5, 5).is_empty());
+
+    assert!(!range.contains(2));
+    assert!(range.contains(3));
+    assert!(range.contains(6));
+    assert!(!range.contains(7));
+
+    assert!(range.overlaps(TextRange::new(6, 10)));
+    assert!(range.overlaps(TextRange::new(0, 4)));
+    assert!(range.overlaps(TextRange::new(4, 5)));
+    assert!(!range.overlaps(TextRange::new(7, 10)));
+    assert!(!range.overlaps(TextRange::new(0, 3)));
+    assert!(!range.overlaps(TextRange::new(5, 5)));
+}
+
+#[test]
+fn spanned_parts_round_trip() {
+    let spanned = TextRange::new(2, 9).embed("hello");
+    let parts = spanned.into_parts();
+    assert_eq!(parts, (2, 9, "hello"));
+    assert_eq!(Spanned::from_parts(parts.0, parts.1, parts.2), spanned);
+
+    let empty = Spanned::from_parts(4, 4, ());
+    assert_eq!(empty.span, TextRange::new(4, 4));
+    assert_eq!(empty.into_parts(), (4, 4, ()));
+}
+
+#[test]
+fn line_index() {
+    use crate::LineIndex;
+
+    let text = "fun f() {\r\n    \"äöü\" x\n}\n";
+    let index = LineIndex::new(text);
+    assert_eq!(index.line_col(text, 0), (1, 1));
+    assert_eq!(index.line_col(text, 4), (1, 5));
+    // `\r` and `\n` are still on the first line
+    assert_eq!(index.line_col(text, 9), (1, 10));
+    assert_eq!(index.line_col(text, 10), (1, 11));
+    assert_eq!(index.line_col(text, 11), (2, 1));
+    // the umlauts take 2 bytes each
+    let x = text.find('x').unwrap() as u32;
+    assert_eq!(index.line_col(text, x), (2, 11));
+    assert_eq!(index.line_col(text, x - 2), (2, 9));
+    assert_eq!(index.line_col(text, x - 3), (2, 8));
+    assert_eq!(index.line_col(text, x - 4), (2, 8));
+    assert_eq!(index.line_col(text, text.len() as u32), (4, 1));
+
+    let range = TextRange::new(4, x + 1);
+    assert_eq!(index.range_to_line_cols(text, range), ((1, 5), (2, 12)));
+
+    assert_eq!(LineIndex::new("").line_col("", 0), (1, 1));
+}
+
+#[test]
+fn line_index_spans() {
+    use crate::LineIndex;
+
+    let text = "let a = 1\r\nlet bä = 2\nb";
+    let index = LineIndex::new(text);
+    assert_eq!(index.line_col(text, 0), (1, 1));
+    assert_eq!(index.line_col(text, 4), (1, 5));
+    assert_eq!(index.line_col(text, 11), (2, 1));
+    // `ä` takes 2 bytes
+    assert_eq!(index.line_col(text, 18), (2, 7));
+    assert_eq!(index.line_col(text, 17), (2, 6));
+    // the end of the text
+    assert_eq!(index.line_col(text, text.len() as u32), (3, 2));
+
+    let across = TextRange::new(8, 14);
+    assert_eq!(&text[across], "1\r\nlet");
+    assert_eq!(index.line_col(text, across.end()), (2, 4));
+    let at_eof = TextRange::new(text.len() as u32, text.len() as u32);
+    assert_eq!(&text[at_eof], "");
+    assert_eq!(index.line_col(text, at_eof.start()), (3, 2));
+}
+
+#[test]
+fn text_range_intersection() {
+    let range = TextRange::new(3, 7);
+    assert_eq!(range.intersection(TextRange::new(7, 9)), None);
+    assert_eq!(range.intersection(TextRange::new(0, 3)), None);
+    assert_eq!(range.intersection(TextRange::new(8, 9)), None);
+
+    assert_eq!(range.intersection(range), Some(range));
+    assert_eq!(range.intersection(TextRange::new(0, 10)), Some(range));
+    assert_eq!(range.intersection(TextRange::new(4, 6)), Some(TextRange::new(4, 6)));
+
+    assert_eq!(range.intersection(TextRange::new(5, 9)), Some(TextRange::new(5, 7)));
+    assert_eq!(range.intersection(TextRange::new(1, 4)), Some(TextRange::new(3, 4)));
+    assert_eq!(range.intersection(TextRange::new(5, 5)), Some(TextRange::new(5, 5)));
+}
+
+#[test]
+fn text_range_offset_by() {
+    let range = TextRange::new(3, 7);
+    assert_eq!(range.offset_by(0), Some(range));
+    assert_eq!(range.offset_by(5), Some(TextRange::new(8, 12)));
+    assert_eq!(range.offset_by(-3), Some(TextRange::new(0, 4)));
+
+    assert_eq!(range.offset_by(-4), None);
+    assert_eq!(range.offset_by(i32::MIN), None);
+    assert_eq!(TextRange::new(0, u32::MAX).offset_by(1), None);
+    let max = TextRange::new(5, u32::MAX);
+    assert_eq!(max.offset_by(-5), Some(TextRange::new(0, u32::MAX - 5)));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    use crate::token::{Keyword, NumberLiteral, NumberSuffix, Punctuation, Token};
+
+    let json = serde_json::to_string(&TextRange::new(0, 5)).unwrap();
+    assert_eq!(json, r#"{"start":0,"end":5}"#);
+    assert!(serde_json::from_str::<TextRange>(r#"{"start":6,"end":5}"#).is_err());
+
+    let tokens = vec![
+        Token::Keyword(Keyword::Fun),
+        Token::Ident(Ident::new("is-empty?")),
+        Token::Punct(Punctuation::OpenParen),
+        Token::UpperIdent(UpperIdent::new("List")),
+        Token::Operator(Operator::new("+=")),
+        Token::StringLit(StringLiteral::with_raw("a\nb", "a\\nb")),
+        Token::StringLit(StringLiteral::new_raw("c\\d")),
+        Token::CharLit('😀'),
+        Token::NumberLit(NumberLiteral::Int(-3, None)),
+        Token::NumberLit(NumberLiteral::UInt(3, Some(NumberSuffix::U8))),
+        Token::NumberLit(NumberLiteral::Float(0.5, Some(NumberSuffix::F32))),
+        Token::DocComment("docs".into()),
+        Token::Error(LexError::InvalidEscape('q')),
+        Token::Error(LexError::InvalidNumSuffix(NumberSuffix::I128)),
+        Token::Error(LexError::IntOverflow { radix: 16, signed: false }),
+        Token::Error(LexError::NoWs),
+        Token::Error(LexError::UnexpectedChar('$')),
+        Token::Error(LexError::MissingExponent),
+        Token::Eof,
+    ];
+    let tokens: Vec<Spanned<Token>> = tokens
+        .into_iter()
+        .enumerate()
+        .map(|(i, t)| TextRange::new(i as u32, i as u32 + 1).embed(t))
+        .collect();
+
+    let json = serde_json::to_string(&tokens).unwrap();
+    let back: Vec<Spanned<Token>> = serde_json::from_str(&json).unwrap();
+    assert!(back == tokens);
+    assert_eq!(serde_json::to_string(&back).unwrap(), json);
+
+    let json = serde_json::to_string(&tokens[..2]).unwrap();
+    assert_eq!(
+        json,
+        r#"[{"inner":{"Keyword":"fun"},"span":{"start":0,"end":1}},"#.to_string()
+            + r#"{"inner":{"Ident":"is-empty?"},"span":{"start":1,"end":2}}]"#
+    );
+    assert!(serde_json::from_str::<Token>(r#"{"Ident":"Foo"}"#).is_err());
+    assert!(serde_json::from_str::<Token>(r#""Eof""#).unwrap() == Token::Eof);
+
+    let json = serde_json::to_string(&tokens[9].inner).unwrap();
+    assert_eq!(json, r#"{"NumberLit":{"UInt":[3,"u8"]}}"#);
+    let json = serde_json::to_string(&tokens[14].inner).unwrap();
+    assert_eq!(json, r#"{"Error":{"IntOverflow":{"radix":16,"signed":false}}}"#);
+}
@@ -22,13 +22,47 @@ impl TextRange {
 
     pub fn end(&self) -> u32 { self.end }
 
+    pub fn len(&self) -> u32 { self.end - self.start }
+
+    pub fn is_empty(&self) -> bool { self.start == self.end }
+
+    /// Returns whether the byte at `pos` is in this range
+    pub fn contains(&self, pos: u32) -> bool { self.start <= pos && pos < self.end }
+
+    /// Returns whether the ranges have at least one byte in common
+    pub fn overlaps(&self, other: Self) -> bool {
+        self.start.max(other.start) < self.end.min(other.end)
+    }
+
     pub fn extend_until(&self, end: u32) -> Self { TextRange::new(self.start, end) }
 
+    /// Moves the range by `delta` bytes. Returns `None` if the start or end
+    /// would be negative or overflow.
+    pub fn offset_by(&self, delta: i32) -> Option<Self> {
+        let start = self.start.checked_add_signed(delta)?;
+        let end = self.end.checked_add_signed(delta)?;
+        Some(TextRange::new(start, end))
+    }
+
     #[must_use]
     pub fn merge(&self, other: Self) -> Self {
         TextRange::new(self.start.min(other.start), self.end.max(other.end))
     }
 
+    /// Returns the largest range contained in both ranges, or `None` if they
+    /// don't overlap according to the `PartialOrd` impl. This means that
+    /// adjacent ranges have no intersection, but an empty range within another
+    /// range does.
+    pub fn intersection(&self, other: Self) -> Option<Self> {
+        match self.partial_cmp(&other) {
+            Some(Ordering::Less) | Some(Ordering::Greater) => None,
+            _ => {
+                let start = self.start.max(other.start);
+                Some(TextRange::new(start, self.end.min(other.end)))
+            }
+        }
+    }
+
     pub fn merge_if<T>(&self, other: &Option<Spanned<T>>) -> Self {
         match other {
             Some(t) => self.merge(t.span),
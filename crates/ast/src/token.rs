@@ -1,21 +1,25 @@
 use std::fmt;
 
 pub use crate::keyword::Keyword;
-pub use crate::literal::{NumberLiteral, StringLiteral};
-pub use crate::name::{Ident, Operator, UpperIdent};
+pub use crate::literal::{NumberLiteral, NumberSuffix, StringLiteral};
+pub use crate::name::{Ident, InvalidName, Operator, UpperIdent};
 pub use crate::punct::Punctuation;
-use crate::LexError;
+use crate::{LexError, TinyString};
 
 #[derive(Clone, PartialEq)]
 #[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub enum Token {
     Punct(Punctuation),
     StringLit(StringLiteral),
+    CharLit(char),
     NumberLit(NumberLiteral),
     Ident(Ident),
+    /// A loop label such as `'outer`. It contains the name without the quote.
+    Label(Ident),
     UpperIdent(UpperIdent),
     Operator(Operator),
     Keyword(Keyword),
+    DocComment(TinyString),
     Error(LexError),
     Eof,
 }
@@ -47,11 +51,14 @@ impl Token {
         match self {
             Token::Punct(_) => TokenKind::Punct,
             Token::StringLit(_) => TokenKind::StringLit,
+            Token::CharLit(_) => TokenKind::CharLit,
             Token::NumberLit(_) => TokenKind::NumberLit,
             Token::Ident(_) => TokenKind::Ident,
+            Token::Label(_) => TokenKind::Label,
             Token::UpperIdent(_) => TokenKind::UpperIdent,
             Token::Operator(_) => TokenKind::Operator,
             Token::Keyword(_) => TokenKind::Keyword,
+            Token::DocComment(_) => TokenKind::DocComment,
             Token::Error(_) => TokenKind::Error,
             Token::Eof => TokenKind::Eof,
         }
@@ -69,11 +76,14 @@ impl Token {
 pub enum TokenKind {
     Punct,
     StringLit,
+    CharLit,
     NumberLit,
     Ident,
+    Label,
     UpperIdent,
     Operator,
     Keyword,
+    DocComment,
     Error,
     Eof,
 }
@@ -82,12 +92,15 @@ impl fmt::Debug for Token {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Token::Punct(p) => write!(f, "`{}`", p),
-            Token::StringLit(l) => write!(f, "s`{}`", l),
+            Token::StringLit(l) => write!(f, "s{:?}", l.get()),
+            Token::CharLit(c) => write!(f, "c{:?}", c),
             Token::NumberLit(l) => write!(f, "{:?}", l),
             Token::Ident(i) => write!(f, "i`{}`", i),
+            Token::Label(l) => write!(f, "l`'{}`", l),
             Token::UpperIdent(i) => write!(f, "I`{}`", i),
             Token::Operator(i) => write!(f, "o`{}`", i),
             Token::Keyword(k) => write!(f, "k`{}`", k),
+            Token::DocComment(d) => write!(f, "d{:?}", d),
             Token::Error(e) => write!(f, "{:?}", e),
             Token::Eof => write!(f, "EOF"),
         }
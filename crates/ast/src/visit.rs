@@ -0,0 +1,267 @@
+//! A visitor over the syntax tree. Every method of [`Visit`] has a default
+//! implementation that calls the matching `walk_*` function, which visits the
+//! children of the node. An implementation only overrides the methods it is
+//! interested in, and calls the `walk_*` function to keep recursing:
+//!
+//! ```
+//! use ast::expr::Expr;
+//! use ast::visit::{walk_expr, Visit};
+//! use ast::Spanned;
+//!
+//! struct CountReturns(usize);
+//!
+//! impl<'ast> Visit<'ast> for CountReturns {
+//!     fn visit_expr(&mut self, expr: &'ast Spanned<Expr>) {
+//!         if let Expr::Return(_) = expr.inner {
+//!             self.0 += 1;
+//!         }
+//!         walk_expr(self, expr);
+//!     }
+//! }
+//! ```
+
+use crate::expr::*;
+use crate::item::*;
+use crate::pattern::*;
+use crate::Spanned;
+
+pub trait Visit<'ast>: Sized {
+    fn visit_item(&mut self, item: &'ast Spanned<Item>) { walk_item(self, item) }
+
+    fn visit_function(&mut self, function: &'ast Function) {
+        walk_function(self, function)
+    }
+
+    fn visit_class(&mut self, class: &'ast Class) { walk_class(self, class) }
+
+    fn visit_enum(&mut self, enum_: &'ast Enum) { walk_enum(self, enum_) }
+
+    fn visit_impl(&mut self, impl_: &'ast Impl) { walk_impl(self, impl_) }
+
+    fn visit_generic_param(&mut self, param: &'ast GenericParam) {
+        walk_generic_param(self, param)
+    }
+
+    fn visit_named_type(&mut self, ty: &'ast NamedType) { walk_named_type(self, ty) }
+
+    fn visit_expr(&mut self, expr: &'ast Spanned<Expr>) { walk_expr(self, expr) }
+
+    fn visit_invokable(&mut self, invokable: &'ast Invokable) {
+        walk_invokable(self, invokable)
+    }
+
+    fn visit_block(&mut self, block: &'ast Block) { walk_block(self, block) }
+
+    fn visit_lambda(&mut self, lambda: &'ast Lambda) { walk_lambda(self, lambda) }
+
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) { walk_match_arm(self, arm) }
+
+    fn visit_pattern(&mut self, pattern: &'ast Pattern) { walk_pattern(self, pattern) }
+}
+
+pub fn walk_item<'ast, V: Visit<'ast>>(v: &mut V, item: &'ast Spanned<Item>) {
+    match &item.inner {
+        Item::Function(f) => v.visit_function(f),
+        Item::Class(c) => v.visit_class(c),
+        Item::Enum(e) => v.visit_enum(e),
+        Item::Impl(i) => v.visit_impl(i),
+        Item::TypeAlias(t) => {
+            t.generics.iter().for_each(|p| v.visit_generic_param(p));
+            v.visit_named_type(&t.ty);
+        }
+        Item::Use(_) | Item::Error => {}
+    }
+}
+
+pub fn walk_function<'ast, V: Visit<'ast>>(v: &mut V, function: &'ast Function) {
+    function.generics.iter().for_each(|p| v.visit_generic_param(p));
+    for arg in function.args.iter() {
+        if let Some(ty) = &arg.ty {
+            v.visit_named_type(ty);
+        }
+        if let Some(default) = &arg.default {
+            v.visit_expr(default);
+        }
+    }
+    if let Some(ty) = &function.return_ty {
+        v.visit_named_type(ty);
+    }
+    if let Some(where_clause) = &function.where_clause {
+        for bound in where_clause.bounds.iter().flat_map(|b| b.bounds.iter()) {
+            v.visit_named_type(&bound.ty);
+        }
+    }
+    if let Some(body) = &function.body {
+        v.visit_block(body);
+    }
+}
+
+pub fn walk_class<'ast, V: Visit<'ast>>(v: &mut V, class: &'ast Class) {
+    class.generics.iter().for_each(|p| v.visit_generic_param(p));
+    walk_fields(v, &class.fields);
+}
+
+pub fn walk_enum<'ast, V: Visit<'ast>>(v: &mut V, enum_: &'ast Enum) {
+    enum_.generics.iter().for_each(|p| v.visit_generic_param(p));
+    for variant in enum_.variants.iter() {
+        if let Some(fields) = &variant.arguments {
+            walk_fields(v, fields);
+        }
+    }
+}
+
+fn walk_fields<'ast, V: Visit<'ast>>(v: &mut V, fields: &'ast [Spanned<ClassField>]) {
+    for field in fields {
+        if let Some(ty) = &field.ty {
+            v.visit_named_type(ty);
+        }
+        if let Some(default) = &field.default {
+            v.visit_expr(default);
+        }
+    }
+}
+
+pub fn walk_impl<'ast, V: Visit<'ast>>(v: &mut V, impl_: &'ast Impl) {
+    impl_.generics.iter().for_each(|p| v.visit_generic_param(p));
+    if let Some(r#trait) = &impl_.r#trait {
+        v.visit_named_type(r#trait);
+    }
+    v.visit_named_type(&impl_.ty);
+    impl_.items.iter().for_each(|item| v.visit_item(item));
+}
+
+pub fn walk_generic_param<'ast, V: Visit<'ast>>(v: &mut V, param: &'ast GenericParam) {
+    param.bounds.iter().for_each(|bound| v.visit_named_type(&bound.ty));
+}
+
+pub fn walk_named_type<'ast, V: Visit<'ast>>(v: &mut V, ty: &'ast NamedType) {
+    walk_type_args(v, &ty.args);
+}
+
+fn walk_type_args<'ast, V: Visit<'ast>>(v: &mut V, args: &'ast [Spanned<TypeArgument>]) {
+    for arg in args {
+        match &arg.inner {
+            TypeArgument::Type(ty) => v.visit_named_type(ty),
+            TypeArgument::Wildcard => {}
+        }
+    }
+}
+
+pub fn walk_expr<'ast, V: Visit<'ast>>(v: &mut V, expr: &'ast Spanned<Expr>) {
+    walk_expr_inner(v, &expr.inner)
+}
+
+fn walk_expr_inner<'ast, V: Visit<'ast>>(v: &mut V, expr: &'ast Expr) {
+    match expr {
+        Expr::Invokable(i) => v.visit_invokable(i),
+        Expr::Literal(_) | Expr::Empty(_) => {}
+        Expr::ParenCall(c) => {
+            v.visit_expr(&c.receiver);
+            for arg in c.args.iter().flat_map(|args| args.iter()) {
+                v.visit_expr(&arg.expr);
+            }
+        }
+        Expr::Index(i) => {
+            v.visit_expr(&i.receiver);
+            i.indices.iter().for_each(|e| v.visit_expr(e));
+        }
+        Expr::MemberCall(m) => {
+            v.visit_expr(&m.receiver);
+            v.visit_invokable(&m.member);
+        }
+        Expr::Operation(Operation { lhs, rhs, .. })
+        | Expr::ShortcircuitingOp(ScOperation { lhs, rhs, .. })
+        | Expr::Assignment(Assignment { lhs, rhs }) => {
+            v.visit_expr(lhs);
+            v.visit_expr(rhs);
+        }
+        Expr::UnaryOp(o) => v.visit_expr(&o.operand),
+        Expr::TypeAscription(t) => {
+            v.visit_expr(&t.expr);
+            v.visit_named_type(&t.ty);
+        }
+        Expr::Statement(e) | Expr::Try(e) => v.visit_expr(e),
+        Expr::Lambda(l) => v.visit_lambda(l),
+        Expr::Block(b) => v.visit_block(b),
+        Expr::Tuple(p) => p.exprs.iter().for_each(|a| v.visit_expr(&a.expr)),
+        Expr::ArrayLit(a) => a.elements.iter().for_each(|e| v.visit_expr(e)),
+        Expr::Declaration(d) => v.visit_expr(&d.value),
+        Expr::Match(m) => {
+            v.visit_expr(&m.expr);
+            m.match_arms.iter().for_each(|arm| v.visit_match_arm(arm));
+        }
+        Expr::While(w) => {
+            v.visit_expr(&w.condition);
+            v.visit_block(&w.body);
+        }
+        Expr::For(f) => {
+            v.visit_invokable(&f.binding);
+            v.visit_expr(&f.iterable);
+            v.visit_block(&f.body);
+        }
+        Expr::IfExpr(i) => {
+            v.visit_expr(&i.condition);
+            v.visit_block(&i.then_branch);
+            if let Some(else_branch) = &i.else_branch {
+                v.visit_expr(else_branch);
+            }
+        }
+        Expr::Return(e) => {
+            if let Some(e) = e {
+                v.visit_expr(e);
+            }
+        }
+        Expr::Break(_) | Expr::Continue(_) => {}
+    }
+}
+
+pub fn walk_invokable<'ast, V: Visit<'ast>>(v: &mut V, invokable: &'ast Invokable) {
+    walk_type_args(v, &invokable.generics);
+}
+
+pub fn walk_block<'ast, V: Visit<'ast>>(v: &mut V, block: &'ast Block) {
+    block.exprs.iter().for_each(|e| v.visit_expr(e));
+}
+
+pub fn walk_lambda<'ast, V: Visit<'ast>>(v: &mut V, lambda: &'ast Lambda) {
+    for arg in lambda.args.iter() {
+        if let Some(ty) = &arg.ty {
+            v.visit_named_type(ty);
+        }
+    }
+    v.visit_expr(&lambda.body);
+}
+
+pub fn walk_match_arm<'ast, V: Visit<'ast>>(v: &mut V, arm: &'ast MatchArm) {
+    v.visit_pattern(&arm.pattern);
+    v.visit_expr(&arm.expr);
+}
+
+/// The guard of a [`GuardPattern`] has no span, so it isn't passed to
+/// [`Visit::visit_expr`]; only its children are visited.
+pub fn walk_pattern<'ast, V: Visit<'ast>>(v: &mut V, pattern: &'ast Pattern) {
+    match pattern {
+        Pattern::Wildcard | Pattern::Binding(_) | Pattern::Literal(_) => {}
+        Pattern::Tuple(patterns) | Pattern::Or(patterns) => {
+            patterns.iter().for_each(|p| v.visit_pattern(p))
+        }
+        Pattern::Range(r) | Pattern::RangeExclusive(r) => {
+            v.visit_pattern(&r.from);
+            v.visit_pattern(&r.to);
+        }
+        Pattern::Class(c) => c.fields.iter().for_each(|p| v.visit_pattern(p)),
+        Pattern::Enum(e) => {
+            if let Some(field) = &e.field {
+                v.visit_pattern(field);
+            }
+        }
+        Pattern::TypeAscription(t) => {
+            v.visit_expr(&t.expr);
+            v.visit_named_type(&t.ty);
+        }
+        Pattern::Guard(g) => {
+            v.visit_pattern(&g.pattern);
+            walk_expr_inner(v, &g.guard);
+        }
+    }
+}
@@ -3,6 +3,9 @@ use ast::token::{Ident, Keyword, Punctuation, Token, UpperIdent};
 use ast::TextRange;
 use lexer::Program;
 
+mod structured;
+#[cfg(test)]
+mod tests;
 mod validate_tokens;
 
 pub fn get_tokens(data: &[u8]) -> Result<Program> {
@@ -16,7 +19,7 @@ pub fn get_tokens(data: &[u8]) -> Result<Program> {
         .map(|(i, t)| Ok(TextRange::from(i..i + 1).embed(t?)))
         .filter(|r| {
             r.as_ref()
-                .map(|r| !matches!(**r, Token::Error(_) | Token::EOF))
+                .map(|r| !matches!(**r, Token::Error(_) | Token::Eof))
                 .unwrap_or(true)
         })
         .collect::<Result<Vec<_>>>()?;
@@ -35,9 +38,10 @@ pub fn get_tokens(data: &[u8]) -> Result<Program> {
         program.push(TextRange::from(0..0).embed(Token::Punct(Punctuation::CloseBrace)));
     }
 
-    program.push(TextRange::new(0, 0).embed(Token::EOF));
+    program.push(TextRange::new(0, 0).embed(Token::Eof));
 
     Ok(program.into())
 }
 
+pub use structured::get_structured_program;
 pub use validate_tokens::is_balanced;
@@ -1,6 +1,12 @@
 fn main() {
     afl::fuzz(true, |data| {
-        if let Ok(program) = fuzzer::get_tokens(data) {
+        // the first byte selects whether the input is token soup or a program
+        // generated from a syntax tree
+        let program = match data.split_first() {
+            Some((&mode, rest)) if mode % 2 == 1 => fuzzer::get_structured_program(rest),
+            _ => fuzzer::get_tokens(data),
+        };
+        if let Ok(program) = program {
             if program.errors().is_empty() {
                 if let Ok(_items) = parser::parse(program.tokens()) {
                     fuzzer::is_balanced(program.tokens()).unwrap();
@@ -0,0 +1,164 @@
+//! Generates programs that are structurally valid, so the fuzzer can exercise the
+//! parser beyond the first few tokens. The input data decides the shape of a
+//! small syntax tree, which is emitted as tokens while it is generated, so
+//! brackets are always balanced.
+
+use arbitrary::{Result, Unstructured};
+use ast::token::{Ident, Keyword, NumberLiteral, Operator, Punctuation, Token, UpperIdent};
+use ast::{Spanned, TextRange};
+use lexer::Program;
+
+/// The maximum nesting depth of expressions
+const MAX_DEPTH: u32 = 4;
+
+const OPERATORS: &[&str] = &["+", "-", "*", "/", "==", "<", ">="];
+
+pub fn get_structured_program(data: &[u8]) -> Result<Program> {
+    let mut gen = Generator { u: Unstructured::new(data), tokens: Vec::new() };
+
+    let functions = 1 + gen.choice(2)?;
+    for i in 0..functions {
+        gen.function(i)?;
+    }
+    gen.push(Token::Eof);
+
+    Ok(gen.tokens.into())
+}
+
+struct Generator<'a> {
+    u: Unstructured<'a>,
+    tokens: Vec<Spanned<Token>>,
+}
+
+impl Generator<'_> {
+    /// Returns a number in `0..=max`. When the data is exhausted, it returns 0, so
+    /// the syntax tree is completed with the smallest possible nodes.
+    fn choice(&mut self, max: u8) -> Result<u8> {
+        if self.u.is_empty() {
+            Ok(0)
+        } else {
+            self.u.int_in_range(0..=max)
+        }
+    }
+
+    fn flip(&mut self) -> Result<bool> { Ok(self.choice(1)? == 1) }
+
+    fn push(&mut self, token: impl Into<Token>) {
+        let i = self.tokens.len();
+        self.tokens.push(TextRange::from(i..i + 1).embed(token.into()));
+    }
+
+    fn ident(&mut self, name: String) { self.push(Ident::new(name)); }
+
+    fn upper_ident(&mut self, name: &str) { self.push(UpperIdent::new(name)); }
+
+    /// `fun f0(x0 Int, x1 Int) Int { ... }`
+    fn function(&mut self, index: u8) -> Result<()> {
+        self.push(Keyword::Fun);
+        self.ident(format!("f{}", index));
+        self.push(Punctuation::OpenParen);
+        let args = self.choice(3)?;
+        for i in 0..args {
+            if i > 0 {
+                self.push(Punctuation::Comma);
+            }
+            self.ident(format!("x{}", i));
+            self.upper_ident("Int");
+        }
+        self.push(Punctuation::CloseParen);
+        self.upper_ident("Int");
+        self.block(0)
+    }
+
+    /// A block with up to 4 statements. Declarations are always followed by a
+    /// semicolon, because they can't be the value of the block.
+    fn block(&mut self, depth: u32) -> Result<()> {
+        self.push(Punctuation::OpenBrace);
+        let statements = self.choice(4)?;
+        for i in 0..statements {
+            if self.flip()? {
+                self.push(Keyword::Let);
+                self.ident(format!("v{}", i));
+                self.push(Punctuation::Equals);
+                self.expr(depth + 1)?;
+                self.push(Punctuation::Semicolon);
+            } else {
+                self.expr(depth + 1)?;
+                if i + 1 < statements || self.flip()? {
+                    self.push(Punctuation::Semicolon);
+                }
+            }
+        }
+        self.push(Punctuation::CloseBrace);
+        Ok(())
+    }
+
+    fn expr(&mut self, depth: u32) -> Result<()> {
+        let max = if depth >= MAX_DEPTH { 1 } else { 6 };
+        match self.choice(max)? {
+            0 | 1 => self.operand(depth),
+            2 => {
+                // operators can't be mixed without blocks, so there is only one
+                self.operand(depth)?;
+                let op = OPERATORS[self.choice(OPERATORS.len() as u8 - 1)? as usize];
+                self.push(Operator::new(op));
+                self.operand(depth)
+            }
+            3 => {
+                let name = format!("f{}", self.choice(2)?);
+                self.ident(name);
+                self.push(Punctuation::OpenParen);
+                let args = self.choice(3)?;
+                for i in 0..args {
+                    if i > 0 {
+                        self.push(Punctuation::Comma);
+                    }
+                    self.expr(depth + 1)?;
+                }
+                self.push(Punctuation::CloseParen);
+                Ok(())
+            }
+            4 => {
+                self.push(Punctuation::Pipe);
+                let args = 1 + self.choice(1)?;
+                for i in 0..args {
+                    if i > 0 {
+                        self.push(Punctuation::Comma);
+                    }
+                    self.ident(format!("y{}", i));
+                }
+                self.push(Punctuation::Pipe);
+                self.expr(depth + 1)
+            }
+            5 => {
+                self.push(Keyword::If);
+                self.operand(MAX_DEPTH)?;
+                self.block(depth)?;
+                if self.flip()? {
+                    self.push(Keyword::Else);
+                    self.block(depth)?;
+                }
+                Ok(())
+            }
+            _ => self.block(depth),
+        }
+    }
+
+    /// A literal, a variable or a block, which can be an operand of an operator
+    fn operand(&mut self, depth: u32) -> Result<()> {
+        let max = if depth >= MAX_DEPTH { 1 } else { 2 };
+        match self.choice(max)? {
+            0 => {
+                let n = self.choice(255)?;
+                self.push(NumberLiteral::Int(n.into(), None));
+                Ok(())
+            }
+            1 => {
+                let name = format!("x{}", self.choice(2)?);
+                self.ident(name);
+                Ok(())
+            }
+            _ => self.block(depth),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{get_structured_program, is_balanced};
+
+/// Returns pseudo-random bytes, so the test is deterministic
+fn random_data(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+    (0..len)
+        .map(|_| {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 24) as u8
+        })
+        .collect()
+}
+
+#[test]
+fn structured_programs_are_parsed() {
+    let seeds = 500;
+    let mut accepted = 0;
+    for seed in 0..seeds {
+        let data = random_data(seed, 512);
+        let program = get_structured_program(&data).unwrap();
+        is_balanced(program.tokens()).unwrap();
+        if parser::parse(program.tokens()).is_ok() {
+            accepted += 1;
+        }
+    }
+    assert!(
+        accepted * 10 >= seeds * 9,
+        "only {} of {} programs were accepted",
+        accepted,
+        seeds,
+    );
+}
@@ -68,7 +68,7 @@ pub fn is_balanced(tokens: &[Spanned<Token>]) -> Result<(), &'static str> {
                 Punctuation::CloseBrace => open_brackets.pop_exact(Bracket::Curly)?,
                 _ => {}
             },
-            Token::EOF => {
+            Token::Eof => {
                 if eof {
                     return Err("token after EOF");
                 } else {
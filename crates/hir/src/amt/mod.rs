@@ -1,11 +1,13 @@
 //! The abstract module tree
 
 use ast::{Spanned, SpannedList};
+use string_interner::DefaultSymbol;
 
-use crate::arena::Idx;
+use crate::arena::{Arena, Idx};
 
+use self::expr::Expr;
 use self::name::{Ident, Operator, UpperIdent};
-use self::types::GenericParam;
+use self::types::{GenericParam, NamedType};
 
 pub mod expr;
 pub mod literal;
@@ -21,6 +23,29 @@ pub struct Namespace {
     pub values: Vec<Idx<Item>>,
 }
 
+impl Namespace {
+    /// Returns the class or enum with the given interned name
+    pub fn get_type(&self, name: DefaultSymbol, arena: &Arena) -> Option<Idx<Item>> {
+        Self::find(&self.types, name, arena)
+    }
+
+    /// Returns the function with the given interned name
+    pub fn get_value(&self, name: DefaultSymbol, arena: &Arena) -> Option<Idx<Item>> {
+        Self::find(&self.values, name, arena)
+    }
+
+    fn find(
+        items: &[Idx<Item>],
+        name: DefaultSymbol,
+        arena: &Arena,
+    ) -> Option<Idx<Item>> {
+        items.iter().copied().find(|&idx| match arena[idx].name() {
+            Some(n) => n.symbol() == name,
+            None => false,
+        })
+    }
+}
+
 pub enum NamespaceKind {
     Module,
     Impl,
@@ -34,15 +59,30 @@ pub enum Item {
     Impl(Impl),
 }
 
+impl Item {
+    /// Returns the name of the item, or `None` for `impl` blocks
+    pub fn name(&self) -> Option<&Spanned<Name>> {
+        match self {
+            Item::Function(f) => Some(&f.name),
+            Item::Class(c) => Some(&c.name),
+            Item::Enum(e) => Some(&e.name),
+            Item::Impl(_) => None,
+        }
+    }
+}
+
 pub struct Function {
     pub name: Spanned<Name>,
     pub generics: SpannedList<GenericParam>,
     pub args: SpannedList<FunctionArg>,
+    /// The body is a [`Block`](expr::Block) expression, or `None` for a function
+    /// signature
+    pub body: Option<Spanned<Idx<Expr>>>,
 }
 
 pub struct FunctionArg {
     pub name: Spanned<Name>,
-    pub ty: Spanned<Type>,
+    pub ty: Option<Spanned<Type>>,
 }
 
 pub struct Class {
@@ -57,6 +97,8 @@ pub struct Enum {
 
 pub struct Impl {
     pub generics: SpannedList<GenericParam>,
+    pub ty: Spanned<NamedType>,
+    pub namespace: Namespace,
 }
 
 pub struct Type {
@@ -70,3 +112,13 @@ pub enum Name {
     Ident(Ident),
     Type(UpperIdent),
 }
+
+impl Name {
+    pub fn symbol(&self) -> DefaultSymbol {
+        match *self {
+            Name::Operator(o) => o.symbol(),
+            Name::Ident(i) => i.symbol(),
+            Name::Type(t) => t.symbol(),
+        }
+    }
+}
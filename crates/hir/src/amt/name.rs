@@ -15,16 +15,22 @@ impl Ident {
     pub fn new(inner: DefaultSymbol) -> Self { Self(inner) }
 
     pub fn get(self, arena: &Arena) -> &str { &arena[self.0] }
+
+    pub fn symbol(self) -> DefaultSymbol { self.0 }
 }
 
 impl Operator {
     pub fn new(inner: DefaultSymbol) -> Self { Self(inner) }
 
     pub fn get(self, arena: &Arena) -> &str { &arena[self.0] }
+
+    pub fn symbol(self) -> DefaultSymbol { self.0 }
 }
 
 impl UpperIdent {
     pub fn new(inner: DefaultSymbol) -> Self { Self(inner) }
 
     pub fn get(self, arena: &Arena) -> &str { &arena[self.0] }
+
+    pub fn symbol(self) -> DefaultSymbol { self.0 }
 }
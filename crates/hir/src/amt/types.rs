@@ -1,9 +1,11 @@
 use ast::{Spanned, SpannedList};
 
-use super::name::UpperIdent;
+use super::name::{Ident, UpperIdent};
 
 #[derive(Clone)]
 pub struct NamedType {
+    /// The modules before the type name, e.g. `std` in `std.Map`
+    pub path: SpannedList<Ident>,
     pub name: Spanned<UpperIdent>,
     pub args: Spanned<SpannedList<TypeArgument>>,
 }
@@ -22,5 +24,6 @@ pub struct GenericParam {
 
 #[derive(Clone)]
 pub enum TypeBound {
-    // TODO: Interface/trait/contract/superclass
+    /// The type must implement the interface, e.g. `Clone` in `[T Clone]`
+    Interface(NamedType),
 }
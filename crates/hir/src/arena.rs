@@ -1,3 +1,6 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 use std::ops::Index;
 
@@ -31,6 +34,28 @@ impl Arena {
     pub fn add_string(&mut self, string: &str) -> DefaultSymbol {
         self.strings.get_or_intern(string)
     }
+
+    pub fn get_item(&self, index: Idx<Item>) -> Option<&Item> {
+        self.items.get(index.idx)
+    }
+
+    pub fn get_expr(&self, index: Idx<Expr>) -> Option<&Expr> {
+        self.exprs.get(index.idx)
+    }
+
+    pub fn items_len(&self) -> usize { self.items.len() }
+
+    pub fn exprs_len(&self) -> usize { self.exprs.len() }
+
+    /// Returns all items in the order in which they were added
+    pub fn items(&self) -> impl Iterator<Item = (Idx<Item>, &Item)> {
+        self.items.iter().enumerate().map(|(i, item)| (Idx::new(i), item))
+    }
+
+    /// Returns all expressions in the order in which they were added
+    pub fn exprs(&self) -> impl Iterator<Item = (Idx<Expr>, &Expr)> {
+        self.exprs.iter().enumerate().map(|(i, expr)| (Idx::new(i), expr))
+    }
 }
 
 impl Index<Idx<Item>> for Arena {
@@ -39,6 +64,12 @@ impl Index<Idx<Item>> for Arena {
     fn index(&self, index: Idx<Item>) -> &Self::Output { &self.items[index.idx] }
 }
 
+impl Index<Idx<Expr>> for Arena {
+    type Output = Expr;
+
+    fn index(&self, index: Idx<Expr>) -> &Self::Output { &self.exprs[index.idx] }
+}
+
 impl Index<DefaultSymbol> for Arena {
     type Output = str;
 
@@ -47,12 +78,46 @@ impl Index<DefaultSymbol> for Arena {
     }
 }
 
-#[derive(Copy, Clone)]
 pub struct Idx<T> {
     idx: usize,
     _t: PhantomData<T>,
 }
 
+// not derived, because that would require `T: Copy`, `T: Eq` etc.
+impl<T> Clone for Idx<T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<T> Copy for Idx<T> {}
+
+impl<T> PartialEq for Idx<T> {
+    fn eq(&self, other: &Self) -> bool { self.idx == other.idx }
+}
+
+impl<T> Eq for Idx<T> {}
+
+impl<T> PartialOrd for Idx<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl<T> Ord for Idx<T> {
+    fn cmp(&self, other: &Self) -> Ordering { self.idx.cmp(&other.idx) }
+}
+
+impl<T> Hash for Idx<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.idx.hash(state) }
+}
+
+impl<T> fmt::Debug for Idx<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Idx({})", self.idx)
+    }
+}
+
 impl<T> Idx<T> {
     fn new(idx: usize) -> Self { Self { idx, _t: PhantomData } }
+
+    /// Returns the position of the element in the arena, in the order in which
+    /// the elements of this type were added
+    pub fn index(self) -> usize { self.idx }
 }
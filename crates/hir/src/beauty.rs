@@ -0,0 +1,338 @@
+//! Formats the abstract module tree with the same [`Beauty`] printer as the
+//! parser's `ToBeauty`, so lowered modules can be inspected and compared in
+//! tests. Child nodes are resolved through the [`Arena`], and names through its
+//! string interner.
+
+use ast::{Beauty, Spanned};
+
+use crate::amt::expr::*;
+use crate::amt::literal::{NumberLiteral, StringLiteral};
+use crate::amt::name::{Ident, Operator, UpperIdent};
+use crate::amt::pattern::*;
+use crate::amt::types::{GenericParam, NamedType, TypeArgument, TypeBound};
+use crate::amt::{
+    Class, Enum, Function, FunctionArg, Impl, Item, Name, Namespace, NamespaceKind, Type,
+};
+use crate::arena::{Arena, Idx};
+
+pub trait ToBeauty {
+    fn to_beauty(&self, arena: &Arena) -> Beauty;
+
+    fn to_beauty_string(&self, arena: &Arena) -> String {
+        let mut buf = String::new();
+        self.to_beauty(arena).write_to(&mut buf);
+        buf
+    }
+}
+
+impl<T: ToBeauty> ToBeauty for Spanned<T> {
+    fn to_beauty(&self, arena: &Arena) -> Beauty { self.inner.to_beauty(arena) }
+}
+
+impl<T: ToBeauty + ?Sized> ToBeauty for Box<T> {
+    fn to_beauty(&self, arena: &Arena) -> Beauty { (**self).to_beauty(arena) }
+}
+
+impl<T: ToBeauty> ToBeauty for [T] {
+    fn to_beauty(&self, arena: &Arena) -> Beauty {
+        Beauty::list(self.iter().map(|x| x.to_beauty(arena)).collect())
+    }
+}
+
+impl<T: ToBeauty> ToBeauty for Vec<T> {
+    fn to_beauty(&self, arena: &Arena) -> Beauty { self[..].to_beauty(arena) }
+}
+
+impl<T: ToBeauty> ToBeauty for Option<T> {
+    fn to_beauty(&self, arena: &Arena) -> Beauty {
+        match self {
+            Some(x) => x.to_beauty(arena),
+            None => Beauty::empty(),
+        }
+    }
+}
+
+impl ToBeauty for bool {
+    fn to_beauty(&self, _: &Arena) -> Beauty {
+        Beauty::str(if *self { "true" } else { "false" })
+    }
+}
+
+impl ToBeauty for Idx<Item> {
+    fn to_beauty(&self, arena: &Arena) -> Beauty { arena[*self].to_beauty(arena) }
+}
+
+impl ToBeauty for Idx<Expr> {
+    fn to_beauty(&self, arena: &Arena) -> Beauty { arena[*self].to_beauty(arena) }
+}
+
+macro_rules! beauty_impl {
+    (struct $name:ident { $($field:ident),* $(,)? }) => {
+        impl ToBeauty for $name {
+            fn to_beauty(&self, arena: &Arena) -> Beauty {
+                Beauty::kvs(
+                    stringify!($name),
+                    vec![ $(
+                        Beauty::kv(stringify!($field), self.$field.to_beauty(arena))
+                    ),* ]
+                )
+            }
+        }
+    };
+    (enum $name:ident { $($variant:ident),* $(,)? }) => {
+        impl ToBeauty for $name {
+            fn to_beauty(&self, arena: &Arena) -> Beauty {
+                match self {
+                    $( $name::$variant(f) => f.to_beauty(arena), )*
+                }
+            }
+        }
+    };
+    (unit $name:ident { $($variant:ident),* $(,)? }) => {
+        impl ToBeauty for $name {
+            fn to_beauty(&self, _: &Arena) -> Beauty {
+                match self {
+                    $( $name::$variant => Beauty::str(stringify!($variant)), )*
+                }
+            }
+        }
+    };
+}
+
+/// The `types` and `values` of a namespace refer to its `items`, so only their
+/// names are printed
+impl ToBeauty for Namespace {
+    fn to_beauty(&self, arena: &Arena) -> Beauty {
+        let names = |items: &[Idx<Item>]| {
+            let names = items.iter().map(|&idx| match &arena[idx] {
+                Item::Function(f) => f.name.to_beauty(arena),
+                Item::Class(c) => c.name.to_beauty(arena),
+                Item::Enum(e) => e.name.to_beauty(arena),
+                Item::Impl(_) => Beauty::str("Impl"),
+            });
+            Beauty::list(names.collect())
+        };
+        Beauty::kvs(
+            "Namespace",
+            vec![
+                Beauty::kv("kind", self.kind.to_beauty(arena)),
+                Beauty::kv("items", self.items.to_beauty(arena)),
+                Beauty::kv("types", names(&self.types)),
+                Beauty::kv("values", names(&self.values)),
+            ],
+        )
+    }
+}
+
+beauty_impl! {
+    unit NamespaceKind { Module, Impl, Enum }
+}
+
+beauty_impl! {
+    enum Item { Function, Class, Enum, Impl }
+}
+
+beauty_impl! {
+    struct Function { name, generics, args, body }
+}
+
+beauty_impl! {
+    struct FunctionArg { name, ty }
+}
+
+beauty_impl! {
+    struct Class { name, generics }
+}
+
+beauty_impl! {
+    struct Enum { name, generics }
+}
+
+beauty_impl! {
+    struct Impl { generics, ty, namespace }
+}
+
+beauty_impl! {
+    struct Type { name, impls }
+}
+
+beauty_impl! {
+    enum Name { Operator, Ident, Type }
+}
+
+impl ToBeauty for Ident {
+    fn to_beauty(&self, arena: &Arena) -> Beauty {
+        Beauty::kv("Ident", Beauty::text(self.get(arena)))
+    }
+}
+
+impl ToBeauty for UpperIdent {
+    fn to_beauty(&self, arena: &Arena) -> Beauty {
+        Beauty::kv("UpperIdent", Beauty::text(self.get(arena)))
+    }
+}
+
+impl ToBeauty for Operator {
+    fn to_beauty(&self, arena: &Arena) -> Beauty {
+        Beauty::kv("Operator", Beauty::text(self.get(arena)))
+    }
+}
+
+beauty_impl! {
+    struct GenericParam { name, bounds }
+}
+
+beauty_impl! {
+    enum TypeBound { Interface }
+}
+
+beauty_impl! {
+    struct NamedType { path, name, args }
+}
+
+impl ToBeauty for TypeArgument {
+    fn to_beauty(&self, arena: &Arena) -> Beauty {
+        match self {
+            TypeArgument::Type(t) => t.to_beauty(arena),
+            TypeArgument::Wildcard => Beauty::str("Wildcard"),
+        }
+    }
+}
+
+beauty_impl! {
+    enum Expr {
+        Invokable, Literal, ParenCall, MemberCall, Operation, ShortcircuitingOp,
+        Assignment, TypeAscription, Statement, Lambda, Block, Tuple, Empty,
+        Declaration, Match
+    }
+}
+
+beauty_impl! {
+    struct Invokable { name, generics }
+}
+
+beauty_impl! {
+    enum Literal { NumberLit, StringLit }
+}
+
+impl ToBeauty for NumberLiteral {
+    fn to_beauty(&self, _: &Arena) -> Beauty {
+        use ast::token::NumberLiteral as Number;
+
+        let (key, number) = match *self {
+            NumberLiteral::Int(x) => ("Int", Number::Int(x, None)),
+            NumberLiteral::UInt(x) => ("UInt", Number::UInt(x, None)),
+            NumberLiteral::Float(x) => ("Float", Number::Float(x, None)),
+        };
+        Beauty::kv(key, Beauty::number(number))
+    }
+}
+
+impl ToBeauty for StringLiteral {
+    fn to_beauty(&self, arena: &Arena) -> Beauty {
+        Beauty::kv("StringLiteral", Beauty::text(self.clone().get(arena)))
+    }
+}
+
+beauty_impl! {
+    struct ParenCall { receiver, args }
+}
+
+beauty_impl! {
+    struct MemberCall { receiver, member }
+}
+
+beauty_impl! {
+    struct Operation { operator, lhs, rhs }
+}
+
+beauty_impl! {
+    struct ScOperation { operator, lhs, rhs }
+}
+
+beauty_impl! {
+    unit ScOperator { And, Or }
+}
+
+beauty_impl! {
+    struct Assignment { lhs, rhs }
+}
+
+beauty_impl! {
+    struct TypeAscription { ty, expr }
+}
+
+beauty_impl! {
+    struct Lambda { args, body }
+}
+
+beauty_impl! {
+    struct Block { exprs, ends_with_semicolon }
+}
+
+beauty_impl! {
+    struct Parens { exprs }
+}
+
+impl ToBeauty for Empty {
+    fn to_beauty(&self, _: &Arena) -> Beauty { Beauty::str("Empty") }
+}
+
+beauty_impl! {
+    struct Declaration { decl_kind, name, value }
+}
+
+beauty_impl! {
+    unit DeclKind { Let, Var }
+}
+
+beauty_impl! {
+    struct Match { expr, match_arms }
+}
+
+beauty_impl! {
+    struct MatchArm { pattern, expr }
+}
+
+beauty_impl! {
+    struct FunCallArgument { name, expr }
+}
+
+beauty_impl! {
+    struct LambdaArgument { name, ty }
+}
+
+impl ToBeauty for Pattern {
+    fn to_beauty(&self, arena: &Arena) -> Beauty {
+        match self {
+            Pattern::Wildcard => Beauty::str("Wildcard"),
+            Pattern::Binding(i) => Beauty::kv("Binding", i.to_beauty(arena)),
+            Pattern::Literal(l) => l.to_beauty(arena),
+            Pattern::Range(r) => Beauty::kv("Range", r.to_beauty(arena)),
+            Pattern::RangeExclusive(r) => {
+                Beauty::kv("RangeExclusive", r.to_beauty(arena))
+            }
+            Pattern::Class(c) => c.to_beauty(arena),
+            Pattern::Enum(e) => e.to_beauty(arena),
+            Pattern::TypeAscription(t) => t.to_beauty(arena),
+            Pattern::Or(o) => Beauty::kv("Or", o.to_beauty(arena)),
+            Pattern::Guard(g) => g.to_beauty(arena),
+        }
+    }
+}
+
+beauty_impl! {
+    struct RangePattern { from, to }
+}
+
+beauty_impl! {
+    struct ClassPattern { name, fields }
+}
+
+beauty_impl! {
+    struct EnumPattern { name, field }
+}
+
+beauty_impl! {
+    struct GuardPattern { pattern, guard }
+}
@@ -2,6 +2,10 @@ use amt::Namespace;
 
 pub mod amt;
 pub mod arena;
+pub mod beauty;
+pub mod lower;
+#[cfg(test)]
+mod tests;
 
 pub trait ModuleResolver {
     fn resolve_path(&self) -> &Namespace;
@@ -0,0 +1,451 @@
+//! Lowers the items of a parsed module into the abstract module tree. Names are
+//! interned in the [`Arena`], and every item is allocated in it. Function bodies
+//! are lowered as well; subexpressions that the abstract module tree refers to
+//! by index are allocated in the arena.
+
+use std::collections::HashMap;
+
+use ast::expr as ast_expr;
+use ast::item as ast_item;
+use ast::pattern as ast_pattern;
+use ast::{Spanned, SpannedList, TextRange};
+use string_interner::DefaultSymbol;
+
+use crate::amt::expr::*;
+use crate::amt::literal::{NumberLiteral, StringLiteral};
+use crate::amt::name::{Ident, Operator, UpperIdent};
+use crate::amt::pattern::{ClassPattern, EnumPattern, GuardPattern, Pattern, RangePattern};
+use crate::amt::types::{GenericParam, NamedType, TypeArgument, TypeBound};
+use crate::amt::{
+    Class, Enum, Function, FunctionArg, Impl, Item, Name, Namespace, NamespaceKind, Type,
+};
+use crate::arena::{Arena, Idx};
+
+#[derive(Debug, thiserror::Error)]
+pub enum LowerError {
+    #[error("`{name}` is defined multiple times")]
+    DuplicateName { name: String, first: TextRange, second: TextRange },
+
+    /// A construct that the abstract module tree can't represent yet, e.g. "`if`
+    /// expressions"
+    #[error("{what} can't be lowered yet")]
+    Unsupported { what: &'static str, span: TextRange },
+}
+
+/// Lowers the top-level items of a module. `use` declarations and type aliases
+/// aren't represented in the abstract module tree yet, so they are skipped.
+pub fn lower_items(
+    items: &[Spanned<ast_item::Item>],
+    arena: &mut Arena,
+) -> Result<Namespace, LowerError> {
+    lower_namespace(items, NamespaceKind::Module, arena)
+}
+
+fn lower_namespace(
+    items: &[Spanned<ast_item::Item>],
+    kind: NamespaceKind,
+    arena: &mut Arena,
+) -> Result<Namespace, LowerError> {
+    let mut lowered = Vec::new();
+    let mut types = Vec::new();
+    let mut values = Vec::new();
+    let mut type_names = HashMap::new();
+    let mut value_names = HashMap::new();
+
+    for item in items {
+        let lowered_item = match &item.inner {
+            ast_item::Item::Function(f) => Item::Function(lower_function(f, arena)?),
+            ast_item::Item::Class(c) => Item::Class(lower_class(c, arena)),
+            ast_item::Item::Enum(e) => Item::Enum(lower_enum(e, arena)),
+            ast_item::Item::Impl(i) => Item::Impl(lower_impl(i, arena)?),
+            ast_item::Item::Use(_)
+            | ast_item::Item::TypeAlias(_)
+            | ast_item::Item::Error => continue,
+        };
+
+        let (names, list) = match lowered_item {
+            Item::Function(_) => (Some(&mut value_names), Some(&mut values)),
+            Item::Class(_) | Item::Enum(_) => (Some(&mut type_names), Some(&mut types)),
+            Item::Impl(_) => (None, None),
+        };
+        if let (Some(names), Some(name)) = (names, lowered_item.name()) {
+            check_duplicate(names, name, arena)?;
+        }
+
+        let idx = arena.add_item(lowered_item);
+        if let Some(list) = list {
+            list.push(idx);
+        }
+        lowered.push(item.span.embed(idx));
+    }
+
+    Ok(Namespace { kind, items: lowered.into_boxed_slice(), types, values })
+}
+
+fn check_duplicate(
+    names: &mut HashMap<DefaultSymbol, TextRange>,
+    name: &Spanned<Name>,
+    arena: &Arena,
+) -> Result<(), LowerError> {
+    match names.insert(name.symbol(), name.span) {
+        Some(first) => Err(LowerError::DuplicateName {
+            name: arena[name.symbol()].to_string(),
+            first,
+            second: name.span,
+        }),
+        None => Ok(()),
+    }
+}
+
+fn lower_function(
+    f: &ast_item::Function,
+    arena: &mut Arena,
+) -> Result<Function, LowerError> {
+    let body = match &f.body {
+        Some(body) => {
+            let block = lower_block(body, arena)?;
+            Some(body.span.embed(arena.add_expr(Expr::Block(block))))
+        }
+        None => None,
+    };
+    // the bounds in the `where` clause are merged with the generic parameters
+    let generics = lower_list(&f.generics, arena, |param, arena| GenericParam {
+        name: lower_upper(&param.name, arena),
+        bounds: lower_bounds(f.bounds_of(&param.name), arena),
+    });
+    Ok(Function {
+        name: f.name.map_ref(|name| lower_name(name, arena)),
+        generics,
+        args: f.args.iter().map(|arg| lower_fun_argument(arg, arena)).collect(),
+        body,
+    })
+}
+
+/// The argument's name has no span of its own, so the span of the whole argument
+/// is used
+fn lower_fun_argument(
+    arg: &Spanned<ast_item::FunArgument>,
+    arena: &mut Arena,
+) -> Spanned<FunctionArg> {
+    let name = Name::Ident(Ident::new(arena.add_string(arg.name.get())));
+    let ty = arg.ty.as_ref().map(|ty| {
+        ty.map_ref(|ty| Type { name: lower_upper(&ty.name, arena), impls: Vec::new() })
+    });
+    arg.span.embed(FunctionArg { name: arg.span.embed(name), ty })
+}
+
+fn lower_class(c: &ast_item::Class, arena: &mut Arena) -> Class {
+    Class {
+        name: lower_upper(&c.name, arena).map(Name::Type),
+        generics: lower_generics(&c.generics, arena),
+    }
+}
+
+fn lower_enum(e: &ast_item::Enum, arena: &mut Arena) -> Enum {
+    Enum {
+        name: lower_upper(&e.name, arena).map(Name::Type),
+        generics: lower_generics(&e.generics, arena),
+    }
+}
+
+fn lower_impl(i: &ast_item::Impl, arena: &mut Arena) -> Result<Impl, LowerError> {
+    Ok(Impl {
+        generics: lower_generics(&i.generics, arena),
+        ty: i.ty.map_ref(|ty| lower_named_type(ty, arena)),
+        namespace: lower_namespace(&i.items, NamespaceKind::Impl, arena)?,
+    })
+}
+
+fn lower_name(name: &ast_item::Name, arena: &mut Arena) -> Name {
+    match name {
+        ast_item::Name::Operator(o) => {
+            Name::Operator(Operator::new(arena.add_string(o.get())))
+        }
+        ast_item::Name::Ident(i) => Name::Ident(Ident::new(arena.add_string(i.get()))),
+        ast_item::Name::Type(t) => {
+            Name::Type(UpperIdent::new(arena.add_string(t.get())))
+        }
+    }
+}
+
+fn lower_upper(
+    name: &Spanned<ast::token::UpperIdent>,
+    arena: &mut Arena,
+) -> Spanned<UpperIdent> {
+    name.map_ref(|name| UpperIdent::new(arena.add_string(name.get())))
+}
+
+fn lower_generics(
+    generics: &Spanned<SpannedList<ast_item::GenericParam>>,
+    arena: &mut Arena,
+) -> SpannedList<GenericParam> {
+    lower_list(generics, arena, |param, arena| GenericParam {
+        name: lower_upper(&param.name, arena),
+        bounds: lower_bounds(param.bounds.iter(), arena),
+    })
+}
+
+fn lower_bounds<'a>(
+    bounds: impl Iterator<Item = &'a Spanned<ast_item::TypeBound>>,
+    arena: &mut Arena,
+) -> SpannedList<TypeBound> {
+    bounds
+        .map(|b| b.span.embed(TypeBound::Interface(lower_named_type(&b.ty, arena))))
+        .collect()
+}
+
+fn lower_named_type(ty: &ast_item::NamedType, arena: &mut Arena) -> NamedType {
+    NamedType {
+        path: ty.path.iter().map(|i| lower_ident(i, arena)).collect(),
+        name: lower_upper(&ty.name, arena),
+        args: ty.args.map_ref(|args| lower_type_args(args, arena)),
+    }
+}
+
+fn lower_type_args(
+    args: &[Spanned<ast_item::TypeArgument>],
+    arena: &mut Arena,
+) -> SpannedList<TypeArgument> {
+    lower_list(args, arena, |arg, arena| match arg {
+        ast_item::TypeArgument::Type(t) => TypeArgument::Type(lower_named_type(t, arena)),
+        ast_item::TypeArgument::Wildcard => TypeArgument::Wildcard,
+    })
+}
+
+fn lower_list<T, U>(
+    list: &[Spanned<T>],
+    arena: &mut Arena,
+    mut f: impl FnMut(&T, &mut Arena) -> U,
+) -> SpannedList<U> {
+    list.iter().map(|x| x.map_ref(|x| f(x, arena))).collect()
+}
+
+fn unsupported<T>(what: &'static str, span: TextRange) -> Result<T, LowerError> {
+    Err(LowerError::Unsupported { what, span })
+}
+
+fn lower_expr(
+    expr: &ast_expr::Expr,
+    span: TextRange,
+    arena: &mut Arena,
+) -> Result<Expr, LowerError> {
+    use ast_expr::Expr as E;
+
+    Ok(match expr {
+        E::Invokable(i) => Expr::Invokable(lower_invokable(i, arena)),
+        E::Literal(l) => Expr::Literal(lower_literal(l, span, arena)?),
+        E::ParenCall(c) => Expr::ParenCall(ParenCall {
+            receiver: lower_expr_idx(&c.receiver, arena)?,
+            args: match &c.args {
+                Some(args) => Some(lower_args(args, arena)?),
+                None => None,
+            },
+        }),
+        E::MemberCall(m) => Expr::MemberCall(MemberCall {
+            receiver: lower_expr_idx(&m.receiver, arena)?,
+            member: lower_invokable(&m.member, arena),
+        }),
+        E::Operation(o) => Expr::Operation(Operation {
+            operator: Operator::new(arena.add_string(o.operator.get())),
+            lhs: lower_expr_idx(&o.lhs, arena)?,
+            rhs: lower_expr_idx(&o.rhs, arena)?,
+        }),
+        E::ShortcircuitingOp(o) => Expr::ShortcircuitingOp(ScOperation {
+            operator: match o.operator {
+                ast_expr::ScOperator::And => ScOperator::And,
+                ast_expr::ScOperator::Or => ScOperator::Or,
+            },
+            lhs: lower_expr_idx(&o.lhs, arena)?,
+            rhs: lower_expr_idx(&o.rhs, arena)?,
+        }),
+        E::Assignment(a) => Expr::Assignment(Assignment {
+            lhs: lower_expr_idx(&a.lhs, arena)?,
+            rhs: lower_expr_idx(&a.rhs, arena)?,
+        }),
+        E::TypeAscription(t) => Expr::TypeAscription(lower_type_ascription(t, arena)?),
+        E::Statement(e) => Expr::Statement(Box::new(lower_spanned_expr(e, arena)?)),
+        E::Lambda(l) => Expr::Lambda(Lambda {
+            args: l.args.map_ref(|args| {
+                lower_list(args, arena, |arg, arena| {
+                    let ty = arg.ty.as_ref();
+                    LambdaArgument {
+                        name: lower_ident(&arg.name, arena),
+                        ty: ty.map(|ty| ty.map_ref(|t| lower_named_type(t, arena))),
+                    }
+                })
+            }),
+            body: Box::new(lower_spanned_expr(&l.body, arena)?),
+        }),
+        E::Block(b) => Expr::Block(lower_block(b, arena)?),
+        E::Tuple(p) => Expr::Tuple(Parens { exprs: lower_args(&p.exprs, arena)? }),
+        E::Empty(_) => Expr::Empty(Empty),
+        E::Declaration(d) => Expr::Declaration(Declaration {
+            decl_kind: match d.decl_kind {
+                ast_expr::DeclKind::Let => DeclKind::Let,
+                ast_expr::DeclKind::Var => DeclKind::Var,
+            },
+            name: lower_ident(&d.name, arena),
+            value: Box::new(lower_spanned_expr(&d.value, arena)?),
+        }),
+        E::Match(m) => {
+            let expr = Box::new(lower_spanned_expr(&m.expr, arena)?);
+            let mut match_arms = Vec::with_capacity(m.match_arms.len());
+            for arm in m.match_arms.iter() {
+                match_arms.push(MatchArm {
+                    pattern: lower_pattern(&arm.pattern, arm.pattern.span, arena)?,
+                    expr: lower_expr(&arm.expr, arm.expr.span, arena)?,
+                });
+            }
+            Expr::Match(Match { expr, match_arms })
+        }
+        E::Index(_) => return unsupported("Index expressions", span),
+        E::UnaryOp(_) => return unsupported("Unary operations", span),
+        E::Try(_) => return unsupported("`?` expressions", span),
+        E::ArrayLit(_) => return unsupported("Array literals", span),
+        E::While(_) => return unsupported("`while` loops", span),
+        E::For(_) => return unsupported("`for` loops", span),
+        E::IfExpr(_) => return unsupported("`if` expressions", span),
+        E::Return(_) => return unsupported("`return` expressions", span),
+        E::Break(_) => return unsupported("`break` expressions", span),
+        E::Continue(_) => return unsupported("`continue` expressions", span),
+    })
+}
+
+fn lower_spanned_expr(
+    expr: &Spanned<ast_expr::Expr>,
+    arena: &mut Arena,
+) -> Result<Spanned<Expr>, LowerError> {
+    Ok(expr.span.embed(lower_expr(expr, expr.span, arena)?))
+}
+
+/// Lowers the expression and allocates it in the arena
+fn lower_expr_idx(
+    expr: &Spanned<ast_expr::Expr>,
+    arena: &mut Arena,
+) -> Result<Spanned<Idx<Expr>>, LowerError> {
+    let lowered = lower_expr(expr, expr.span, arena)?;
+    Ok(expr.span.embed(arena.add_expr(lowered)))
+}
+
+fn lower_block(block: &ast_expr::Block, arena: &mut Arena) -> Result<Block, LowerError> {
+    let exprs = block.exprs.iter().map(|e| lower_spanned_expr(e, arena));
+    Ok(Block {
+        exprs: exprs.collect::<Result<_, _>>()?,
+        ends_with_semicolon: block.ends_with_semicolon,
+    })
+}
+
+fn lower_args(
+    args: &[Spanned<ast_expr::FunCallArgument>],
+    arena: &mut Arena,
+) -> Result<SpannedList<FunCallArgument>, LowerError> {
+    let mut lowered = Vec::with_capacity(args.len());
+    for arg in args {
+        let name = match &arg.name {
+            Some(name) => match &name.inner {
+                ast_expr::ArgName::Named(n) => {
+                    Some(name.span.embed(Ident::new(arena.add_string(n.get()))))
+                }
+                ast_expr::ArgName::RecordArg(_) => {
+                    return unsupported("Record arguments", name.span);
+                }
+            },
+            None => None,
+        };
+        let expr = lower_spanned_expr(&arg.expr, arena)?;
+        lowered.push(arg.span.embed(FunCallArgument { name, expr }));
+    }
+    Ok(lowered.into_boxed_slice())
+}
+
+fn lower_invokable(invokable: &ast_expr::Invokable, arena: &mut Arena) -> Invokable {
+    Invokable {
+        name: invokable.name.map_ref(|name| lower_name(name, arena)),
+        generics: invokable.generics.map_ref(|args| lower_type_args(args, arena)),
+    }
+}
+
+fn lower_literal(
+    literal: &ast_expr::Literal,
+    span: TextRange,
+    arena: &mut Arena,
+) -> Result<Literal, LowerError> {
+    use ast::token::NumberLiteral as N;
+
+    Ok(match literal {
+        ast_expr::Literal::NumberLit(n) => Literal::NumberLit(match *n {
+            N::Int(x, _) => NumberLiteral::Int(x),
+            N::UInt(x, _) => NumberLiteral::UInt(x),
+            N::Float(x, _) => NumberLiteral::Float(x),
+        }),
+        ast_expr::Literal::StringLit(s) => {
+            Literal::StringLit(StringLiteral::new(arena.add_string(s.get())))
+        }
+        ast_expr::Literal::CharLit(_) => return unsupported("Char literals", span),
+    })
+}
+
+fn lower_type_ascription(
+    t: &ast_expr::TypeAscription,
+    arena: &mut Arena,
+) -> Result<TypeAscription, LowerError> {
+    Ok(TypeAscription {
+        expr: Box::new(lower_spanned_expr(&t.expr, arena)?),
+        ty: lower_named_type(&t.ty, arena),
+    })
+}
+
+/// Patterns don't have spans, so `span` is the span of the whole pattern
+fn lower_pattern(
+    pattern: &ast_pattern::Pattern,
+    span: TextRange,
+    arena: &mut Arena,
+) -> Result<Pattern, LowerError> {
+    use ast_pattern::Pattern as P;
+
+    let lower_box = |p: &P, arena: &mut Arena| -> Result<Box<Pattern>, LowerError> {
+        Ok(Box::new(lower_pattern(p, span, arena)?))
+    };
+    let lower_vec = |ps: &[P], arena: &mut Arena| -> Result<Vec<Pattern>, LowerError> {
+        ps.iter().map(|p| lower_pattern(p, span, arena)).collect()
+    };
+    let lower_range = |r: &ast_pattern::RangePattern, arena: &mut Arena| {
+        Ok(RangePattern {
+            from: Box::new(lower_pattern(&r.from, span, arena)?),
+            to: Box::new(lower_pattern(&r.to, span, arena)?),
+        })
+    };
+
+    Ok(match pattern {
+        P::Wildcard => Pattern::Wildcard,
+        P::Binding(i) => Pattern::Binding(Ident::new(arena.add_string(i.get()))),
+        P::Literal(l) => Pattern::Literal(lower_literal(l, span, arena)?),
+        P::Tuple(_) => return unsupported("Tuple patterns", span),
+        P::Range(r) => Pattern::Range(lower_range(r, arena)?),
+        P::RangeExclusive(r) => Pattern::RangeExclusive(lower_range(r, arena)?),
+        P::Class(c) => Pattern::Class(ClassPattern {
+            name: Ident::new(arena.add_string(c.name.get())),
+            fields: lower_vec(&c.fields, arena)?,
+        }),
+        P::Enum(e) => Pattern::Enum(EnumPattern {
+            name: Ident::new(arena.add_string(e.name.get())),
+            field: match &e.field {
+                Some(field) => Some(lower_box(field, arena)?),
+                None => None,
+            },
+        }),
+        P::TypeAscription(t) => Pattern::TypeAscription(lower_type_ascription(t, arena)?),
+        P::Or(patterns) => Pattern::Or(lower_vec(patterns, arena)?),
+        P::Guard(g) => {
+            let guard = lower_expr(&g.guard, span, arena)?;
+            Pattern::Guard(GuardPattern {
+                pattern: lower_box(&g.pattern, arena)?,
+                guard: span.embed(arena.add_expr(guard)),
+            })
+        }
+    })
+}
+
+fn lower_ident(name: &Spanned<ast::token::Ident>, arena: &mut Arena) -> Spanned<Ident> {
+    name.map_ref(|name| Ident::new(arena.add_string(name.get())))
+}
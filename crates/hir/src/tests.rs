@@ -0,0 +1,383 @@
+use std::fs::{read_to_string, File};
+use std::io::Write;
+
+use ast::{Spanned, TextRange};
+
+use crate::amt::expr::{Expr, Invokable, Literal, Operation};
+use crate::amt::literal::NumberLiteral;
+use crate::amt::name::{Ident, Operator, UpperIdent};
+use crate::amt::types::GenericParam;
+use crate::amt::{Function, FunctionArg, Item, Name, Namespace, NamespaceKind, Type};
+use crate::arena::Arena;
+use crate::beauty::ToBeauty;
+use crate::lower::{lower_items, LowerError};
+
+fn spanned<T>(inner: T) -> Spanned<T> { TextRange::new(0, 0).embed(inner) }
+
+/// Compares the output with a snapshot file, which is created if it is missing
+fn assert_snapshot(path: &str, actual: &str) {
+    let actual = actual.trim_end();
+    if std::path::Path::new(path).exists() {
+        let expected = read_to_string(path).unwrap();
+        assert_eq!(expected.trim_end(), actual);
+    } else {
+        let mut file = File::create(path).unwrap();
+        file.write_all(actual.as_bytes()).unwrap();
+        file.write_all(b"\n").unwrap();
+        file.flush().unwrap();
+    }
+}
+
+/// A lowered function `add` with a type parameter and two arguments, and the
+/// expression `a + 1`
+#[test]
+fn beauty_function() {
+    let mut arena = Arena::new();
+    let ident = |arena: &mut Arena, s: &str| Ident::new(arena.add_string(s));
+    let upper = |arena: &mut Arena, s: &str| UpperIdent::new(arena.add_string(s));
+
+    let t = upper(&mut arena, "T");
+    let arg = |arena: &mut Arena, name: &str| {
+        let name = spanned(Name::Ident(ident(arena, name)));
+        let ty = spanned(Type { name: spanned(t), impls: Vec::new() });
+        spanned(FunctionArg { name, ty: Some(ty) })
+    };
+    let function = Function {
+        name: spanned(Name::Ident(ident(&mut arena, "add"))),
+        generics: vec![spanned(GenericParam { name: spanned(t), bounds: Box::new([]) })]
+            .into_boxed_slice(),
+        args: vec![arg(&mut arena, "a"), arg(&mut arena, "b")].into_boxed_slice(),
+        body: None,
+    };
+    let function = arena.add_item(Item::Function(function));
+    let namespace = Namespace {
+        kind: NamespaceKind::Module,
+        items: vec![spanned(function)].into_boxed_slice(),
+        types: Vec::new(),
+        values: vec![function],
+    };
+
+    let a = Expr::Invokable(Invokable {
+        name: spanned(Name::Ident(ident(&mut arena, "a"))),
+        generics: spanned(Box::new([])),
+    });
+    let lhs = arena.add_expr(a);
+    let rhs = arena.add_expr(Expr::Literal(Literal::NumberLit(NumberLiteral::Int(1))));
+    let operation = Expr::Operation(Operation {
+        operator: Operator::new(arena.add_string("+")),
+        lhs: spanned(lhs),
+        rhs: spanned(rhs),
+    });
+
+    let actual = namespace.to_beauty_string(&arena) + &operation.to_beauty_string(&arena);
+    assert_snapshot("./tests/function.beauty", &actual);
+}
+
+fn lower_source(source: &str, arena: &mut Arena) -> Result<Namespace, LowerError> {
+    let lexed = lexer::lex(source);
+    let items = parser::parse(lexed.tokens()).unwrap();
+    lower_items(&items, arena)
+}
+
+#[test]
+fn lower_namespace() {
+    let mut arena = Arena::new();
+    let namespace = lower_source(
+        "class Point(x Int, y Int);
+        enum Shape { circle(radius Float), empty }
+        impl Point {
+            fun sum(self Point) Int { self.x + self.y }
+        }
+        use std.io;
+        fun main() Unit {}",
+        &mut arena,
+    )
+    .unwrap();
+
+    assert!(matches!(namespace.kind, NamespaceKind::Module));
+    assert_eq!(namespace.items.len(), 4);
+    assert_eq!(namespace.types.len(), 2);
+    assert_eq!(namespace.values.len(), 1);
+
+    let point = arena.add_string("Point");
+    let main = arena.add_string("main");
+    let sum = arena.add_string("sum");
+    let point = namespace.get_type(point, &arena).unwrap();
+    assert!(matches!(arena[point], Item::Class(_)));
+    let main = namespace.get_value(main, &arena).unwrap();
+    assert!(matches!(arena[main], Item::Function(_)));
+    assert!(namespace.get_type(sum, &arena).is_none());
+    assert!(namespace.get_value(sum, &arena).is_none());
+
+    match &arena[namespace.items[2].inner] {
+        Item::Impl(i) => {
+            assert!(matches!(i.namespace.kind, NamespaceKind::Impl));
+            assert_eq!(i.ty.name.get(&arena), "Point");
+            let sum = i.namespace.get_value(sum, &arena).unwrap();
+            assert_eq!(arena[sum].name().unwrap().symbol(), arena.add_string("sum"));
+        }
+        _ => panic!("expected an impl"),
+    }
+}
+
+#[test]
+fn lower_duplicate_names() {
+    let mut arena = Arena::new();
+    let source = "fun foo() Unit {}\nclass Foo();\nfun foo() Int {}";
+    match lower_source(source, &mut arena) {
+        Err(LowerError::DuplicateName { name, first, second }) => {
+            assert_eq!(name, "foo");
+            assert_eq!(&source[first.start() as usize..first.end() as usize], "foo");
+            assert_eq!(second.start(), 35);
+        }
+        _ => panic!("expected a duplicate name error"),
+    }
+
+    let mut arena = Arena::new();
+    let source = "impl Foo { fun bar() Unit {} fun bar() Unit {} }";
+    assert!(matches!(
+        lower_source(source, &mut arena),
+        Err(LowerError::DuplicateName { .. })
+    ));
+
+    // functions in different namespaces may have the same name
+    let mut arena = Arena::new();
+    assert!(lower_source("fun bar() Unit {}\nimpl Foo { fun bar() Unit {} }", &mut arena)
+        .is_ok());
+}
+
+#[test]
+fn arena_iteration_and_hashing() {
+    use std::collections::HashSet;
+
+    let mut arena = Arena::new();
+    let one = arena.add_expr(Expr::Literal(Literal::NumberLit(NumberLiteral::Int(1))));
+    let two = arena.add_expr(Expr::Literal(Literal::NumberLit(NumberLiteral::Int(2))));
+    let namespace = lower_source("class A();\nfun b() Unit {}\nenum C {}", &mut arena);
+    assert!(namespace.is_ok());
+
+    // the body of `b` is allocated after the literals
+    assert_eq!(arena.exprs_len(), 3);
+    assert_eq!(arena.items_len(), 3);
+    let exprs: Vec<_> = arena.exprs().map(|(idx, _)| idx).collect();
+    assert_eq!(exprs[..2], [one, two]);
+    assert!(matches!(arena[exprs[2]], Expr::Block(_)));
+    assert!(matches!(
+        arena.get_expr(two),
+        Some(Expr::Literal(Literal::NumberLit(NumberLiteral::Int(2))))
+    ));
+
+    let names = |arena: &Arena| -> Vec<String> {
+        let names = arena.items().map(|(_, item)| item.name().unwrap().symbol());
+        names.map(|name| arena[name].to_string()).collect()
+    };
+    assert_eq!(names(&arena), ["A", "b", "C"]);
+    // iterating again yields the same order
+    assert_eq!(names(&arena), ["A", "b", "C"]);
+
+    let items: HashSet<_> = arena.items().map(|(idx, _)| idx).collect();
+    assert_eq!(items.len(), 3);
+    for (idx, item) in arena.items() {
+        assert!(items.contains(&idx));
+        assert!(std::ptr::eq(&arena[idx], item));
+        assert!(arena.get_item(idx).is_some());
+    }
+    assert_eq!(format!("{:?}", one), "Idx(0)");
+    let exprs: HashSet<_> = vec![one, two, one].into_iter().collect();
+    assert_eq!(exprs.len(), 2);
+    assert!(one < two && one != two);
+    assert_eq!(exprs.iter().max(), Some(&two));
+    assert_eq!((one.index(), two.index()), (0, 1));
+    assert!(matches!(arena[two], Expr::Literal(_)));
+
+    // an index from a different arena may be out of bounds
+    let empty = Arena::new();
+    assert!(empty.get_expr(one).is_none());
+}
+
+#[test]
+fn lower_function_bodies() {
+    let mut arena = Arena::new();
+    let namespace = lower_source(
+        "class Point(x Int, y Int);
+        fun dist(p Point) Int {
+            let sq = |a Int| a * a;
+            sq(p.x) + sq(p.y)
+        }",
+        &mut arena,
+    );
+    let namespace = match namespace {
+        Ok(namespace) => namespace,
+        Err(e) => panic!("{}", e),
+    };
+    assert_eq!(namespace.types.len(), 1);
+    assert_eq!(namespace.values.len(), 1);
+
+    let dist = arena.add_string("dist");
+    let dist = match &arena[namespace.get_value(dist, &arena).unwrap()] {
+        Item::Function(f) => f,
+        _ => panic!("expected a function"),
+    };
+    let body = dist.body.as_ref().unwrap();
+    let exprs = match &arena[body.inner] {
+        Expr::Block(b) => &b.exprs,
+        _ => panic!("expected a block"),
+    };
+    assert_eq!(exprs.len(), 2);
+    assert!(matches!(exprs[0].inner, Expr::Declaration(_)));
+
+    // the operands are allocated in the arena
+    let (lhs, rhs) = match &exprs[1].inner {
+        Expr::Operation(o) => (o.lhs.inner, o.rhs.inner),
+        _ => panic!("expected an operation"),
+    };
+    for idx in [lhs, rhs] {
+        match arena.get_expr(idx) {
+            Some(Expr::ParenCall(c)) => {
+                assert!(matches!(arena[c.receiver.inner], Expr::Invokable(_)));
+            }
+            _ => panic!("expected a function call"),
+        }
+    }
+    assert_eq!(
+        exprs[1].to_beauty_string(&arena),
+        "Operation
+   operator: Operator: +
+   lhs
+      ParenCall
+         receiver: Invokable: name: Ident: sq
+         args
+            FunCallArgument
+               expr
+                  MemberCall
+                     receiver: Invokable: name: Ident: p
+                     member: Invokable: name: Ident: x
+   rhs
+      ParenCall
+         receiver: Invokable: name: Ident: sq
+         args
+            FunCallArgument
+               expr
+                  MemberCall
+                     receiver: Invokable: name: Ident: p
+                     member: Invokable: name: Ident: y
+"
+    );
+}
+
+#[test]
+fn lower_unsupported_expr() {
+    let mut arena = Arena::new();
+    let source = "fun f(a Bool) Int { if a { 1 } else { 2 } }";
+    match lower_source(source, &mut arena) {
+        Err(e @ LowerError::Unsupported { span, .. }) => {
+            assert_eq!(e.to_string(), "`if` expressions can't be lowered yet");
+            let text = &source[span.start() as usize..span.end() as usize];
+            assert_eq!(text, "if a { 1 } else { 2 }");
+        }
+        _ => panic!("expected an unsupported expression"),
+    }
+}
+
+#[test]
+fn lower_match() {
+    let mut arena = Arena::new();
+    let source = "fun f(x Int) Int { x match { 0: 1, let y: y } }";
+    let namespace = lower_source(source, &mut arena).unwrap();
+    let f = match &arena[namespace.items[0].inner] {
+        Item::Function(f) => f,
+        _ => panic!("expected a function"),
+    };
+    let block = match &arena[f.body.as_ref().unwrap().inner] {
+        Expr::Block(b) => b,
+        _ => panic!("expected a block"),
+    };
+    assert_eq!(
+        block.exprs[0].to_beauty_string(&arena),
+        "Match
+   expr: Invokable: name: Ident: x
+   match_arms
+      MatchArm
+         pattern: Int: 0
+         expr: Int: 1
+      MatchArm
+         pattern: Binding: Ident: y
+         expr: Invokable: name: Ident: y
+"
+    );
+
+    let mut arena = Arena::new();
+    let source = "fun f(x Int) Int { x match { (let a, let b): a } }";
+    assert!(matches!(
+        lower_source(source, &mut arena),
+        Err(LowerError::Unsupported { what: "Tuple patterns", .. })
+    ));
+}
+
+#[test]
+fn lower_generic_bounds() {
+    let mut arena = Arena::new();
+    let namespace = lower_source(
+        "impl[T Clone Debug] Foo[T] {}
+        fun f[T Eq, U](x T, y U) T where T: Hash, U: Show { x }",
+        &mut arena,
+    )
+    .unwrap();
+
+    let generics: Vec<_> = namespace
+        .items
+        .iter()
+        .map(|item| match &arena[item.inner] {
+            Item::Impl(i) => i.generics.to_beauty_string(&arena),
+            Item::Function(f) => f.generics.to_beauty_string(&arena),
+            _ => panic!("expected an impl or function"),
+        })
+        .collect();
+    assert_eq!(
+        generics,
+        [
+            "GenericParam
+   name: UpperIdent: T
+   bounds
+      NamedType: name: UpperIdent: Clone
+      NamedType: name: UpperIdent: Debug
+",
+            "GenericParam
+   name: UpperIdent: T
+   bounds
+      NamedType: name: UpperIdent: Eq
+      NamedType: name: UpperIdent: Hash
+GenericParam
+   name: UpperIdent: U
+   bounds: NamedType: name: UpperIdent: Show
+",
+        ]
+    );
+}
+
+#[test]
+fn lower_qualified_types() {
+    let mut arena = Arena::new();
+    let namespace =
+        lower_source("fun f[T a.b.C, U C](x T, y U) T { x }", &mut arena).unwrap();
+
+    let generics = match &arena[namespace.items[0].inner] {
+        Item::Function(f) => f.generics.to_beauty_string(&arena),
+        _ => panic!("expected a function"),
+    };
+    assert_eq!(
+        generics,
+        "GenericParam
+   name: UpperIdent: T
+   bounds
+      NamedType
+         path
+            Ident: a
+            Ident: b
+         name: UpperIdent: C
+GenericParam
+   name: UpperIdent: U
+   bounds: NamedType: name: UpperIdent: C
+"
+    );
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use ast::token::Keyword;
+
+/// The words that are lexed as keywords. Words that aren't in the map are lexed
+/// as identifiers instead.
+///
+/// The default contains all keywords of the language. Note that only words
+/// starting with a lowercase letter can be keywords, unless the keywords are
+/// [case-insensitive](Keywords::set_case_insensitive).
+#[derive(Clone)]
+pub struct Keywords {
+    map: HashMap<Box<str>, Keyword>,
+    case_insensitive: bool,
+}
+
+impl Keywords {
+    /// Creates a map without any keywords
+    pub fn empty() -> Self { Keywords { map: HashMap::new(), case_insensitive: false } }
+
+    /// Adds a keyword, returning the keyword that was previously associated with
+    /// the word, if any
+    pub fn insert(&mut self, word: &str, keyword: Keyword) -> Option<Keyword> {
+        self.map.insert(word.into(), keyword)
+    }
+
+    /// Removes a keyword, so the word is lexed as an identifier
+    pub fn remove(&mut self, word: &str) -> Option<Keyword> { self.map.remove(word) }
+
+    pub fn get(&self, word: &str) -> Option<Keyword> {
+        if !self.case_insensitive || !word.bytes().any(|b| b.is_ascii_uppercase()) {
+            return self.map.get(word).copied();
+        }
+        // lowercase the word on the stack; only very long words are allocated
+        let mut buf = [0; 32];
+        match buf.get_mut(..word.len()) {
+            Some(buf) => {
+                buf.copy_from_slice(word.as_bytes());
+                buf.make_ascii_lowercase();
+                let word = std::str::from_utf8(buf).expect("the word is still UTF-8");
+                self.map.get(word).copied()
+            }
+            None => self.map.get(&*word.to_ascii_lowercase()).copied(),
+        }
+    }
+
+    /// When enabled, words are lowercased before they're looked up, so `FUN` and
+    /// `Fun` are lexed as `fun`. The keywords in the map must be lowercase.
+    /// Other words starting with an uppercase letter are still type names.
+    pub fn set_case_insensitive(&mut self, case_insensitive: bool) {
+        self.case_insensitive = case_insensitive;
+    }
+
+    pub fn is_case_insensitive(&self) -> bool { self.case_insensitive }
+
+    /// Returns the default keywords. The map is only built once and shared
+    /// afterwards, so lexing with the default keywords doesn't allocate it.
+    pub(crate) fn shared_default() -> &'static Keywords {
+        static DEFAULT: OnceLock<Keywords> = OnceLock::new();
+        DEFAULT.get_or_init(Keywords::default)
+    }
+}
+
+impl Default for Keywords {
+    fn default() -> Self {
+        let mut keywords = Keywords::empty();
+        for &(word, keyword) in &[
+            ("and", Keyword::And),
+            ("break", Keyword::Break),
+            ("match", Keyword::Match),
+            ("class", Keyword::Class),
+            ("continue", Keyword::Continue),
+            ("else", Keyword::Else),
+            ("enum", Keyword::Enum),
+            ("use", Keyword::Use),
+            ("for", Keyword::For),
+            ("fun", Keyword::Fun),
+            ("if", Keyword::If),
+            ("impl", Keyword::Impl),
+            ("in", Keyword::In),
+            ("let", Keyword::Let),
+            ("not", Keyword::Not),
+            ("or", Keyword::Or),
+            ("return", Keyword::Return),
+            ("type", Keyword::Type),
+            ("var", Keyword::Var),
+            ("where", Keyword::Where),
+            ("while", Keyword::While),
+        ] {
+            keywords.insert(word, keyword);
+        }
+        keywords
+    }
+}
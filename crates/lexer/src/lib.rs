@@ -1,21 +1,97 @@
+mod keywords;
 mod numbers;
+mod strings;
 mod syntax;
 #[cfg(test)]
 mod tests;
 mod tokens;
 
+use std::borrow::Cow;
 use std::fmt;
+use std::ops::Range;
+use std::sync::OnceLock;
 
 use ast::token::Token;
-use ast::{LexError, Spanned};
+use ast::{LexError, LineIndex, Spanned, TextRange};
+pub use ast::unescape;
+pub use keywords::Keywords;
 
+/// Lexes the text. A UTF-8 byte order mark at the start is skipped; the spans of
+/// the tokens still refer to the original text, so the first token starts at
+/// offset 3.
 pub fn lex(text: &str) -> Program {
-    let tokens = tokens::lex(text);
-    Program { tokens }
+    program(text, Cow::Borrowed(Keywords::shared_default()))
 }
 
+/// Lexes the text on demand, without collecting the tokens into a `Vec`. The
+/// tokens are the same as in [`Program::tokens`], ending with `Eof`.
+pub fn tokens(text: &str) -> impl Iterator<Item = Spanned<Token>> + '_ {
+    tokens::Tokens::new(text, Keywords::shared_default())
+}
+
+/// Lexes the text and moves all errors out of the token list, so the valid
+/// tokens can still be parsed.
+pub fn lex_lossless(text: &str) -> LosslessProgram {
+    let (all_tokens, _) = tokens::lex(text, Keywords::shared_default());
+    let mut tokens = Vec::with_capacity(all_tokens.len());
+    let mut errors = Vec::new();
+    for t in all_tokens {
+        match t.lex_error() {
+            Some(e) => errors.push(t.span.embed(e)),
+            None => tokens.push(t),
+        }
+    }
+    LosslessProgram { tokens, errors }
+}
+
+/// The result of [`lex_lossless`]
+#[derive(Debug, Clone)]
+pub struct LosslessProgram {
+    /// All tokens except errors, ending with `Eof`
+    pub tokens: Vec<Spanned<Token>>,
+    pub errors: Vec<Spanned<LexError>>,
+}
+
+/// Returns the program after its source text was edited. Only the tokens near
+/// the edit are lexed again, see [`Program::relex`].
+pub fn relex(old: &Program, edit: TextEdit<'_>) -> Program {
+    let Range { start, end } = Range::from(edit.range);
+    let (before, after) = (&old.text[..start], &old.text[end..]);
+    let full_text = format!("{}{}{}", before, edit.new_text, after);
+
+    let mut program = old.clone();
+    program.relex(edit.range, edit.new_text, &full_text);
+    program
+}
+
+/// Replacement of the text in `range` with `new_text`
+#[derive(Debug, Clone, Copy)]
+pub struct TextEdit<'a> {
+    pub range: TextRange,
+    pub new_text: &'a str,
+}
+
+/// Lexes the text with a custom set of keywords, e.g. for a dialect of the
+/// language. Words that aren't in `keywords` are lexed as identifiers.
+pub fn lex_with_config(text: &str, keywords: &Keywords) -> Program {
+    program(text, Cow::Owned(keywords.clone()))
+}
+
+fn program(text: &str, keywords: Cow<'static, Keywords>) -> Program {
+    let (tokens, comments) = tokens::lex(text, &keywords);
+    Program { tokens, comments, text: text.into(), keywords, line_index: OnceLock::new() }
+}
+
+#[derive(Clone)]
 pub struct Program {
     tokens: Vec<Spanned<Token>>,
+    /// Comments that aren't doc comments; they're skipped by the parser
+    comments: Vec<TextRange>,
+    text: Box<str>,
+    /// The keywords used for re-lexing; only custom keywords are owned
+    keywords: Cow<'static, Keywords>,
+    /// Built on the first call to [`Program::line_index`]
+    line_index: OnceLock<LineIndex>,
 }
 
 impl Program {
@@ -23,6 +99,26 @@ impl Program {
 
     pub fn tokens(&self) -> &[Spanned<Token>] { &self.tokens }
 
+    pub fn iter(&self) -> impl Iterator<Item = &Spanned<Token>> { self.tokens.iter() }
+
+    /// Returns the source text. It is empty if the program was created from a
+    /// list of tokens.
+    pub fn text(&self) -> &str { &self.text }
+
+    /// Returns the source text in `range`, e.g. to quote it in an error message.
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn text_at(&self, range: TextRange) -> &str { &self.text[range] }
+
+    /// Returns all `#` comments, including the `#`. Doc comments (`##`) that
+    /// are followed by an item are tokens instead.
+    pub fn comments(&self) -> Vec<Spanned<&str>> {
+        self.comments
+            .iter()
+            .map(|&r| r.embed(&self.text[r]))
+            .collect()
+    }
+
     pub fn errors(&self) -> Vec<Spanned<LexError>> {
         let mut lex_errors = Vec::new();
         for t in self.tokens() {
@@ -33,6 +129,126 @@ impl Program {
         lex_errors
     }
 
+    /// Returns a [`LineIndex`] of the source text, to convert the spans of tokens
+    /// to lines and columns. It is built on the first call and reused afterwards.
+    pub fn line_index(&self) -> &LineIndex {
+        self.line_index.get_or_init(|| LineIndex::new(&self.text))
+    }
+
+    /// Returns the line and column of a byte offset, both starting at 1.
+    ///
+    /// Panics if the offset is greater than the length of the text.
+    pub fn line_col_of(&self, byte_pos: u32) -> (u32, u32) {
+        self.line_index().line_col(&self.text, byte_pos)
+    }
+
+    /// Renders each error with the line and column where it occurs, e.g.
+    /// `error: Invalid number token at 3:14`. Lines and columns start at 1;
+    /// columns are counted in characters, not bytes.
+    pub fn error_report(&self) -> String {
+        let mut report = String::new();
+        for e in self.errors() {
+            if !report.is_empty() {
+                report.push('\n');
+            }
+            let offset = e.span.start();
+            if self.text.is_empty() || offset as usize > self.text.len() {
+                report.push_str(&format!("error: {} at {:?}", e.inner, e.span));
+            } else {
+                let (line, col) = self.line_col_of(offset);
+                report.push_str(&format!("error: {} at {}:{}", e.inner, line, col));
+            }
+        }
+        report
+    }
+
+    /// Updates the program after its source text was edited, by re-lexing only
+    /// the affected region.
+    ///
+    /// `edit` is the range in the _old_ text that was replaced with `new_text`,
+    /// and `full_text` is the entire text after the edit. Lexing starts before
+    /// the edit and stops as soon as the produced tokens are in sync with the
+    /// old tokens again; the remaining tokens are reused with shifted spans.
+    /// The result is the same as lexing `full_text` from scratch.
+    ///
+    /// Returns the indices of the tokens that were lexed again; all other
+    /// tokens were reused.
+    pub fn relex(
+        &mut self,
+        edit: TextRange,
+        new_text: &str,
+        full_text: &str,
+    ) -> Range<usize> {
+        let delta = new_text.len() as i64 - edit.len() as i64;
+        let edit_end_new = edit.start() as usize + new_text.len();
+
+        // Restart at a token that is preceded by whitespace (or at the start of
+        // the text), so the preceding tokens can't be affected by the edit. Doc
+        // comments depend on the token after them, so they're lexed again too.
+        // Char literals and strings can contain whitespace, so the edit can close
+        // an unterminated quote before it, e.g. the first `'` in `x '= b c'`.
+        let before = self.tokens.iter().take_while(|t| t.span.end() < edit.start());
+        let mut r = before.count().saturating_sub(1);
+        if let Some(quote) = self.tokens[..r].iter().position(|t| {
+            matches!(t.inner, Token::Error(LexError::UnexpectedChar('\'' | '"')))
+        }) {
+            r = quote;
+        }
+        while r > 0
+            && (self.tokens[r - 1].span.end() == self.tokens[r].span.start()
+                || matches!(self.tokens[r - 1].inner, Token::DocComment(_)))
+        {
+            r -= 1;
+        }
+        let start = if r == 0 { 0 } else { self.tokens[r].span.start() as usize };
+
+        let old_text = std::mem::replace(&mut self.text, full_text.into());
+        self.line_index = OnceLock::new();
+        let old_tail = self.tokens.split_off(r);
+        let c = self.comments.iter().take_while(|c| (c.start() as usize) < start).count();
+        let old_comments = self.comments.split_off(c);
+        let mut i = 0;
+
+        let keywords = &self.keywords;
+        let (tokens, comments) = (&mut self.tokens, &mut self.comments);
+        let synced = tokens::lex_from(full_text, start, keywords, tokens, comments, |t| {
+            let (new_start, new_end) = (t.span.start() as usize, t.span.end() as usize);
+            if new_start < edit_end_new {
+                return false;
+            }
+            let old_start = (new_start as i64 - delta) as u32;
+            while i < old_tail.len() && old_tail[i].span.start() < old_start {
+                i += 1;
+            }
+            match old_tail.get(i) {
+                Some(old) if old.span.start() == old_start => {
+                    let old_range = old.span.start() as usize..old.span.end() as usize;
+                    old_text[old_range] == full_text[new_start..new_end]
+                }
+                _ => false,
+            }
+        });
+        let relexed = r..self.tokens.len();
+
+        if synced {
+            let shift = |r: TextRange| {
+                let start = (r.start() as i64 + delta) as u32;
+                let end = (r.end() as i64 + delta) as u32;
+                TextRange::new(start, end)
+            };
+            self.tokens.extend(
+                old_tail[i + 1..].iter().map(|t| shift(t.span).embed(t.inner.clone())),
+            );
+            let synced_at = old_tail[i].span.start();
+            self.comments.extend(
+                old_comments.into_iter().filter(|c| c.start() > synced_at).map(shift),
+            );
+        }
+        relexed
+    }
+
+    /// Removes the `Eof` token at the end. Other tokens are never removed, so
+    /// calling this repeatedly is fine.
     pub fn no_eof(&mut self) {
         match self.tokens.pop() {
             Some(t) if *t != Token::Eof => self.tokens.push(t),
@@ -41,8 +257,14 @@ impl Program {
     }
 }
 
+/// The resulting program has no source text, so [`Program::error_report`] can
+/// only report the byte offsets of errors.
 impl From<Vec<Spanned<Token>>> for Program {
-    fn from(tokens: Vec<Spanned<Token>>) -> Self { Program { tokens } }
+    fn from(tokens: Vec<Spanned<Token>>) -> Self {
+        let keywords = Cow::Borrowed(Keywords::shared_default());
+        let line_index = OnceLock::new();
+        Program { tokens, comments: Vec::new(), text: "".into(), keywords, line_index }
+    }
 }
 
 impl fmt::Debug for Program {
@@ -77,12 +299,15 @@ impl fmt::Display for Program {
             }
             match &**t {
                 Token::Punct(p) => write!(f, "{}", p)?,
-                Token::StringLit(l) => write!(f, "{}", l)?,
+                Token::StringLit(l) => write!(f, "{:?}", l.get())?,
+                Token::CharLit(c) => write!(f, "{:?}", c)?,
                 Token::NumberLit(l) => write!(f, "{}", l)?,
                 Token::Ident(i) => write!(f, "{}", i)?,
+                Token::Label(l) => write!(f, "'{}", l)?,
                 Token::UpperIdent(u) => write!(f, "{}", u)?,
                 Token::Operator(o) => write!(f, "{}", o)?,
                 Token::Keyword(k) => write!(f, "{}", k)?,
+                Token::DocComment(d) => write!(f, "## {}", d)?,
                 Token::Error(e) => write!(f, "{}", e)?,
                 Token::Eof => write!(f, "EOF")?,
             }
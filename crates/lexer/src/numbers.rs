@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 
-use ast::token::{NumberLiteral, Token};
+use ast::token::{NumberLiteral, NumberSuffix, Token};
 use ast::LexError;
 
 trait Int: Copy + 'static {
@@ -61,131 +61,355 @@ fn parse_int_digits<N: Int>(
     Ok(num)
 }
 
+/// Parses the exponent of a float. An exponent that doesn't fit into an `i32` is
+/// clamped, since it either overflows the float (unless the mantissa is zero) or
+/// makes it round to zero. Whether it overflows is decided by the caller.
 fn parse_exp(text: &str) -> Result<i32, LexError> {
-    Ok(match text.chars().next() {
-        Some('+') => parse_int_digits(false, &text[1..], 10)?,
-        Some('-') => parse_int_digits(true, &text[1..], 10)?,
-        _ => parse_int_digits(false, text, 10)?,
-    })
-}
-
-fn parse_at_dot(text: &str) -> Result<f64, LexError> {
-    let text = if text.contains('_') {
-        Cow::Owned(text.chars().filter(|&c| c != '_').collect())
-    } else {
-        Cow::Borrowed(text)
+    if !text.contains(|c: char| c.is_ascii_digit()) {
+        return Err(LexError::MissingExponent);
+    }
+    let clamp = |limit| {
+        move |e| match e {
+            LexError::NumberOverflow => Ok(limit),
+            e => Err(e),
+        }
     };
-    text.parse().map_err(|_| LexError::InvalidNum)
+    match text.chars().next() {
+        Some('+') => parse_int_digits(false, &text[1..], 10).or_else(clamp(i32::MAX)),
+        Some('-') => parse_int_digits(true, &text[1..], 10).or_else(clamp(i32::MIN)),
+        _ => parse_int_digits(false, text, 10).or_else(clamp(i32::MAX)),
+    }
 }
 
-pub(crate) fn leading_dot(input: &str) -> Result<NumberLiteral, LexError> {
-    let exp = input.find(|c: char| c == 'e' || c == 'E');
-
-    let num = if let Some(exp_index) = exp {
-        let exp = parse_exp(&input[exp_index + 1..])?;
-        let num = parse_at_dot(&input[..exp_index])?;
-        num * 10f64.powi(exp)
+/// Parses a decimal float after its characters were checked with
+/// [`check_float_chars`]. `InvalidNum` is only returned for structural problems
+/// such as a second `.`; a number too large for an `f64` is a `NumberOverflow`.
+fn parse_decimal(mantissa: &str, exp: Option<i32>) -> Result<f64, LexError> {
+    let mantissa = if mantissa.contains('_') {
+        Cow::Owned(mantissa.chars().filter(|&c| c != '_').collect())
     } else {
-        parse_at_dot(input)?
+        Cow::Borrowed(mantissa)
     };
+    let num: f64 = match exp {
+        Some(exp) => format!("{}e{}", mantissa, exp).parse(),
+        None => mantissa.parse(),
+    }
+    .map_err(|_| LexError::InvalidNum)?;
+
     if num.is_finite() {
-        Ok(NumberLiteral::Float(num))
+        Ok(num)
     } else {
         Err(LexError::NumberOverflow)
     }
 }
 
+/// Returns an error for the first character that can't appear in a decimal
+/// float, e.g. `f` in `0f.1`. A sign is only allowed at the start and after the
+/// `e` of the exponent.
+fn check_float_chars(input: &str) -> Result<(), LexError> {
+    let text = input.strip_prefix(['+', '-']).unwrap_or(input);
+    let mut prev = None;
+    for c in text.chars() {
+        let is_sign = matches!(c, '+' | '-') && matches!(prev, Some('e') | Some('E'));
+        if !(c.is_ascii_digit() || matches!(c, '_' | '.' | 'e' | 'E') || is_sign) {
+            return Err(LexError::InvalidCharInNum(c));
+        }
+        prev = Some(c);
+    }
+    Ok(())
+}
+
+/// Parses a float such as `.5`, which is parsed like `0.5`
+pub(crate) fn leading_dot(input: &str) -> Result<NumberLiteral, LexError> {
+    float(&format!("0{}", input))
+}
+
 pub(crate) fn float(input: &str) -> Result<NumberLiteral, LexError> {
     let input = input.trim_end_matches('_');
-    if input.ends_with(|c: char| c == 'e' || c == 'E' || c == '.') {
-        return Err(LexError::InvalidNum);
-    }
-    let exp = input.find(|c: char| c == 'e' || c == 'E');
-    let num: f64 = if let Some(exp_index) = exp {
-        let exp = parse_exp(&input[exp_index + 1..])?;
-        let num: String = input[..exp_index].chars().filter(|&c| c != '_').collect();
-        format!("{}e{}", num, exp).parse().map_err(|_| LexError::InvalidNum)?
-    } else {
-        let num: String = input.chars().filter(|&c| c != '_').collect();
-        num.parse().map_err(|_| LexError::InvalidNum)?
-    };
-    if num.is_finite() {
-        Ok(NumberLiteral::Float(num))
-    } else {
-        Err(LexError::NumberOverflow)
+    check_float_chars(input)?;
+    if input.ends_with(['e', 'E']) {
+        return Err(LexError::MissingExponent);
     }
+    let num = match input.find(['e', 'E']) {
+        Some(exp_index) => {
+            let exp = parse_exp(&input[exp_index + 1..])?;
+            parse_decimal(&input[..exp_index], Some(exp))?
+        }
+        None => parse_decimal(input, None)?,
+    };
+    Ok(NumberLiteral::Float(num, None))
 }
 
+/// Parses an integer. Without a sign, it is parsed as an `i64`, unless `unsigned`
+/// is true because the number has an unsigned suffix.
 fn int_with_radix(
     input: &str,
     radix_width: usize,
     radix: u32,
+    unsigned: bool,
 ) -> Result<NumberLiteral, LexError> {
+    // report which type the number doesn't fit into
+    let overflow = |signed| {
+        move |e| match e {
+            LexError::NumberOverflow => LexError::IntOverflow { radix, signed },
+            e => e,
+        }
+    };
+
     Ok(match input.chars().next() {
         Some('-') => {
             let text = input[radix_width + 1..].trim_start_matches('_');
             if text.is_empty() {
-                return Err(LexError::InvalidNum);
+                return Err(LexError::MissingDigits);
             }
-            NumberLiteral::Int(parse_int_digits(true, text, radix)?)
+            let num = parse_int_digits(true, text, radix).map_err(overflow(true))?;
+            NumberLiteral::Int(num, None)
         }
         Some('+') => {
             let text = input[radix_width + 1..].trim_start_matches('_');
             if text.is_empty() {
-                return Err(LexError::InvalidNum);
+                return Err(LexError::MissingDigits);
             }
-            NumberLiteral::UInt(parse_int_digits(false, text, radix)?)
+            let num = parse_int_digits(false, text, radix).map_err(overflow(false))?;
+            NumberLiteral::UInt(num, None)
         }
         _ => {
             let text = input[radix_width..].trim_start_matches('_');
             if text.is_empty() {
-                return Err(LexError::InvalidNum);
+                return Err(LexError::MissingDigits);
+            }
+            if unsigned {
+                let num = parse_int_digits(false, text, radix).map_err(overflow(false))?;
+                NumberLiteral::UInt(num, None)
+            } else {
+                let num = parse_int_digits(false, text, radix).map_err(overflow(true))?;
+                NumberLiteral::Int(num, None)
             }
-            NumberLiteral::Int(parse_int_digits(false, text, radix)?)
         }
     })
 }
 
-pub(crate) fn hex(input: &str) -> Result<NumberLiteral, LexError> {
-    int_with_radix(input, 2, 16)
+/// Parses a float with a mantissa in base 2 or 16 and a binary exponent, e.g.
+/// `0x1.8p3` (1.5 * 2^3). The exponent is required, since the radix of the
+/// fractional part would be ambiguous otherwise.
+///
+/// The mantissa is collected as an integer, so the result is rounded only once,
+/// also when it is subnormal.
+fn float_with_radix(input: &str, radix: u32) -> Result<NumberLiteral, LexError> {
+    let (negative, text) = match input.chars().next() {
+        Some('-') => (true, &input[3..]),
+        Some('+') => (false, &input[3..]),
+        _ => (false, &input[2..]),
+    };
+    let exp_index = text.find(['p', 'P']).ok_or(LexError::MissingExponent)?;
+    let (mantissa, exp) = (&text[..exp_index], &text[exp_index + 1..]);
+    let exp = parse_exp(exp)?;
+
+    let (int_part, fraction) = match mantissa.find('.') {
+        Some(dot) => (&mantissa[..dot], &mantissa[dot + 1..]),
+        None => (mantissa, ""),
+    };
+    if !int_part.contains(|c: char| c != '_') {
+        return Err(LexError::MissingDigits);
+    }
+    let digit = |c: char| c.to_digit(radix).ok_or(LexError::InvalidCharInNum(c));
+    let digit_bits = radix.trailing_zeros() as i64;
+
+    // the number is `mantissa * 2^exp`; digits that don't fit into the mantissa
+    // only matter for rounding, so it's enough to know if any of them is non-zero
+    let mut mantissa = 0u64;
+    let mut exp = exp as i64;
+    let mut sticky = false;
+    for c in int_part.chars().filter(|&c| c != '_') {
+        let d = digit(c)?;
+        if mantissa >> (64 - digit_bits) == 0 {
+            mantissa = mantissa << digit_bits | d as u64;
+        } else {
+            exp += digit_bits;
+            sticky |= d != 0;
+        }
+    }
+    for c in fraction.chars().filter(|&c| c != '_') {
+        let d = digit(c)?;
+        if mantissa >> (64 - digit_bits) == 0 {
+            mantissa = mantissa << digit_bits | d as u64;
+            exp -= digit_bits;
+        } else {
+            sticky |= d != 0;
+        }
+    }
+
+    let num = f64::from_bits(round_to_f64_bits(mantissa, exp, sticky)?);
+    Ok(NumberLiteral::Float(if negative { -num } else { num }, None))
 }
 
-pub(crate) fn oct(input: &str) -> Result<NumberLiteral, LexError> {
-    int_with_radix(input, 2, 8)
+/// Returns the bits of the `f64` closest to `mantissa * 2^exp`, rounding ties to
+/// even. `sticky` means that the exact value is a bit larger than that.
+fn round_to_f64_bits(mantissa: u64, exp: i64, sticky: bool) -> Result<u64, LexError> {
+    // a zero mantissa stays zero, even if the exponent is huge
+    if mantissa == 0 {
+        return Ok(0);
+    }
+    let zeros = mantissa.leading_zeros();
+    let (mantissa, exp) = (mantissa << zeros, exp - zeros as i64);
+
+    // keep 53 bits, or fewer if the number is subnormal, where the lowest bit
+    // has the value 2^-1074
+    let shift = i64::max(11, -1074 - exp);
+    if shift >= 128 {
+        return Ok(0);
+    }
+    let mantissa = mantissa as u128;
+    let mut kept = mantissa >> shift;
+    let rest = mantissa & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    if rest > half || (rest == half && (sticky || kept & 1 == 1)) {
+        kept += 1;
+    }
+
+    if shift > 11 {
+        // subnormal, or rounded up to the smallest normal number
+        return Ok(kept as u64);
+    }
+    let mut exp = exp + shift;
+    if kept == 1 << 53 {
+        kept >>= 1;
+        exp += 1;
+    }
+    let biased_exp = exp + 52 + 1023;
+    if biased_exp >= 0x7FF {
+        return Err(LexError::NumberOverflow);
+    }
+    Ok((biased_exp as u64) << 52 | (kept as u64 & ((1 << 52) - 1)))
+}
+
+pub(crate) fn hex(input: &str, unsigned: bool) -> Result<NumberLiteral, LexError> {
+    if input.contains(['.', 'p', 'P']) {
+        float_with_radix(input, 16)
+    } else {
+        int_with_radix(input, 2, 16, unsigned)
+    }
 }
 
-pub(crate) fn bin(input: &str) -> Result<NumberLiteral, LexError> {
-    int_with_radix(input, 2, 2)
+pub(crate) fn oct(input: &str, unsigned: bool) -> Result<NumberLiteral, LexError> {
+    int_with_radix(input, 2, 8, unsigned)
+}
+
+pub(crate) fn bin(input: &str, unsigned: bool) -> Result<NumberLiteral, LexError> {
+    if input.contains(['.', 'p', 'P']) {
+        float_with_radix(input, 2)
+    } else {
+        int_with_radix(input, 2, 2, unsigned)
+    }
 }
 
-pub(crate) fn dec(input: &str) -> Result<NumberLiteral, LexError> {
-    int_with_radix(input, 0, 10)
+pub(crate) fn dec(input: &str, unsigned: bool) -> Result<NumberLiteral, LexError> {
+    int_with_radix(input, 0, 10, unsigned)
 }
 
 pub(super) fn parse_number(input: &str) -> Token {
+    let without_sign =
+        input.strip_prefix(|c: char| c == '+' || c == '-').unwrap_or(input);
+    let is_hex_int = without_sign.starts_with("0x") || without_sign.starts_with("0X");
+    let is_hex_int = is_hex_int && !without_sign.contains(['.', 'p', 'P']);
+
+    let (digits, suffix) = split_suffix(input, is_hex_int);
+    // parse with the signedness of the suffix, so e.g. `u64::MAX` fits
+    let unsigned = matches!(suffix, Some(s) if s.is_unsigned());
+    let num = parse_without_suffix(digits, unsigned);
+    match suffix {
+        Some(suffix) => into_token(num.and_then(|n| add_suffix(n, suffix))),
+        None => into_token(num),
+    }
+}
+
+fn parse_without_suffix(input: &str, unsigned: bool) -> Result<NumberLiteral, LexError> {
     if input.starts_with('.') {
-        into_token(leading_dot(input))
+        leading_dot(input)
     } else {
         let without_sign =
             input.strip_prefix(|c: char| c == '+' || c == '-').unwrap_or(input);
         if without_sign.starts_with('0') {
             if let Some(x) = without_sign.chars().nth(1) {
                 match x {
-                    'x' | 'X' => return into_token(hex(input)),
-                    'b' | 'B' => return into_token(bin(input)),
-                    'o' | 'O' => return into_token(oct(input)),
+                    'x' | 'X' => return hex(input, unsigned),
+                    'b' | 'B' => return bin(input, unsigned),
+                    'o' | 'O' => return oct(input, unsigned),
                     _ => {}
                 }
             }
         }
-        if without_sign.contains(|c: char| c == '.' || c == 'e' || c == 'E') {
-            into_token(float(input))
+        if without_sign.contains(['.', 'e', 'E']) {
+            float(input)
         } else {
-            into_token(dec(input))
+            dec(input, unsigned)
         }
     }
 }
 
+/// Splits a type suffix such as `i32` off the end of a number literal. In
+/// hexadecimal integers, `f32` and `f64` are digits, not suffixes.
+fn split_suffix(input: &str, is_hex_int: bool) -> (&str, Option<NumberSuffix>) {
+    for &suffix in &NumberSuffix::ALL {
+        if is_hex_int && suffix.is_float() {
+            continue;
+        }
+        if let Some(digits) = input.strip_suffix(suffix.as_str()) {
+            return (digits, Some(suffix));
+        }
+    }
+    (input, None)
+}
+
+/// Checks that the suffix matches the kind of the number and that the number
+/// fits into the suffix's type. Numbers with an unsigned suffix were already
+/// parsed as a `UInt`, unless they are negative.
+fn add_suffix(
+    num: NumberLiteral,
+    suffix: NumberSuffix,
+) -> Result<NumberLiteral, LexError> {
+    let invalid = LexError::InvalidNumSuffix(suffix);
+    let check_bounds = |n: i128| {
+        let (min, max) = int_bounds(suffix);
+        if n < min || n > max {
+            Err(LexError::NumberOverflow)
+        } else {
+            Ok(())
+        }
+    };
+
+    Ok(match num {
+        NumberLiteral::Int(i, _) if suffix.is_signed() => {
+            check_bounds(i as i128)?;
+            NumberLiteral::Int(i, Some(suffix))
+        }
+        NumberLiteral::UInt(u, _) if suffix.is_unsigned() => {
+            check_bounds(u as i128)?;
+            NumberLiteral::UInt(u, Some(suffix))
+        }
+        NumberLiteral::Float(f, _) if suffix.is_float() => {
+            if suffix == NumberSuffix::F32 && (f as f32).is_infinite() {
+                return Err(LexError::NumberOverflow);
+            }
+            NumberLiteral::Float(f, Some(suffix))
+        }
+        _ => return Err(invalid),
+    })
+}
+
+fn int_bounds(suffix: NumberSuffix) -> (i128, i128) {
+    match suffix {
+        NumberSuffix::I8 => (i8::MIN as i128, i8::MAX as i128),
+        NumberSuffix::I16 => (i16::MIN as i128, i16::MAX as i128),
+        NumberSuffix::I32 => (i32::MIN as i128, i32::MAX as i128),
+        NumberSuffix::U8 => (0, u8::MAX as i128),
+        NumberSuffix::U16 => (0, u16::MAX as i128),
+        NumberSuffix::U32 => (0, u32::MAX as i128),
+        NumberSuffix::U64 | NumberSuffix::U128 => (0, i128::MAX),
+        _ => (i128::MIN, i128::MAX),
+    }
+}
+
 fn into_token(result: Result<NumberLiteral, LexError>) -> Token {
     result.map(Token::NumberLit).unwrap_or_else(Token::Error)
 }
@@ -0,0 +1,56 @@
+use ast::token::{StringLiteral, Token};
+use ast::{unescape, LexError};
+
+use super::syntax::find_closing_triple_quote;
+
+pub(super) fn parse_string(input: &str) -> Token {
+    let raw = &input[1..input.len() - 1];
+    match unescape(raw) {
+        Ok(s) => Token::StringLit(StringLiteral::with_raw(s, raw)),
+        Err(e) => Token::Error(e),
+    }
+}
+
+pub(super) fn parse_raw_string(input: &str) -> Token {
+    let hashes = input[1..].find('"').unwrap();
+    let closing_len = hashes + 1;
+    let content_start = hashes + 2;
+    // compare bytes, since the input can end in a multibyte character if the
+    // string is unterminated
+    let bytes = input.as_bytes();
+    let closing = &bytes[bytes.len().saturating_sub(closing_len)..];
+
+    if input.len() < content_start + closing_len
+        || closing[0] != b'"'
+        || !closing[1..].iter().all(|&b| b == b'#')
+    {
+        return Token::Error(LexError::UnterminatedString);
+    }
+    let raw = &input[content_start..input.len() - closing_len];
+    Token::StringLit(StringLiteral::new_raw(raw))
+}
+
+pub(super) fn parse_multi_line_string(input: &str) -> Token {
+    let raw = match find_closing_triple_quote(&input[3..]) {
+        Some(i) if i + 6 == input.len() => &input[3..3 + i],
+        _ => return Token::Error(LexError::UnterminatedString),
+    };
+    match unescape(raw) {
+        Ok(s) => Token::StringLit(StringLiteral::with_raw(s, raw)),
+        Err(e) => Token::Error(e),
+    }
+}
+
+pub(super) fn parse_char(input: &str) -> Token {
+    let raw = &input[1..input.len() - 1];
+    match unescape(raw) {
+        Ok(s) => {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Token::CharLit(c),
+                _ => Token::Error(LexError::InvalidChar),
+            }
+        }
+        Err(e) => Token::Error(e),
+    }
+}
@@ -1,12 +1,15 @@
-use ast::token::{Keyword, Punctuation};
-use logos::Logos;
+use ast::token::Punctuation;
+use logos::{Lexer, Logos};
 
 /// Intermediate token type for lexing
 #[derive(Logos)]
 pub(super) enum IToken<'a> {
-    #[regex(r"([ \t\n\f]+|#.*)+")]
+    #[regex(r"[ \t\r\n\f]+")]
     Ws,
 
+    #[regex(r"#.*")]
+    Comment(&'a str),
+
     #[token(".", |_| Punctuation::Dot)]
     #[token(",", |_| Punctuation::Comma)]
     #[token(":", |_| Punctuation::Colon)]
@@ -17,6 +20,7 @@ pub(super) enum IToken<'a> {
     #[token("\\", |_| Punctuation::Backslash)]
     #[token("@", |_| Punctuation::At)]
     #[token("_", |_| Punctuation::Underscore)]
+    #[token("?", |_| Punctuation::QuestionMark)]
     #[token("(", |_| Punctuation::OpenParen)]
     #[token(")", |_| Punctuation::CloseParen)]
     #[token("[", |_| Punctuation::OpenBracket)]
@@ -29,35 +33,109 @@ pub(super) enum IToken<'a> {
         r"[+-]?\d[a-zA-Z_+\-*/%~<>=!0-9]*(\.\d[a-zA-Z_+\-*/%~<>=!?0-9]*)?",
         priority = 2
     )]
+    // in hexadecimal floats, the fractional part can start with a letter
+    #[regex(
+        r"[+-]?0[xX][0-9a-fA-F_]*\.[0-9a-fA-F][a-zA-Z_+\-*/%~<>=!?0-9]*",
+        priority = 3
+    )]
     #[regex(r"\.\d[a-zA-Z_+\-*/%~<>=!?0-9]*")]
     NumberLit(&'a str),
 
     #[regex(r#""([^"\\]|\\.)*""#)]
     StringLit(&'a str),
 
+    /// A char literal, or a quote followed by a label
+    #[token("'", char_or_label)]
+    Quoted(Quoted<'a>),
+
+    #[regex(r#"r#*""#, raw_string)]
+    RawStringLit(&'a str),
+
+    #[token(r#"""""#, multi_line_string)]
+    MultiLineStringLit(&'a str),
+
     #[regex(r"[a-zA-Z_+\-*/%~<>=!?][a-zA-Z_+\-*/%~<>=!?0-9]*", priority = 1)]
     Word(&'a str),
 
     #[error]
     Error,
+
+    /// Replaces `Error` in `RawTokens`, so the character can be reported
+    UnexpectedChar(char),
 }
 
+pub(super) enum Quoted<'a> {
+    /// A char literal including the quotes, e.g. `'a'`
+    CharLit(&'a str),
+    /// The name of a label without the quote, e.g. `outer` in `'outer`
+    Label(&'a str),
+}
+
+/// Consumes a label (a quote followed by a lowercase word that isn't followed by
+/// a quote or backslash), or a char literal up to the closing quote. An unterminated
+/// char literal is an error.
+fn char_or_label<'a>(lex: &mut Lexer<'a, IToken<'a>>) -> Option<Quoted<'a>> {
+    let rest = lex.remainder();
+    let is_word_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let word_len = rest.find(|c| !is_word_char(c)).unwrap_or(rest.len());
+    if rest.starts_with(|c: char| c.is_ascii_lowercase())
+        && !rest[word_len..].starts_with(['\'', '\\'])
+    {
+        lex.bump(word_len);
+        return Some(Quoted::Label(&lex.slice()[1..]));
+    }
+
+    let mut chars = rest.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' => {
+                lex.bump(i + 1);
+                return Some(Quoted::CharLit(lex.slice()));
+            }
+            '\\' => match chars.next() {
+                Some((_, '\n')) | None => return None,
+                Some(_) => {}
+            },
+            '\n' => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Consumes a raw string up to the closing `"` followed by as many `#` as in the
+/// opening delimiter, or until the end of the input if it is unterminated
+fn raw_string<'a>(lex: &mut Lexer<'a, IToken<'a>>) -> &'a str {
+    let hashes = lex.slice().len() - 2;
+    let closing = format!("\"{}", "#".repeat(hashes));
+    match lex.remainder().find(&closing) {
+        Some(i) => lex.bump(i + closing.len()),
+        None => lex.bump(lex.remainder().len()),
+    }
+    lex.slice()
+}
+
+/// Consumes a multi-line string up to the closing `"""`, or until the end of the
+/// input if it is unterminated
+fn multi_line_string<'a>(lex: &mut Lexer<'a, IToken<'a>>) -> &'a str {
+    let rest = lex.remainder();
+    match find_closing_triple_quote(rest) {
+        Some(i) => lex.bump(i + 3),
+        None => lex.bump(rest.len()),
+    }
+    lex.slice()
+}
 
-pub(super) fn parse_keyword(s: &str) -> Option<Keyword> {
-    Some(match s {
-        "and" => Keyword::And,
-        "match" => Keyword::Match,
-        "class" => Keyword::Class,
-        "enum" => Keyword::Enum,
-        "use" => Keyword::Use,
-        "for" => Keyword::For,
-        "fun" => Keyword::Fun,
-        "impl" => Keyword::Impl,
-        "let" => Keyword::Let,
-        "not" => Keyword::Not,
-        "or" => Keyword::Or,
-        "type" => Keyword::Type,
-        "var" => Keyword::Var,
-        _ => return None,
-    })
+/// Returns the index of the first `"""` that isn't preceded by an escaping
+/// backslash
+pub(super) fn find_closing_triple_quote(s: &str) -> Option<usize> {
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if s[i..].starts_with(r#"""""#) {
+            return Some(i);
+        }
+    }
+    None
 }
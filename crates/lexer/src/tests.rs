@@ -1,7 +1,11 @@
+use std::borrow::Cow;
 use std::ffi::OsStr;
 use std::fs::{read_to_string, File};
 use std::io::Write;
 
+use ast::token::{Ident, Keyword, NumberLiteral, Punctuation, StringLiteral, Token};
+use ast::{LexError, TextRange};
+
 #[test]
 fn run_lexer_tests() {
     for file in std::fs::read_dir("./tests").unwrap() {
@@ -10,7 +14,7 @@ fn run_lexer_tests() {
             let content: String = read_to_string(&path).unwrap();
             let content = content.trim_end();
 
-            let p = crate::lex(&content);
+            let p = crate::lex(content);
             let formatted = format!("{:<#?}", p);
 
             let tokens_path = path.with_extension("tokens");
@@ -36,3 +40,715 @@ fn run_lexer_tests() {
         }
     }
 }
+
+fn lex_string(text: &str) -> Token {
+    let program = crate::lex(text);
+    assert_eq!(program.token_len(), 2, "{:?}", program);
+    program.tokens()[0].inner.clone()
+}
+
+#[test]
+fn string_literal() {
+    let token = lex_string(r#""hello, world!""#);
+    assert_eq!(token, Token::StringLit(StringLiteral::new("hello, world!")));
+}
+
+#[test]
+fn string_escapes() {
+    let cases = [
+        (r#""plain""#, "plain"),
+        (r#""a\nb""#, "a\nb"),
+        (r#""a\tb""#, "a\tb"),
+        (r#""a\\b""#, "a\\b"),
+        (r#""a\"b""#, "a\"b"),
+        (r#""\u{41}""#, "A"),
+        (r#""\u{10FFFF}""#, "\u{10FFFF}"),
+        (r#""a\rb""#, "a\rb"),
+        (r#""a\0b""#, "a\0b"),
+        (r#""\x41\x7f\x0A""#, "A\x7f\n"),
+    ];
+    for &(input, expected) in &cases {
+        assert_eq!(lex_string(input), Token::StringLit(StringLiteral::new(expected)));
+    }
+}
+
+#[test]
+fn invalid_string_escapes() {
+    let cases = [
+        (r#""\q""#, 'q'),
+        (r#""\u""#, 'u'),
+        (r#""\u{""#, 'u'),
+        (r#""\u{}""#, 'u'),
+        (r#""\u{41""#, 'u'),
+        (r#""\u{1234567}""#, 'u'),
+        (r#""\u{D800}""#, 'u'),
+        (r#""\u{xyz}""#, 'u'),
+        (r#""\x""#, 'x'),
+        (r#""\x4""#, 'x'),
+        (r#""\x4g""#, 'x'),
+        (r#""\x80""#, 'x'),
+    ];
+    for &(input, c) in &cases {
+        let expected = Token::Error(LexError::InvalidEscape(c));
+        assert_eq!(lex_string(input), expected, "{}", input);
+    }
+}
+
+#[test]
+fn doc_comments_are_not_errors() {
+    let program = crate::lex("## Docs\r\n# comment\nfun");
+    let tokens: Vec<_> = program.tokens().iter().map(|t| t.inner.clone()).collect();
+    assert_eq!(tokens, vec![
+        Token::DocComment("Docs".into()),
+        Token::Keyword(Keyword::Fun),
+        Token::Eof,
+    ]);
+    assert_eq!(program.errors(), vec![]);
+}
+
+#[test]
+fn comments_are_trivia() {
+    let program = crate::lex("# hi\nfun f()");
+    assert_eq!(program.comments(), vec![TextRange::new(0, 4).embed("# hi")]);
+    assert_eq!(program.tokens()[0].span, TextRange::new(5, 8));
+
+    let program = crate::lex("x # a\r\n#\ny # b");
+    let comments = program.comments();
+    assert_eq!(comments, vec![
+        TextRange::new(2, 5).embed("# a"),
+        TextRange::new(7, 8).embed("#"),
+        TextRange::new(11, 14).embed("# b"),
+    ]);
+}
+
+#[test]
+fn doc_comments_without_item_are_comments() {
+    let program = crate::lex("x ## a\ny\n##### banner\n## b\nfun {\n    ## c\n}\n## d");
+    let tokens: Vec<_> = program.tokens().iter().map(|t| t.inner.clone()).collect();
+    assert_eq!(tokens, vec![
+        Token::Ident(Ident::new("x")),
+        Token::Ident(Ident::new("y")),
+        Token::DocComment("b".into()),
+        Token::Keyword(Keyword::Fun),
+        Token::Punct(Punctuation::OpenBrace),
+        Token::Punct(Punctuation::CloseBrace),
+        Token::Eof,
+    ]);
+    let comments: Vec<_> = program.comments().into_iter().map(|c| c.inner).collect();
+    assert_eq!(comments, ["## a", "##### banner", "## c", "## d"]);
+}
+
+#[test]
+fn error_report_positions() {
+    let program = crate::lex("\"😀🎉\" 12x3\n  `");
+    assert_eq!(
+        program.error_report(),
+        "error: Invalid char 'x' in number literal at 1:6\n\
+         error: Unexpected character '`' at 2:3"
+    );
+
+    let program = crate::lex("a\r\nb\r\n  😀 0x\r\n");
+    assert_eq!(
+        program.error_report(),
+        "error: Unexpected character '😀' at 3:3\n\
+         error: Missing digits in number literal at 3:5"
+    );
+
+    assert_eq!(crate::lex("fun x() Unit {}").error_report(), "");
+}
+
+#[test]
+fn string_raw_text() {
+    let token = lex_string(r#""tab:\t, quote:\"""#);
+    match token {
+        Token::StringLit(s) => {
+            assert_eq!(s.get(), "tab:\t, quote:\"");
+            assert_eq!(s.raw(), r#"tab:\t, quote:\""#);
+        }
+        t => panic!("expected string literal, got {:?}", t),
+    }
+}
+
+/// A simple xorshift PRNG, so the tests are deterministic
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self, max: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % max as u64) as usize
+    }
+}
+
+#[test]
+fn relex_random_edits() {
+    const SNIPPETS: &[&str] = &[
+        "", " ", "\n", "x", "X", "+", "1", ".5", "\"", "\\", "# comment", "#", "(",
+        "😀", "\"string\"", "fun f() Unit {}", "## doc\n", "fun", "@", "'", "'a'",
+        "'a", "\"\"\"",
+    ];
+
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let mut sources = vec!["x '= b c".to_string(), "x \"a\\\n b".to_string()];
+    for file in std::fs::read_dir("./tests").unwrap() {
+        let path = file.unwrap().path();
+        if path.is_file() && path.extension() == Some(OsStr::new("wa")) {
+            sources.push(read_to_string(&path).unwrap());
+        }
+    }
+
+    for source in &sources {
+        for _ in 0..300 {
+            let mut program = crate::lex(source);
+            let mut text = source.clone();
+            // apply several edits in a row to the same program
+            for _ in 0..3 {
+                let start = rng.next(text.len() + 1);
+                let end = (start + rng.next(6)).min(text.len());
+                if !text.is_char_boundary(start) || !text.is_char_boundary(end) {
+                    continue;
+                }
+                let snippet = SNIPPETS[rng.next(SNIPPETS.len())];
+                let new_text = format!("{}{}{}", &text[..start], snippet, &text[end..]);
+
+                program.relex(TextRange::from(start..end), snippet, &new_text);
+                text = new_text;
+
+                let expected = crate::lex(&text);
+                let formatted = format!("{:?}", expected);
+                assert_eq!(format!("{:?}", program), formatted, "{:?}", text);
+                assert_eq!(program.comments(), expected.comments(), "{:?}", text);
+            }
+        }
+    }
+}
+
+#[test]
+fn raw_strings() {
+    let token = lex_string(r#"r"hello\nworld""#);
+    match token {
+        Token::StringLit(s) => {
+            assert!(s.is_raw());
+            assert_eq!(s.get(), r"hello\nworld");
+            assert_eq!(s.raw(), r"hello\nworld");
+            assert_eq!(s.decoded().unwrap(), r"hello\nworld");
+        }
+        t => panic!("expected string literal, got {:?}", t),
+    }
+    assert_eq!(lex_string(r#"r"""#), Token::StringLit(StringLiteral::new("")));
+    assert_eq!(lex_string(r#"r"\q\""#), Token::StringLit(StringLiteral::new(r"\q\")));
+}
+
+#[test]
+fn raw_strings_with_hashes() {
+    let cases = [
+        (r####"r#"hello"#"####, "hello"),
+        (r####"r#"contains "quotes""#"####, r#"contains "quotes""#),
+        (r####"r##"contains "# and \n"##"####, r##"contains "# and \n"##),
+        (r####"r#""#"####, ""),
+    ];
+    for &(input, expected) in &cases {
+        match lex_string(input) {
+            Token::StringLit(s) => {
+                assert!(s.is_raw());
+                assert_eq!(s.get(), expected);
+            }
+            t => panic!("expected string literal for {}, got {:?}", input, t),
+        }
+    }
+    for &input in &[r#"r"abc"#, r##"r#"abc""##, r###"r##"abc"#"###, r#"r#""#] {
+        let expected = Token::Error(LexError::UnterminatedString);
+        assert_eq!(lex_string(input), expected, "{}", input);
+    }
+    // the input ends in a multibyte character
+    for &input in &["r\"€", "r#\"€", "r##\"😀", "r#\"a\"€"] {
+        let expected = Token::Error(LexError::UnterminatedString);
+        assert_eq!(lex_string(input), expected, "{}", input);
+    }
+}
+
+#[test]
+fn multi_line_strings() {
+    let input = "\"\"\"first line\n  \"quoted\" and \"\"double quoted\"\"\n\\tlast\"\"\"";
+    match lex_string(input) {
+        Token::StringLit(s) => {
+            assert!(!s.is_raw());
+            let expected = "first line\n  \"quoted\" and \"\"double quoted\"\"\n\tlast";
+            assert_eq!(s.get(), expected);
+            assert_eq!(s.raw(), &input[3..input.len() - 3]);
+        }
+        t => panic!("expected string literal, got {:?}", t),
+    }
+    assert_eq!(lex_string(r#""""""""#), Token::StringLit(StringLiteral::new("")));
+
+    for &input in &[r#"""""#, r#""""abc"#, r#""""abc"""#, r#""""abc\""""#] {
+        let expected = Token::Error(LexError::UnterminatedString);
+        assert_eq!(lex_string(input), expected, "{}", input);
+    }
+}
+
+#[test]
+fn char_literals() {
+    let cases = [
+        ("'a'", 'a'),
+        ("'😀'", '😀'),
+        ("'\"'", '"'),
+        (r"'\n'", '\n'),
+        (r"'\''", '\''),
+        (r"'\\'", '\\'),
+        (r"'\x41'", 'A'),
+        (r"'\u{1F600}'", '😀'),
+    ];
+    for &(input, expected) in &cases {
+        assert_eq!(lex_string(input), Token::CharLit(expected), "{}", input);
+    }
+
+    for &input in &["''", "'ab'", r"'\n\n'", r"'a\t'"] {
+        assert_eq!(lex_string(input), Token::Error(LexError::InvalidChar), "{}", input);
+    }
+    assert_eq!(lex_string(r"'\q'"), Token::Error(LexError::InvalidEscape('q')));
+}
+
+#[test]
+fn labels() {
+    for &(input, expected) in &[("'outer", "outer"), ("'a", "a"), ("'loop_2", "loop_2")] {
+        assert_eq!(lex_string(input), Token::Label(Ident::new(expected)), "{}", input);
+    }
+    assert_eq!(lex_string("'a'"), Token::CharLit('a'));
+
+    let program = crate::lex("'outer: while x { break 'outer }");
+    let kinds: Vec<_> = program.tokens().iter().map(|t| t.inner.kind()).collect();
+    assert_eq!(kinds[0], ast::token::TokenKind::Label);
+    assert_eq!(kinds[kinds.len() - 3], ast::token::TokenKind::Label);
+}
+
+#[test]
+fn custom_keywords() {
+    use crate::Keywords;
+
+    let mut keywords = Keywords::default();
+    assert!(keywords.remove("match") == Some(Keyword::Match));
+    keywords.insert("loop", Keyword::While);
+
+    let program = crate::lex_with_config("x match loop", &keywords);
+    let tokens: Vec<_> = program.tokens().iter().map(|t| t.inner.clone()).collect();
+    assert_eq!(tokens, vec![
+        Token::Ident(Ident::new("x")),
+        Token::Ident(Ident::new("match")),
+        Token::Keyword(Keyword::While),
+        Token::Eof,
+    ]);
+
+    let mut program = crate::lex_with_config("x match", &Keywords::empty());
+    program.relex(TextRange::from(0..1), "let", "let match");
+    assert_eq!(format!("{:?}", program), "[i`let` @ 0..3 i`match` @ 4..9 EOF @ 9..9]");
+
+    // the default keywords are shared instead of being copied into each program
+    assert!(matches!(program.keywords, Cow::Owned(_)));
+    let program = crate::lex("x");
+    let shared = Keywords::shared_default();
+    assert!(matches!(program.keywords, Cow::Borrowed(k) if std::ptr::eq(k, shared)));
+}
+
+#[test]
+fn relex_small_window() {
+    use crate::TextEdit;
+
+    let text = "fun foo(a Int) Int {\n    let bar = a + 1;\n    bar * 2\n}\n";
+    let old = crate::lex(text);
+
+    // insert a character in the middle of `bar`
+    let pos = text.find("bar").unwrap() + 1;
+    let edit = TextEdit { range: TextRange::from(pos..pos), new_text: "x" };
+    let new = crate::relex(&old, edit);
+    let new_text = format!("{}x{}", &text[..pos], &text[pos..]);
+    assert_eq!(format!("{:?}", new), format!("{:?}", crate::lex(&new_text)));
+    assert_eq!(format!("{:?}", old), format!("{:?}", crate::lex(text)));
+
+    let mut program = crate::lex(text);
+    let relexed = program.relex(edit.range, edit.new_text, &new_text);
+    assert!(relexed.len() <= 3, "{:?}", relexed);
+    let bxar = Token::Ident(Ident::new("bxar"));
+    assert_eq!(program.tokens()[relexed.start + 1].inner, bxar);
+
+    // replace the `1` with `10`
+    let pos = text.find('1').unwrap();
+    let edit = TextEdit { range: TextRange::from(pos..pos + 1), new_text: "10" };
+    let new = crate::relex(&old, edit);
+    let new_text = text.replace('1', "10");
+    assert_eq!(format!("{:?}", new), format!("{:?}", crate::lex(&new_text)));
+
+    // the edit closes a char literal that contains whitespace
+    let old = crate::lex("x '= b c");
+    let edit = TextEdit { range: TextRange::from(8..8), new_text: "'" };
+    let new = crate::relex(&old, edit);
+    assert_eq!(format!("{:?}", new), format!("{:?}", crate::lex("x '= b c'")));
+    assert_eq!(new.errors(), vec![TextRange::new(2, 9).embed(LexError::InvalidChar)]);
+}
+
+#[test]
+fn lossless_errors() {
+    let lexed = crate::lex_lossless("fun 0x_ f()");
+    let tokens: Vec<Token> = lexed.tokens.into_iter().map(|t| t.inner).collect();
+    let expected = [
+        Token::Keyword(Keyword::Fun),
+        Token::Ident(Ident::new("f")),
+        Token::Punct(Punctuation::OpenParen),
+        Token::Punct(Punctuation::CloseParen),
+        Token::Eof,
+    ];
+    assert_eq!(tokens, expected);
+    assert_eq!(lexed.errors, vec![TextRange::new(4, 7).embed(LexError::MissingDigits)]);
+
+    let lexed = crate::lex_lossless("f(1)");
+    assert_eq!(lexed.tokens.len(), 5);
+    assert_eq!(lexed.errors, vec![]);
+}
+
+#[test]
+fn radix_floats() {
+    use ast::token::NumberLiteral;
+
+    let float = |text: &str| match lex_string(text) {
+        Token::NumberLit(NumberLiteral::Float(f, None)) => f,
+        t => panic!("expected float, got {:?}", t),
+    };
+    assert_eq!(float("0x1p4"), 16.0);
+    assert_eq!(float("0x1.8p1"), 3.0);
+    assert_eq!(float("0X1.8P1"), 3.0);
+    assert_eq!(float("0xA_0p-4"), 10.0);
+    assert_eq!(float("-0x1p+1"), -2.0);
+    assert_eq!(float("0b1.1p2"), 6.0);
+    assert_eq!(float("0x1.0000_1p0"), 1.0 + 16f64.powi(-5));
+    assert_eq!(float("0x1.ap0"), 1.625);
+    assert_eq!(float("0xf.fp0"), 15.9375);
+
+    // subnormal numbers
+    assert_eq!(float("0x1p-1074"), f64::from_bits(1));
+    assert_eq!(float("0x1.8p-1074"), f64::from_bits(2));
+    assert_eq!(float("0x1p-1075"), 0.0);
+    assert_eq!(float("0x1.0000_0000_0000_0000_01p-1075"), f64::from_bits(1));
+    assert_eq!(float("0x0.fffffffffffffp-1022"), f64::from_bits((1 << 52) - 1));
+    assert_eq!(float("0x1p-1022"), f64::MIN_POSITIVE);
+    assert_eq!(float("0x1p-99999"), 0.0);
+
+    // the largest exponents
+    assert_eq!(float("0x0.8p1024"), 2f64.powi(1023));
+    assert_eq!(float("0x1.fffffffffffffp1023"), f64::MAX);
+    assert_eq!(float("0x1.fffffffffffff7ffp1023"), f64::MAX);
+    assert_eq!(float("0x0.000_01p1043"), 2f64.powi(1023));
+    assert_eq!(float("0x0p99999"), 0.0);
+    let overflow = Token::Error(LexError::NumberOverflow);
+    assert_eq!(lex_string("0x1.fffffffffffff8p1023"), overflow);
+    assert_eq!(lex_string("0x1p1024"), overflow);
+
+    assert_eq!(lex_string("0x1.8"), Token::Error(LexError::MissingExponent));
+    assert_eq!(lex_string("0x1p"), Token::Error(LexError::MissingExponent));
+    assert_eq!(lex_string("0x1p-"), Token::Error(LexError::MissingExponent));
+    assert_eq!(lex_string("0b1.2p0"), Token::Error(LexError::InvalidCharInNum('2')));
+    assert_eq!(lex_string("0x1p9999"), Token::Error(LexError::NumberOverflow));
+    assert_eq!(lex_string("0x1e5"), Token::NumberLit(NumberLiteral::Int(0x1e5, None)));
+}
+
+#[test]
+fn program_line_index() {
+    let text = "fun f()\r\n  g(\"ä\")";
+    let program = crate::lex(text);
+    let index = program.line_index();
+    let positions: Vec<_> = program
+        .tokens()
+        .iter()
+        .map(|t| (&text[t.span], index.line_col(text, t.span.start())))
+        .collect();
+    assert_eq!(
+        positions,
+        vec![
+            ("fun", (1, 1)),
+            ("f", (1, 5)),
+            ("(", (1, 6)),
+            (")", (1, 7)),
+            ("g", (2, 3)),
+            ("(", (2, 4)),
+            ("\"ä\"", (2, 5)),
+            (")", (2, 8)),
+            ("", (2, 9)),
+        ]
+    );
+}
+
+#[test]
+fn line_col_of() {
+    let program = crate::lex("fun f()\n  g()\n");
+    assert_eq!(program.line_col_of(0), (1, 1));
+    assert_eq!(program.line_col_of(4), (1, 5));
+    assert_eq!(program.line_col_of(7), (1, 8));
+    assert_eq!(program.line_col_of(8), (2, 1));
+    assert_eq!(program.line_col_of(10), (2, 3));
+    assert_eq!(program.line_col_of(14), (3, 1));
+}
+
+#[test]
+fn line_col_is_cached() {
+    let text = "fun f()\r\n  g(\"ä\")\n\n# end\n";
+    let program = crate::lex(text);
+    let index = program.line_index();
+    let offsets: Vec<u32> = (0..=text.len() as u32).collect();
+    let first: Vec<_> = offsets.iter().map(|&o| program.line_col_of(o)).collect();
+    let second: Vec<_> = offsets.iter().rev().map(|&o| program.line_col_of(o)).collect();
+    let expected: Vec<_> = offsets.iter().map(|&o| index.line_col(text, o)).collect();
+    assert_eq!(first, expected);
+    assert_eq!(second.into_iter().rev().collect::<Vec<_>>(), expected);
+
+    assert!(std::ptr::eq(index, program.line_index()));
+
+    // the line starts are computed again after an edit
+    let edit = crate::TextEdit { range: TextRange::new(0, 0), new_text: "\n\n" };
+    let edited = crate::relex(&program, edit);
+    assert_eq!(program.line_col_of(9), (2, 1));
+    assert_eq!(edited.line_col_of(9), (3, 8));
+    assert_eq!(edited.line_col_of(11), (4, 1));
+}
+
+#[test]
+fn program_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<crate::Program>();
+}
+
+#[test]
+fn single_eof() {
+    let inputs = [
+        "", "  \n\t", "# comment", "fun f()", "12x3", "fun `", "\"abc", "f 0x_",
+    ];
+    for text in inputs {
+        let program = crate::lex(text);
+        let eofs = program.tokens().iter().filter(|t| t.inner == Token::Eof).count();
+        assert_eq!(eofs, 1, "{:?}", text);
+
+        let last = program.tokens().last().unwrap();
+        let end = text.len() as u32;
+        assert_eq!(*last, TextRange::new(end, end).embed(Token::Eof), "{:?}", text);
+
+        let mut program = program;
+        let len = program.token_len();
+        program.no_eof();
+        assert_eq!(program.token_len(), len - 1, "{:?}", text);
+        assert!(program.tokens().iter().all(|t| t.inner != Token::Eof));
+        // only an `Eof` token is removed
+        program.no_eof();
+        assert_eq!(program.token_len(), len - 1, "{:?}", text);
+    }
+}
+
+#[test]
+fn text_at() {
+    let program = crate::lex("fun f(a: Int) = a + 1");
+    let texts: Vec<_> =
+        program.tokens().iter().map(|t| program.text_at(t.span)).collect();
+    assert_eq!(texts, ["fun", "f", "(", "a", ":", "Int", ")", "=", "a", "+", "1", ""]);
+    assert_eq!(program.text(), "fun f(a: Int) = a + 1");
+}
+
+#[test]
+fn number_suffixes() {
+    use ast::token::{NumberLiteral, NumberSuffix::*};
+
+    let num = |text: &str| match lex_string(text) {
+        Token::NumberLit(n) => n,
+        t => panic!("expected number, got {:?}", t),
+    };
+    assert_eq!(num("-128i8"), NumberLiteral::Int(-128, Some(I8)));
+    assert_eq!(num("1_000i16"), NumberLiteral::Int(1000, Some(I16)));
+    assert_eq!(num("42i32"), NumberLiteral::Int(42, Some(I32)));
+    assert_eq!(num("0x7Fi64"), NumberLiteral::Int(127, Some(I64)));
+    assert_eq!(num("-1i128"), NumberLiteral::Int(-1, Some(I128)));
+    assert_eq!(num("7u8"), NumberLiteral::UInt(7, Some(U8)));
+    assert_eq!(num("+0b11u16"), NumberLiteral::UInt(3, Some(U16)));
+    assert_eq!(num("0o7_u32"), NumberLiteral::UInt(7, Some(U32)));
+    assert_eq!(num("+5u64"), NumberLiteral::UInt(5, Some(U64)));
+    assert_eq!(num("5u128"), NumberLiteral::UInt(5, Some(U128)));
+    assert_eq!(num("18446744073709551615u64"), NumberLiteral::UInt(u64::MAX, Some(U64)));
+    assert_eq!(num("0xFFFFFFFFFFFFFFFFu64"), NumberLiteral::UInt(u64::MAX, Some(U64)));
+    assert_eq!(num("0xFFFF_FFFFu32"), NumberLiteral::UInt(u32::MAX as u64, Some(U32)));
+    assert_eq!(num("3.0f32"), NumberLiteral::Float(3.0, Some(F32)));
+    assert_eq!(num("1e3f64"), NumberLiteral::Float(1000.0, Some(F64)));
+    assert_eq!(num("0x1p4f64"), NumberLiteral::Float(16.0, Some(F64)));
+    // in hexadecimal integers, `f32` are digits
+    assert_eq!(num("0x1f32"), NumberLiteral::Int(0x1f32, None));
+
+    assert_eq!(num("42i32").to_string(), "42i32");
+    assert_eq!(format!("{:?}", num("7u8")), "UInt(7, U8)");
+
+    let err = |text: &str| lex_string(text);
+    assert_eq!(err("3.0i32"), Token::Error(LexError::InvalidNumSuffix(I32)));
+    assert_eq!(err("3f32"), Token::Error(LexError::InvalidNumSuffix(F32)));
+    assert_eq!(err("-3u8"), Token::Error(LexError::InvalidNumSuffix(U8)));
+    assert_eq!(err("+3i8"), Token::Error(LexError::InvalidNumSuffix(I8)));
+    assert_eq!(err("256u8"), Token::Error(LexError::NumberOverflow));
+    assert_eq!(err("0x1_0000_0000u32"), Token::Error(LexError::NumberOverflow));
+    let overflow = LexError::IntOverflow { radix: 10, signed: false };
+    assert_eq!(err("18446744073709551616u64"), Token::Error(overflow));
+    assert_eq!(err("-1u64"), Token::Error(LexError::InvalidNumSuffix(U64)));
+    assert_eq!(err("-129i8"), Token::Error(LexError::NumberOverflow));
+    assert_eq!(err("1e39f32"), Token::Error(LexError::NumberOverflow));
+    assert_eq!(err("3i7"), Token::Error(LexError::InvalidCharInNum('i')));
+}
+
+#[test]
+fn int_overflow() {
+    let overflow = |radix, signed| LexError::IntOverflow { radix, signed };
+    let err = |text: &str| match lex_string(text) {
+        Token::Error(e) => e,
+        t => panic!("expected error, got {:?}", t),
+    };
+    assert_eq!(err("99999999999999999999"), overflow(10, true));
+    assert_eq!(err("-99999999999999999999"), overflow(10, true));
+    assert_eq!(err("+99999999999999999999"), overflow(10, false));
+    assert_eq!(err("0xFFFFFFFFFFFFFFFFF"), overflow(16, true));
+    assert_eq!(err(&format!("+0b1{}", "0".repeat(64))), overflow(2, false));
+
+    assert_eq!(overflow(10, true).to_string(), "Integer too large for i64");
+    assert_eq!(overflow(16, false).to_string(), "Integer too large for u64");
+    // the limits themselves are fine
+    assert!(matches!(lex_string("-9223372036854775808"), Token::NumberLit(_)));
+    assert!(matches!(lex_string("+0xFFFFFFFFFFFFFFFF"), Token::NumberLit(_)));
+}
+
+#[test]
+fn lazy_tokens() {
+    let inputs = [
+        "",
+        "  ",
+        "fun f(a: Int) = a + 1",
+        "# comment\n## docs\nfun f()",
+        "x ## a\n# b\n## c\n@cfg(d) ## e\nfun ## f",
+        "a1b c+d e\"str\"f 12x3",
+        "fun `",
+        "f 0x_",
+        "a.b?.c()? ?? x?y",
+        "\u{feff}fun f()",
+        include_str!("../tests/numbers.wa"),
+        include_str!("../tests/numbers_invalid.wa"),
+    ];
+    for text in inputs {
+        let lazy: Vec<_> = crate::tokens(text).collect();
+        let program = crate::lex(text);
+        assert_eq!(lazy, program.tokens(), "{:?}", text);
+        assert!(program.iter().eq(program.tokens()));
+    }
+
+    let mut tokens = crate::tokens("fun");
+    assert_eq!(tokens.next().map(|t| t.inner), Some(Token::Keyword(Keyword::Fun)));
+    assert_eq!(tokens.next().map(|t| t.inner), Some(Token::Eof));
+    assert_eq!(tokens.next(), None);
+}
+
+#[test]
+fn question_mark() {
+    let program = crate::lex("foo()? a.b?.c() x?y ?? ? 1?");
+    assert_eq!(program.to_string(), "foo ( ) ? a . b ? . c ( ) x?y ?? ? 1 ? EOF");
+    assert!(program.errors().is_empty());
+}
+
+#[test]
+fn case_insensitive_keywords() {
+    use ast::token::{Keyword, UpperIdent};
+
+    use crate::Keywords;
+
+    let text = "FUN f() Unit {}";
+    let lex = |keywords: &Keywords| -> Vec<Token> {
+        let program = crate::lex_with_config(text, keywords);
+        program.tokens().iter().map(|t| t.inner.clone()).take(2).collect()
+    };
+
+    let mut keywords = Keywords::default();
+    assert_eq!(lex(&keywords), vec![
+        Token::UpperIdent(UpperIdent::new("FUN")),
+        Token::Ident(Ident::new("f")),
+    ]);
+
+    keywords.set_case_insensitive(true);
+    assert_eq!(lex(&keywords), vec![
+        Token::Keyword(Keyword::Fun),
+        Token::Ident(Ident::new("f")),
+    ]);
+    let program = crate::lex_with_config("Fun fUN Unit", &keywords);
+    assert_eq!(program.tokens()[0].inner, Token::Keyword(Keyword::Fun));
+    assert_eq!(program.tokens()[1].inner, Token::Keyword(Keyword::Fun));
+    assert_eq!(program.tokens()[2].inner, Token::UpperIdent(UpperIdent::new("Unit")));
+
+    // words that are too long for the stack buffer
+    let long = "a_very_long_keyword_that_needs_an_allocation";
+    keywords.insert(long, Keyword::Fun);
+    assert!(keywords.get(&long.to_ascii_uppercase()) == Some(Keyword::Fun));
+    assert!(keywords.get("a_very_long_keyword_that_needs_an_allocatioN").is_some());
+    assert!(keywords.get("fUNCTION").is_none());
+}
+
+#[test]
+fn specific_errors() {
+    let cases = [
+        ("$", LexError::UnexpectedChar('$')),
+        ("\u{7}", LexError::UnexpectedChar('\u{7}')),
+        ("é", LexError::UnexpectedChar('é')),
+        ("<3", LexError::DigitInOperator),
+        ("0x", LexError::MissingDigits),
+        ("-0b_", LexError::MissingDigits),
+        ("0x.8p1", LexError::MissingDigits),
+        ("1e", LexError::MissingExponent),
+        ("1e+", LexError::MissingExponent),
+        (".5e-", LexError::MissingExponent),
+        ("0b1.1", LexError::MissingExponent),
+        ("1.5x", LexError::InvalidCharInNum('x')),
+        ("1e2e3", LexError::InvalidCharInNum('e')),
+        ("0o8", LexError::InvalidCharInNum('8')),
+        ("0b2", LexError::InvalidCharInNum('2')),
+        ("0b1.2p1", LexError::InvalidCharInNum('2')),
+        ("1e309", LexError::NumberOverflow),
+        ("-1e309", LexError::NumberOverflow),
+        (".1e99999999999", LexError::NumberOverflow),
+        ("0x1p99999999999", LexError::NumberOverflow),
+        ("1e39f32", LexError::NumberOverflow),
+    ];
+    for &(input, error) in &cases {
+        assert_eq!(lex_string(input), Token::Error(error), "{}", input);
+    }
+
+    // a leading dot is parsed like a leading `0.`
+    for &input in &[".5", ".5_", ".5e", ".5e3f32", ".2_5E-1", ".1e99999999999", ".5u8"] {
+        assert_eq!(lex_string(input), lex_string(&format!("0{}", input)), "{}", input);
+    }
+}
+
+#[test]
+fn byte_order_mark() {
+    let text = "\u{feff}fun f() Unit {}";
+    let program = crate::lex(text);
+    assert_eq!(program.errors(), vec![]);
+    assert_eq!(program.tokens()[0].span, TextRange::new(3, 6));
+    assert_eq!(program.text_at(program.tokens()[0].span), "fun");
+    assert_eq!(program.tokens().len(), 8);
+
+    // only a BOM at the start is skipped
+    let program = crate::lex("fun\u{feff}");
+    let error = LexError::UnexpectedChar('\u{feff}');
+    assert_eq!(program.errors(), vec![TextRange::new(3, 6).embed(error)]);
+
+    // ...also when relexing from the middle of the text
+    let text = "a \u{feff}b";
+    let edit = crate::TextEdit { range: TextRange::new(6, 6), new_text: "c" };
+    let relexed = crate::relex(&crate::lex(text), edit);
+    let expected = crate::lex("a \u{feff}bc");
+    assert_eq!(format!("{:?}", relexed), format!("{:?}", expected));
+    assert_eq!(relexed.errors(), vec![TextRange::new(2, 5).embed(error)]);
+}
+
+#[test]
+fn huge_exponents() {
+    let zero = Token::NumberLit(NumberLiteral::Float(0.0, None));
+    for &input in &["1e-99999999999", "0e99999999999", ".0e400", "0x0p99999999999"] {
+        assert_eq!(lex_string(input), zero, "{}", input);
+    }
+}
@@ -1,58 +1,311 @@
-use ast::token::{Ident, Operator, StringLiteral, Token, UpperIdent};
-use ast::{LexError, Spanned, TextRange};
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use ast::token::{Ident, Keyword, Operator, Punctuation, Token, UpperIdent};
+use ast::{LexError, Spanned, TextRange, TinyString};
 use logos::Lexer;
 
-use super::numbers;
-use super::syntax::{parse_keyword, IToken};
+use super::{numbers, strings};
+use super::syntax::{IToken, Quoted};
+use super::Keywords;
+
+/// Returns the tokens and the ranges of all comments that aren't doc comments
+pub(super) fn lex(
+    text: &str,
+    keywords: &Keywords,
+) -> (Vec<Spanned<Token>>, Vec<TextRange>) {
+    let mut v = Vec::new();
+    let mut comments = Vec::new();
+    lex_from(text, 0, keywords, &mut v, &mut comments, |_| false);
+    (v, comments)
+}
 
-pub(super) fn lex(text: &str) -> Vec<Spanned<Token>> {
+/// Lexes `text`, starting at the byte offset `start`, which must be at the
+/// beginning of a token that isn't directly preceded by a word or a doc comment.
+/// The tokens are appended to `v`, and the ranges of comments are appended to
+/// `comments`.
+///
+/// `stop` is called with every token that is pushed, except doc comments. When
+/// it returns `true`, lexing stops and `true` is returned. Otherwise, the `Eof`
+/// token is pushed at the end and `false` is returned.
+pub(super) fn lex_from(
+    text: &str,
+    start: usize,
+    keywords: &Keywords,
+    v: &mut Vec<Spanned<Token>>,
+    comments: &mut Vec<TextRange>,
+    mut stop: impl FnMut(&Spanned<Token>) -> bool,
+) -> bool {
     let mut was_word = false;
-    let mut v: Vec<Spanned<Token>> = Vec::new();
-
-    for (t, span) in Lexer::<IToken>::new(text).spanned() {
-        let span = TextRange::from(span);
-
-        let data = match t {
-            IToken::Word(word) => {
-                if word.starts_with(|c: char| c.is_ascii_lowercase()) {
-                    parse_keyword(word)
-                        .map(Token::Keyword)
-                        .unwrap_or_else(|| Token::Ident(Ident::new(word)))
-                } else if word.starts_with(|c: char| c.is_ascii_uppercase()) {
-                    Token::UpperIdent(UpperIdent::new(word))
-                } else if word.contains(|c: char| c.is_ascii_digit()) {
-                    Token::Error(LexError::InvalidNum)
+    // Doc comments and their comment ranges, until it is known whether they
+    // are followed by an item
+    let mut docs = Vec::new();
+
+    for (t, span) in RawTokens::new(&text[start..], start == 0) {
+        let span = TextRange::from(span.start + start..span.end + start);
+
+        let comment = match t {
+            IToken::Comment(c) => {
+                let len = c.strip_suffix('\r').unwrap_or(c).len() as u32;
+                Some(TextRange::new(span.start(), span.start() + len))
+            }
+            _ => None,
+        };
+        let data = convert(t, keywords);
+        match data {
+            Token::Error(LexError::Ws) => {
+                comments.extend(comment);
+                was_word = false;
+            }
+            Token::DocComment(_) => {
+                docs.push((span.embed(data), comment.unwrap()));
+                was_word = false;
+            }
+            _ => {
+                let is_word = is_word(&data);
+                if was_word && is_word {
+                    let prev = v.pop().unwrap();
+                    let no_ws = Token::Error(LexError::NoWs);
+                    v.push(prev.span.extend_until(span.end()).embed(no_ws));
                 } else {
-                    Token::Operator(Operator::new(word))
+                    was_word = is_word;
+                    if starts_item(&data) {
+                        v.extend(docs.drain(..).map(|(doc, _)| doc));
+                    } else {
+                        demote_doc_comments(&mut docs, comments);
+                    }
+                    v.push(span.embed(data));
+                }
+                if stop(v.last().unwrap()) {
+                    return true;
                 }
             }
-            IToken::NumberLit(input) => numbers::parse_number(input),
-            IToken::StringLit(s) => Token::StringLit(StringLiteral::new(s)),
-            IToken::Punct(p) => Token::Punct(p),
-            IToken::Error => Token::Error(LexError::Unexpected),
-            IToken::Ws => Token::Error(LexError::Ws),
-        };
-        if let Token::Error(LexError::Ws) = data {
-            was_word = false;
-        } else {
-            let is_word = matches!(
-                data,
-                Token::NumberLit(_)
-                    | Token::Ident(_)
-                    | Token::UpperIdent(_)
-                    | Token::Operator(_)
-                    | Token::Keyword(_)
-            );
-            if was_word && is_word {
-                let prev = v.pop().unwrap();
+        }
+    }
+    demote_doc_comments(&mut docs, comments);
+    v.push(TextRange::from(text.len()..text.len()).embed(Token::Eof));
+    false
+}
+
+/// Turns doc comments that aren't followed by an item into regular comments,
+/// which are inserted into `comments` so they stay ordered
+fn demote_doc_comments(
+    docs: &mut Vec<(Spanned<Token>, TextRange)>,
+    comments: &mut Vec<TextRange>,
+) {
+    for (_, range) in docs.drain(..) {
+        let i = comments.partition_point(|c| c.start() < range.start());
+        comments.insert(i, range);
+    }
+}
+
+/// Returns whether a token can be the start of an item. Doc comments are only
+/// tokens if they are followed by such a token.
+fn starts_item(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Keyword(
+            Keyword::Fun
+                | Keyword::Class
+                | Keyword::Enum
+                | Keyword::Impl
+                | Keyword::Use
+                | Keyword::Type
+        ) | Token::Punct(Punctuation::At)
+    )
+}
+
+/// Intermediate tokens with their spans. Question marks at the end of an
+/// identifier are split off, e.g. `a?` becomes `a` and `?`, so `?` can be used
+/// as a postfix operator. Operators such as `??` are not split.
+///
+/// Errors are replaced with `IToken::UnexpectedChar`. If `at_start` is true, a
+/// byte order mark at the start of the text is skipped, so the spans still refer
+/// to the original text. It is false when lexing resumes in the middle of a file,
+/// where a BOM is an error.
+struct RawTokens<'a> {
+    text: &'a str,
+    inner: logos::SpannedIter<'a, IToken<'a>>,
+    /// The spans of `?` tokens that are returned before the next token
+    question_marks: Range<usize>,
+}
+
+impl<'a> RawTokens<'a> {
+    fn new(text: &'a str, at_start: bool) -> Self {
+        let mut lexer = Lexer::<IToken>::new(text);
+        if at_start && text.starts_with('\u{feff}') {
+            lexer.bump('\u{feff}'.len_utf8());
+        }
+        RawTokens { text, inner: lexer.spanned(), question_marks: 0..0 }
+    }
+}
+
+impl<'a> Iterator for RawTokens<'a> {
+    type Item = (IToken<'a>, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(start) = self.question_marks.next() {
+            return Some((IToken::Punct(Punctuation::QuestionMark), start..start + 1));
+        }
+        match self.inner.next()? {
+            (IToken::Word(word), span) if word.ends_with('?') => {
+                let trimmed = word.trim_end_matches('?');
+                let is_ident = trimmed.starts_with(|c: char| c.is_ascii_alphabetic())
+                    || (trimmed.starts_with('_') && trimmed.len() > 1);
+                if !is_ident {
+                    return Some((IToken::Word(word), span));
+                }
+                let end = span.start + trimmed.len();
+                self.question_marks = end..span.end;
+                Some((IToken::Word(trimmed), span.start..end))
+            }
+            (IToken::Error, span) => {
+                let c = self.text[span.clone()].chars().next().unwrap_or_default();
+                Some((IToken::UnexpectedChar(c), span))
+            }
+            t => Some(t),
+        }
+    }
+}
+
+/// Converts an intermediate token. Whitespace and comments that aren't doc
+/// comments become `LexError::Ws`. Comments starting with three or more `#`,
+/// e.g. banners, aren't doc comments.
+fn convert(t: IToken<'_>, keywords: &Keywords) -> Token {
+    match t {
+        IToken::Word(word) => {
+            let is_upper = word.starts_with(|c: char| c.is_ascii_uppercase());
+            if is_upper && keywords.is_case_insensitive() {
+                if let Some(keyword) = keywords.get(word) {
+                    return Token::Keyword(keyword);
+                }
+            }
+            if word.starts_with(|c: char| c.is_ascii_lowercase()) {
+                keywords
+                    .get(word)
+                    .map(Token::Keyword)
+                    .unwrap_or_else(|| Token::Ident(Ident::new(word)))
+            } else if is_upper {
+                Token::UpperIdent(UpperIdent::new(word))
+            } else if word.contains(|c: char| c.is_ascii_digit()) {
+                Token::Error(LexError::DigitInOperator)
+            } else {
+                Token::Operator(Operator::new(word))
+            }
+        }
+        IToken::NumberLit(input) => numbers::parse_number(input),
+        IToken::StringLit(s) => strings::parse_string(s),
+        IToken::Quoted(Quoted::CharLit(s)) => strings::parse_char(s),
+        IToken::Quoted(Quoted::Label(l)) => Token::Label(Ident::new(l)),
+        IToken::RawStringLit(s) => strings::parse_raw_string(s),
+        IToken::MultiLineStringLit(s) => strings::parse_multi_line_string(s),
+        IToken::Punct(p) => Token::Punct(p),
+        IToken::UnexpectedChar(c) => Token::Error(LexError::UnexpectedChar(c)),
+        IToken::Error => unreachable!("errors are replaced in RawTokens"),
+        IToken::Comment(c) => match c.strip_prefix("##") {
+            Some(doc) if !doc.starts_with('#') => {
+                Token::DocComment(parse_doc_comment(doc))
+            }
+            _ => Token::Error(LexError::Ws),
+        },
+        IToken::Ws => Token::Error(LexError::Ws),
+    }
+}
+
+/// Returns whether the token is a word. Two consecutive words must be separated
+/// by whitespace, otherwise they're replaced with `LexError::NoWs`.
+fn is_word(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::NumberLit(_)
+            | Token::Ident(_)
+            | Token::UpperIdent(_)
+            | Token::Operator(_)
+            | Token::Keyword(_)
+    )
+}
+
+/// Lexes tokens on demand; see [`crate::tokens`]. Since consecutive words are
+/// merged into a `NoWs` error, each token is only returned once the next
+/// token is known.
+pub(super) struct Tokens<'a> {
+    inner: RawTokens<'a>,
+    keywords: &'a Keywords,
+    /// The last token, which may still be merged with the next one
+    pending: Option<Spanned<Token>>,
+    was_word: bool,
+    /// Doc comments that are kept if the next token starts an item
+    docs: Vec<Spanned<Token>>,
+    /// Tokens that are returned before lexing further
+    ready: VecDeque<Spanned<Token>>,
+    /// The `Eof` token, until it is returned
+    eof: Option<Spanned<Token>>,
+}
+
+impl<'a> Tokens<'a> {
+    pub(super) fn new(text: &'a str, keywords: &'a Keywords) -> Self {
+        Tokens {
+            inner: RawTokens::new(text, true),
+            keywords,
+            pending: None,
+            was_word: false,
+            docs: Vec::new(),
+            ready: VecDeque::new(),
+            eof: Some(TextRange::from(text.len()..text.len()).embed(Token::Eof)),
+        }
+    }
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Spanned<Token>;
+
+    fn next(&mut self) -> Option<Spanned<Token>> {
+        if let Some(t) = self.ready.pop_front() {
+            return Some(t);
+        }
+        for (t, span) in &mut self.inner {
+            let span = TextRange::from(span);
+            let data = convert(t, self.keywords);
+            match data {
+                Token::Error(LexError::Ws) => {
+                    self.was_word = false;
+                    continue;
+                }
+                Token::DocComment(_) => {
+                    self.ready.extend(self.pending.take());
+                    self.docs.push(span.embed(data));
+                    self.was_word = false;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let is_word = is_word(&data);
+            if self.was_word && is_word {
+                let prev = self.pending.take().unwrap();
                 let no_ws = Token::Error(LexError::NoWs);
-                v.push(prev.span.extend_until(span.end()).embed(no_ws));
+                self.pending = Some(prev.span.extend_until(span.end()).embed(no_ws));
             } else {
-                was_word = is_word;
-                v.push(span.embed(data));
+                self.was_word = is_word;
+                if starts_item(&data) {
+                    self.ready.extend(self.docs.drain(..));
+                } else {
+                    self.docs.clear();
+                }
+                self.ready.extend(self.pending.replace(span.embed(data)));
+                if let Some(t) = self.ready.pop_front() {
+                    return Some(t);
+                }
             }
         }
+        // doc comments at the end aren't followed by an item
+        self.docs.clear();
+        self.ready.pop_front().or_else(|| self.pending.take()).or_else(|| self.eof.take())
     }
-    v.push(TextRange::from(text.len()..text.len()).embed(Token::Eof));
-    v
+}
+
+/// Removes the space after `##` and a trailing `\r`
+fn parse_doc_comment(doc: &str) -> TinyString {
+    let doc = doc.strip_prefix(' ').unwrap_or(doc);
+    doc.strip_suffix('\r').unwrap_or(doc).into()
 }
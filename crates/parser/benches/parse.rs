@@ -0,0 +1,56 @@
+//! Benchmarks for lexing and parsing a large generated file. Run them with
+//! `cargo bench -p parser`. Criterion isn't a dependency, so this is a minimal
+//! harness that reports the fastest of several runs.
+
+use std::time::{Duration, Instant};
+
+const FUNCTIONS: usize = 10_000;
+const RUNS: usize = 10;
+
+fn main() {
+    let text = generate(FUNCTIONS);
+    println!("{} functions, {} KiB", FUNCTIONS, text.len() / 1024);
+
+    bench("lex", || lexer::lex(&text).token_len());
+
+    let program = lexer::lex(&text);
+    bench("parse", || parser::parse(program.tokens()).unwrap().len());
+
+    bench("lex + parse", || {
+        let program = lexer::lex(&text);
+        parser::parse(program.tokens()).unwrap().len()
+    });
+}
+
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        std::hint::black_box(f());
+        best = best.min(start.elapsed());
+    }
+    println!("{:<12} {:>10.2?}", name, best);
+}
+
+/// Generates functions with the most common kinds of expressions: calls with
+/// many arguments, operations, member calls, declarations and short blocks
+fn generate(functions: usize) -> String {
+    let mut text = String::new();
+    for i in 0..functions {
+        text.push_str(&format!(
+            "## Function number {i}\n\
+             fun f{i}(a Int, b Int, c String) Int {{\n    \
+                 let x = a + {i};\n    \
+                 var y = g(x, b, c, \"str\", 'c', 1.5);\n    \
+                 k(.z = 3, .w = x);\n    \
+                 y = y.map(|v| v + 1).filter(|v| {{ v }});\n    \
+                 h(a, b)(c) Int match {{\n        \
+                     let n: n,\n    \
+                 }};\n    \
+                 x\n\
+             }}\n\n",
+            i = i,
+        ));
+    }
+    text
+}
@@ -1,6 +1,8 @@
+use std::fmt;
+
 use ast::expr::Expr;
-use ast::token::{Operator, Token};
-use ast::Spanned;
+use ast::token::{Ident, Operator, Token};
+use ast::{Spanned, TextRange};
 
 use crate::validation::ValidationError;
 
@@ -15,8 +17,10 @@ pub enum Error {
     #[error("Expected {0:?}, got {1:?}")]
     ExpectedGot(Token, Token),
 
-    #[error("Expected {0}, got {1:?}")]
-    ExpectedGot2(&'static str, Token),
+    /// All alternatives that were tried at the same token, e.g. `Expected \`if\`
+    /// or block, got ...`
+    #[error("Expected {}, got {got:?}", format_options(.options))]
+    ExpectedOneOf { options: Vec<&'static str>, got: Token, at: TextRange },
 
     #[error("Expected {0}, got {1:?}")]
     ExpectedGot3(&'static str, Expr),
@@ -30,6 +34,45 @@ pub enum Error {
     )]
     OperatorInsteadOfOperand(Operator),
 
+    #[error("Unknown attribute `@{0}`")]
+    UnknownAttribute(Ident),
+
     #[error("{0}")]
     ValidationError(#[from] ValidationError),
 }
+
+fn format_options(options: &[&str]) -> String {
+    match options.split_last() {
+        Some((last, rest)) if !rest.is_empty() => {
+            format!("{} or {}", rest.join(", "), last)
+        }
+        _ => options.join(""),
+    }
+}
+
+/// Multiple errors that were collected while parsing. The `Display` impl
+/// prints each error on its own line, prefixed with its span.
+#[derive(Debug)]
+pub struct Errors(pub Vec<Spanned<Error>>);
+
+impl fmt::Display for Errors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{:?}: {}", error.span, error.inner)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Errors {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.first().map(|e| &e.inner as _)
+    }
+}
+
+impl From<Spanned<Error>> for Errors {
+    fn from(error: Spanned<Error>) -> Self { Errors(vec![error]) }
+}
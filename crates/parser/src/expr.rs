@@ -7,80 +7,266 @@ use ast::token::{
     Ident, Keyword, NumberLiteral, Operator, Punctuation, StringLiteral, Token,
     UpperIdent,
 };
-use ast::Spanned;
+use ast::{Spanned, SpannedList, TextRange};
 
+use crate::operators::{Assoc, OperatorInfo, OperatorTable};
 use crate::uoret;
+use crate::validation::ValidationError;
 
 use super::helpers::*;
 use super::{Error, LexerMut, Parse, ParseResult};
 
 impl Parse for Expr {
     fn parse(lexer: LexerMut) -> ParseResult<Self> {
-        let mut parts = Vec::new();
+        let start = lexer.peek_span();
+        match parse_expr(lexer) {
+            Err(Error::ExpectedOneOf { at, .. }) if at == start => Ok(None),
+            result => result,
+        }
+    }
 
-        if let Some(d) = Declaration::parse(lexer)? {
-            return Ok(Some(d.span.embed(Expr::Declaration(d.inner))));
+    /// Unlike the default implementation, this lists the tokens that can start
+    /// an expression in the error if the next token can't
+    fn parse_expect(
+        lexer: LexerMut,
+        expect: &'static str,
+    ) -> Result<Spanned<Self>, Error> {
+        match parse_expr(lexer)? {
+            Some(result) => Ok(result),
+            None => Err(lexer.expected(expect)),
         }
+    }
+}
 
-        let mut len = lexer.len();
-        while let Some(part) = ExprPart::parse(lexer)? {
-            parts.push(part);
-            if lexer.len() == len {
-                return Err(Error::ExpectedGot2("expression", lexer.peek().clone()));
-            }
-            len = lexer.len();
+/// Parses an expression; returns an [`Error::ExpectedOneOf`] error if the next
+/// token can't start an expression
+fn parse_expr(lexer: LexerMut) -> ParseResult<Expr> {
+    or5(
+        map(Declaration::parse, Expr::Declaration),
+        parse_loop,
+        parse_return,
+        parse_break_or_continue,
+        |lexer| parse_expr_parts(lexer, false),
+    )(lexer)
+}
+
+/// Parses `return`, followed by the returned value unless it is at the end of
+/// a statement or block
+fn parse_return(lexer: LexerMut) -> ParseResult<Expr> {
+    let span = uoret!(lexer.eat(Keyword::Return));
+    let value = Expr::parse(lexer)?;
+    let span = span.merge_if(&value);
+    Ok(Some(span.embed(Expr::Return(value.map(Box::new)))))
+}
+
+/// Parses `break` or `continue`, optionally followed by the label of an
+/// enclosing loop
+fn parse_break_or_continue(lexer: LexerMut) -> ParseResult<Expr> {
+    let make_expr = match lexer.peek() {
+        Token::Keyword(Keyword::Break) => Expr::Break,
+        Token::Keyword(Keyword::Continue) => Expr::Continue,
+        _ => return Ok(None),
+    };
+    let span = lexer.next().span;
+    let label = parse_label(lexer);
+    let span = span.merge_if(&label);
+    Ok(Some(span.embed(make_expr(label))))
+}
+
+fn parse_label(lexer: LexerMut) -> Option<Spanned<Ident>> {
+    match lexer.peek() {
+        Token::Label(label) => {
+            let label = label.clone();
+            Some(lexer.next().span.embed(label))
+        }
+        _ => None,
+    }
+}
+
+/// Parses a `while` or `for` loop, which may be preceded by a label, e.g.
+/// `'outer: while x { ... }`. The labels of `break` and `continue` are checked
+/// in [`check_module`](crate::validation::check_module).
+fn parse_loop(lexer: LexerMut) -> ParseResult<Expr> {
+    let label = match parse_label(lexer) {
+        Some(label) => label,
+        None => {
+            return or2(map(While::parse, Expr::While), map(For::parse, Expr::For))(lexer)
         }
-        Ok(if parts.is_empty() {
-            None
-        } else if parts.len() == 1 {
-            let (expr, span) = parts.pop().unwrap().into_inner();
+    };
+    lexer.expect(Punctuation::Colon)?;
+
+    let expr = or2(
+        expecting("`while`", map(While::parse, Expr::While)),
+        expecting("`for`", map(For::parse, Expr::For)),
+    )(lexer)?;
+    Ok(expr.map(|expr| {
+        let (mut expr, span) = expr.into_inner();
+        let span = label.span.merge(span);
+        match &mut expr {
+            Expr::While(w) => w.label = Some(label),
+            Expr::For(f) => f.label = Some(label),
+            _ => {}
+        }
+        span.embed(expr)
+    }))
+}
+
+/// Parses an expression made of operands and operators. If `is_condition` is
+/// true, parsing stops at a `{` following an operand, because it starts the
+/// body of a loop.
+///
+/// Most expressions consist of at most 3 parts, e.g. `a + b`, so the first 3
+/// parts are stored inline, and a `Vec` is only allocated for longer ones.
+fn parse_expr_parts(lexer: LexerMut, is_condition: bool) -> ParseResult<Expr> {
+    let first = uoret!(parse_expr_part(lexer, is_condition, None)?);
+    let second = match parse_expr_part(lexer, is_condition, Some(&first))? {
+        Some(second) => second,
+        None => {
+            let (expr, span) = first.into_inner();
             let expr_data = match expr {
                 ExprPart::Literal(o) => Expr::Literal(o),
                 ExprPart::Invokable(o) => Expr::Invokable(o),
                 ExprPart::Lambda(o) => Expr::Lambda(o),
                 ExprPart::Block(o) => Expr::Block(o),
+                ExprPart::IfExpr(o) => Expr::IfExpr(o),
                 ExprPart::Parens(o) => Expr::Tuple(o),
+                ExprPart::ArrayLit(elements) => Expr::ArrayLit(ArrayLit { elements }),
+                ExprPart::Not => return Err(Error::Expected("operand")),
+                ExprPart::Index(_) => return Err(Error::ExpectedGot4("operand", "`[`")),
                 ExprPart::And
                 | ExprPart::Or
                 | ExprPart::Dot
                 | ExprPart::Equals
+                | ExprPart::CompoundAssign(_)
+                | ExprPart::QuestionMark
                 | ExprPart::Match(_) => return Ok(None),
             };
-            Some(span.embed(expr_data))
-        } else {
-            let expr = pratt_parser(&mut parts.into_iter().peekable(), 0)?;
-            Some(expr)
-        })
+            return Ok(Some(span.embed(expr_data)));
+        }
+    };
+
+    let mut inline = [Some(first), Some(second), None];
+    let mut rest = Vec::new();
+    loop {
+        let prev = rest.last().or(inline[2].as_ref()).or(inline[1].as_ref());
+        match parse_expr_part(lexer, is_condition, prev)? {
+            Some(part) if inline[2].is_none() => inline[2] = Some(part),
+            Some(part) => rest.push(part),
+            None => break,
+        }
+    }
+    let parts = IntoIterator::into_iter(inline).flatten().chain(rest);
+    let expr = pratt_parser(&mut parts.peekable(), 0, lexer.operators)?;
+    Ok(Some(expr))
+}
+
+/// Parses the part of an expression following `prev`, see [`parse_expr_parts`]
+fn parse_expr_part(
+    lexer: LexerMut,
+    is_condition: bool,
+    prev: Option<&Spanned<ExprPart>>,
+) -> ParseResult<ExprPart> {
+    if is_condition
+        && *lexer.peek() == Token::Punct(Punctuation::OpenBrace)
+        && prev.is_some_and(|p| p.ends_operand())
+    {
+        return Ok(None);
+    }
+    let len = lexer.len();
+    let start = lexer.peek_span();
+    let part = match ExprPart::parse(lexer) {
+        Ok(part) => uoret!(part),
+        // the expression ends if the next token can't continue it
+        Err(Error::ExpectedOneOf { at, .. }) if prev.is_some() && at == start => {
+            return Ok(None)
+        }
+        Err(e) => return Err(e),
+    };
+    if lexer.len() == len {
+        return Err(lexer.expected("expression"));
     }
+    let (part, span) = part.into_inner();
+    let part = match part {
+        // square brackets are only an index if they follow an operand
+        ExprPart::Index(elements) if !prev.is_some_and(|p| p.ends_operand()) => {
+            ExprPart::ArrayLit(elements)
+        }
+        part => part,
+    };
+    Ok(Some(span.embed(part)))
 }
 
 /// <https://matklad.github.io/2020/04/13/simple-but-powerful-pratt-parsing.html>
+///
+/// Operators in the `operators` table get binding powers between those of
+/// `and` and of postfix operators, depending on their precedence. The prefix
+/// `not` binds more tightly than `and` and `or`, but less tightly than other
+/// operators, so `not a == b` is parsed as `not (a == b)`.
 fn pratt_parser(
     expr_parts: &mut Peekable<impl Iterator<Item = Spanned<ExprPart>>>,
-    min_bp: u8,
+    min_bp: u16,
+    operators: Option<&OperatorTable>,
 ) -> Result<Spanned<Expr>, Error> {
-    fn postfix_binding_power(op: &ExprPart) -> Option<(u8, ())> {
+    /// Leaves room for the 2 * 256 binding powers of operators in the table
+    const fn bp(level: u16) -> u16 { level << 9 }
+
+    fn prefix_binding_power(op: &ExprPart) -> Option<((), u16)> {
         match op.kind() {
-            ExprPartKind::InvokableType => Some((13, ())),
-            ExprPartKind::Parens => Some((11, ())),
-            ExprPartKind::Match => Some((9, ())),
+            ExprPartKind::Not => Some(((), bp(7))),
             _ => None,
         }
     }
 
-    fn infix_binding_power(op: &ExprPart) -> Option<(u8, u8)> {
+    fn postfix_binding_power(op: &ExprPart) -> Option<(u16, ())> {
         match op.kind() {
-            ExprPartKind::Dot => Some((15, 16)),
-            ExprPartKind::InvokableOperator => Some((7, 8)),
-            ExprPartKind::And => Some((5, 6)),
-            ExprPartKind::Or => Some((3, 4)),
-            ExprPartKind::Equals => Some((2, 1)),
+            ExprPartKind::InvokableType => Some((bp(13), ())),
+            ExprPartKind::QuestionMark | ExprPartKind::Index => Some((bp(12), ())),
+            ExprPartKind::Parens => Some((bp(11), ())),
+            ExprPartKind::Match => Some((bp(9), ())),
+            _ => None,
+        }
+    }
+
+    fn infix_binding_power(
+        op: &ExprPart,
+        operators: Option<&OperatorTable>,
+    ) -> Option<(u16, u16)> {
+        match op.kind() {
+            ExprPartKind::Dot => Some((bp(15), bp(16))),
+            ExprPartKind::InvokableOperator => {
+                let info = match (op, operators) {
+                    (ExprPart::Invokable(i), Some(operators)) => match &*i.name {
+                        Name::Operator(o) => operators.get(o),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                match info {
+                    Some(OperatorInfo { precedence, assoc }) => {
+                        let l_bp = bp(7) + 2 * precedence as u16;
+                        Some(match assoc {
+                            Assoc::Left => (l_bp, l_bp + 1),
+                            Assoc::Right => (l_bp + 1, l_bp),
+                        })
+                    }
+                    None => Some((bp(7), bp(8))),
+                }
+            }
+            ExprPartKind::And => Some((bp(5), bp(6))),
+            ExprPartKind::Or => Some((bp(3), bp(4))),
+            ExprPartKind::Equals | ExprPartKind::CompoundAssign => Some((bp(2), bp(1))),
             _ => None,
         }
     }
 
     let lhs = expr_parts.next().ok_or(Error::Expected("expression"))?;
-    let mut lhs = lhs.span.embed(lhs.inner.into_operand()?);
+    let mut lhs = match prefix_binding_power(&lhs.inner) {
+        Some(((), r_bp)) => {
+            let operand = pratt_parser(expr_parts, r_bp, operators)?;
+            lhs.inner.into_unary_operation(lhs.span, operand)?
+        }
+        None => lhs.span.embed(lhs.inner.into_operand()?),
+    };
 
     loop {
         let op = match expr_parts.peek() {
@@ -97,17 +283,20 @@ fn pratt_parser(
             let lhs_span = lhs.span;
 
             let lhs_data = match op.inner {
-                ExprPart::Parens(tuple) => Expr::ParenCall(ParenCall {
-                    receiver: Box::new(lhs),
-                    args: Some(tuple.into_fun_call_args()),
-                }),
+                ExprPart::Parens(Parens { exprs, trailing_comma }) => {
+                    Expr::ParenCall(ParenCall {
+                        receiver: Box::new(lhs),
+                        args: Some(exprs),
+                        trailing_comma,
+                    })
+                }
                 ExprPart::Invokable(Invokable { name, generics: args }) => {
                     match name.into_inner() {
                         (Name::Type(name), name_span) => {
                             let name = Spanned::new(name, name_span);
                             Expr::TypeAscription(TypeAscription {
                                 expr: Box::new(lhs),
-                                ty: NamedType { name, args },
+                                ty: NamedType { path: Box::new([]), name, args },
                             })
                         }
                         (t, _) => panic!("Unexpected token {:?}", t),
@@ -117,20 +306,24 @@ fn pratt_parser(
                     expr: Box::new(lhs),
                     match_arms: match_body.arms,
                 }),
+                ExprPart::QuestionMark => Expr::Try(Box::new(lhs)),
+                ExprPart::Index(indices) => {
+                    Expr::Index(IndexExpr { receiver: Box::new(lhs), indices })
+                }
                 t => panic!("Unexpected token {:?}", t),
             };
             lhs = lhs_span.merge(op.span).embed(lhs_data);
             continue;
         }
 
-        if let Some((l_bp, r_bp)) = infix_binding_power(op) {
+        if let Some((l_bp, r_bp)) = infix_binding_power(op, operators) {
             if l_bp < min_bp {
                 break;
             }
             let op = expr_parts.next().unwrap();
 
-            let rhs = pratt_parser(expr_parts, r_bp)?;
-            lhs = op.inner.into_operation(lhs, rhs)?;
+            let rhs = pratt_parser(expr_parts, r_bp, operators)?;
+            lhs = op.inner.into_operation(lhs, rhs, operators)?;
             continue;
         }
 
@@ -142,17 +335,27 @@ fn pratt_parser(
 
 impl Parse for Literal {
     fn parse(lexer: LexerMut) -> ParseResult<Self> {
-        or2(
+        or3(
             map(NumberLiteral::parse, Literal::NumberLit),
             map(StringLiteral::parse, Literal::StringLit),
+            map(char::parse, Literal::CharLit),
         )(lexer)
     }
 }
 
+/// Brackets after a name contain type arguments if they can be parsed as such,
+/// e.g. `foo[T]`. Otherwise they are left for an index expression, e.g. `foo[i]`.
 impl Parse for Invokable {
     fn parse(lexer: LexerMut) -> ParseResult<Self> {
         let name = uoret!(Name::parse(lexer)?);
-        let generics = parse_type_arguments(lexer)?;
+        let mut lexer_clone = lexer.clone();
+        let generics = match parse_type_arguments(&mut lexer_clone) {
+            Ok(generics) => {
+                *lexer = lexer_clone;
+                generics
+            }
+            Err(_) => None,
+        };
         let span = name.span.merge_if(&generics);
         let generics = generics.unwrap_or_default();
         Ok(Some(span.embed(Invokable { name, generics })))
@@ -161,8 +364,8 @@ impl Parse for Invokable {
 
 impl Parse for Operator {
     fn parse(lexer: LexerMut) -> ParseResult<Self> {
-        Ok(match lexer.peek().clone() {
-            Token::Operator(o) => Some(lexer.next().span.embed(o)),
+        Ok(match lexer.peek() {
+            Token::Operator(o) => Some(lexer.next().span.embed(o.clone())),
             _ => None,
         })
     }
@@ -224,16 +427,12 @@ impl Parse for Parens {
     fn parse(lexer: LexerMut) -> ParseResult<Self> {
         let span1 = uoret!(lexer.eat(Punctuation::OpenParen));
 
-        let exprs = vec_separated(lexer, FunCallArgument::parse, Punctuation::Comma)?
-            .unwrap_or_default()
-            .inner;
-
-        if !exprs.is_empty() {
-            let _ = lexer.eat(Punctuation::Comma);
-        }
+        let (exprs, trailing_comma) =
+            vec_separated_trailing(lexer, FunCallArgument::parse, Punctuation::Comma)?;
+        let exprs = exprs.inner;
 
         let span2 = lexer.expect(Punctuation::CloseParen)?;
-        Ok(Some(span1.merge(span2).embed(Parens { exprs })))
+        Ok(Some(span1.merge(span2).embed(Parens { exprs, trailing_comma })))
     }
 }
 
@@ -258,6 +457,56 @@ impl Parse for Declaration {
     }
 }
 
+impl Parse for While {
+    fn parse(lexer: LexerMut) -> ParseResult<Self> {
+        let span = uoret!(lexer.eat(Keyword::While));
+        let condition = parse_expr_parts(lexer, true)?;
+        let condition = Box::new(condition.ok_or(Error::Expected("loop condition"))?);
+        let body = Block::parse_expect(lexer, "loop body")?;
+        let span = span.merge(body.span);
+
+        Ok(Some(span.embed(While { label: None, condition, body })))
+    }
+}
+
+impl Parse for For {
+    fn parse(lexer: LexerMut) -> ParseResult<Self> {
+        let span = uoret!(lexer.eat(Keyword::For));
+        let binding = Box::new(Invokable::parse_expect(lexer, "loop variable")?);
+        lexer.expect(Keyword::In)?;
+        let iterable = parse_expr_parts(lexer, true)?;
+        let iterable = Box::new(iterable.ok_or(Error::Expected("expression"))?);
+        let body = Block::parse_expect(lexer, "loop body")?;
+        let span = span.merge(body.span);
+
+        Ok(Some(span.embed(For { label: None, binding, iterable, body })))
+    }
+}
+
+impl Parse for IfExpr {
+    fn parse(lexer: LexerMut) -> ParseResult<Self> {
+        let span = uoret!(lexer.eat(Keyword::If));
+        let condition = parse_expr_parts(lexer, true)?;
+        let condition = Box::new(condition.ok_or(Error::Expected("condition"))?);
+        let then_branch = Block::parse_expect(lexer, "block")?;
+
+        let else_branch = match lexer.eat(Keyword::Else) {
+            Some(_) => {
+                let branch = or2(
+                    expecting("`if`", map(IfExpr::parse, Expr::IfExpr)),
+                    expecting("block", map(Block::parse, Expr::Block)),
+                )(lexer)?;
+                Some(Box::new(branch.expect("`expecting` doesn't return `None`")))
+            }
+            None => None,
+        };
+        let end = else_branch.as_ref().map_or(then_branch.span, |e| e.span);
+        let span = span.merge(end);
+
+        Ok(Some(span.embed(IfExpr { condition, then_branch, else_branch })))
+    }
+}
+
 impl Parse for DeclKind {
     fn parse(lexer: LexerMut) -> ParseResult<Self> {
         let decl_kind = match *lexer.peek() {
@@ -280,21 +529,41 @@ impl Parse for FunCallArgument {
 
             *lexer = lexer_clone;
             let span = name.span.merge(expr.span);
-            Ok(Some(span.embed(FunCallArgument { name: Some(name), expr })))
+            let name = Some(name.map(ArgName::Named));
+            Ok(Some(span.embed(FunCallArgument { name, expr })))
+        }
+
+        fn parse_record_arg(lexer: LexerMut) -> ParseResult<FunCallArgument> {
+            let dot = uoret!(lexer.eat(Punctuation::Dot));
+            let field = Ident::parse_expect(lexer, "field name")?;
+            lexer.expect(Punctuation::Equals)?;
+            let expr = Expr::parse_expect(lexer, "expression")?;
+
+            let name = Some(dot.merge(field.span).embed(ArgName::RecordArg(field.inner)));
+            Ok(Some(dot.merge(expr.span).embed(FunCallArgument { name, expr })))
         }
 
         fn wrap_expr(expr: Spanned<Expr>) -> Spanned<FunCallArgument> {
             expr.span.embed(FunCallArgument { name: None, expr })
         }
 
-        or2(parse_with_name, map2(Expr::parse, wrap_expr))(lexer)
+        or3(parse_record_arg, parse_with_name, map2(Expr::parse, wrap_expr))(lexer)
     }
 }
 
 impl Parse for StringLiteral {
     fn parse(lexer: LexerMut) -> ParseResult<Self> {
-        Ok(match lexer.peek().clone() {
-            Token::StringLit(s) => Some(lexer.next().span.embed(s)),
+        Ok(match lexer.peek() {
+            Token::StringLit(s) => Some(lexer.next().span.embed(s.clone())),
+            _ => None,
+        })
+    }
+}
+
+impl Parse for char {
+    fn parse(lexer: LexerMut) -> ParseResult<Self> {
+        Ok(match *lexer.peek() {
+            Token::CharLit(c) => Some(lexer.next().span.embed(c)),
             _ => None,
         })
     }
@@ -311,8 +580,8 @@ impl Parse for NumberLiteral {
 
 impl Parse for Ident {
     fn parse(lexer: LexerMut) -> ParseResult<Self> {
-        Ok(match lexer.peek().clone() {
-            Token::Ident(i) => Some(lexer.next().span.embed(i)),
+        Ok(match lexer.peek() {
+            Token::Ident(i) => Some(lexer.next().span.embed(i.clone())),
             _ => None,
         })
     }
@@ -320,8 +589,8 @@ impl Parse for Ident {
 
 impl Parse for UpperIdent {
     fn parse(lexer: LexerMut) -> ParseResult<Self> {
-        Ok(match lexer.peek().clone() {
-            Token::UpperIdent(i) => Some(lexer.next().span.embed(i)),
+        Ok(match lexer.peek() {
+            Token::UpperIdent(i) => Some(lexer.next().span.embed(i.clone())),
             _ => None,
         })
     }
@@ -358,60 +627,112 @@ pub(super) enum ExprPart {
     Invokable(Invokable),
     Lambda(Lambda),
     Block(Block),
+    /// An `if` expression, which can be an operand, e.g. `1 + if a { 2 } else { 3 }`
+    IfExpr(IfExpr),
     Parens(Parens),
+    /// The indices of an index expression, e.g. `[i]` in `a[i]`
+    Index(SpannedList<Expr>),
+    /// Square brackets that don't follow an operand, e.g. `[1, 2]` in `x = [1, 2]`
+    ArrayLit(SpannedList<Expr>),
     Match(MatchBody),
     And,
     Or,
+    Not,
     Dot,
     Equals,
+    /// An operator followed by `=`, e.g. `+=`. It contains the operator
+    /// without the `=`.
+    CompoundAssign(Operator),
+    QuestionMark,
 }
 
 pub(super) enum ExprPartKind {
     StringLit,
+    CharLit,
     NumberLit,
     InvokableIdent,
     InvokableType,
     InvokableOperator,
     Lambda,
     Block,
+    IfExpr,
     Parens,
+    Index,
+    ArrayLit,
     Match,
     And,
     Or,
+    Not,
     Dot,
     Equals,
+    CompoundAssign,
+    QuestionMark,
 }
 
 impl Parse for ExprPart {
     fn parse(lexer: LexerMut) -> ParseResult<Self> {
         #[allow(clippy::unnecessary_wraps)]
-        fn parse_and_or_dot_equals(lexer: LexerMut) -> ParseResult<ExprPart> {
+        fn parse_keyword_or_punct(lexer: LexerMut) -> ParseResult<ExprPart> {
             let part = match *lexer.peek() {
                 Token::Keyword(Keyword::And) => ExprPart::And,
                 Token::Keyword(Keyword::Or) => ExprPart::Or,
+                Token::Keyword(Keyword::Not) => ExprPart::Not,
                 Token::Punct(Punctuation::Dot) => ExprPart::Dot,
                 Token::Punct(Punctuation::Equals) => ExprPart::Equals,
+                Token::Punct(Punctuation::QuestionMark) => ExprPart::QuestionMark,
+                Token::Operator(ref o) => match compound_assign_operator(o) {
+                    Some(o) => ExprPart::CompoundAssign(o),
+                    None => return Ok(None),
+                },
                 _ => return Ok(None),
             };
             Ok(Some(lexer.next().span.embed(part)))
         }
 
         or7(
-            map(Literal::parse, ExprPart::Literal),
-            map(Invokable::parse, ExprPart::Invokable),
-            map(Lambda::parse, ExprPart::Lambda),
-            map(Block::parse, ExprPart::Block),
-            map(Parens::parse, ExprPart::Parens),
-            map(MatchBody::parse, ExprPart::Match),
-            parse_and_or_dot_equals,
+            expecting("`not`", parse_keyword_or_punct),
+            expecting("literal", map(Literal::parse, ExprPart::Literal)),
+            expecting("name", map(Invokable::parse, ExprPart::Invokable)),
+            expecting("lambda", map(Lambda::parse, ExprPart::Lambda)),
+            or2(
+                expecting("block", map(Block::parse, ExprPart::Block)),
+                expecting("`if`", map(IfExpr::parse, ExprPart::IfExpr)),
+            ),
+            or2(
+                expecting("`(`", map(Parens::parse, ExprPart::Parens)),
+                expecting("`[`", parse_index),
+            ),
+            expecting("`match`", map(MatchBody::parse, ExprPart::Match)),
         )(lexer)
     }
 }
 
+fn parse_index(lexer: LexerMut) -> ParseResult<ExprPart> {
+    let indices = enclose_multiple(
+        Expr::parse,
+        Punctuation::OpenBracket,
+        Punctuation::Comma,
+        Punctuation::CloseBracket,
+        true,
+    )(lexer)?;
+    Ok(indices.map(|i| i.map(ExprPart::Index)))
+}
+
+/// Returns the operator of a compound assignment such as `+=` without the `=`.
+/// Comparison operators (`==`, `!=`, `<=` and `>=`) aren't compound assignments.
+fn compound_assign_operator(operator: &Operator) -> Option<Operator> {
+    let op = operator.get().strip_suffix('=')?;
+    if op.is_empty() || op.ends_with('=') || matches!(op, "!" | "<" | ">") {
+        return None;
+    }
+    Some(Operator::new(op))
+}
+
 impl ExprPart {
     fn kind(&self) -> ExprPartKind {
         match self {
             ExprPart::Literal(Literal::StringLit(_)) => ExprPartKind::StringLit,
+            ExprPart::Literal(Literal::CharLit(_)) => ExprPartKind::CharLit,
             ExprPart::Literal(Literal::NumberLit(_)) => ExprPartKind::NumberLit,
             ExprPart::Invokable(i) => match *i.name {
                 Name::Operator(_) => ExprPartKind::InvokableOperator,
@@ -420,33 +741,67 @@ impl ExprPart {
             },
             ExprPart::Lambda(_) => ExprPartKind::Lambda,
             ExprPart::Block(_) => ExprPartKind::Block,
+            ExprPart::IfExpr(_) => ExprPartKind::IfExpr,
             ExprPart::Parens(_) => ExprPartKind::Parens,
+            ExprPart::Index(_) => ExprPartKind::Index,
+            ExprPart::ArrayLit(_) => ExprPartKind::ArrayLit,
             ExprPart::Match(_) => ExprPartKind::Match,
             ExprPart::And => ExprPartKind::And,
             ExprPart::Or => ExprPartKind::Or,
+            ExprPart::Not => ExprPartKind::Not,
             ExprPart::Dot => ExprPartKind::Dot,
             ExprPart::Equals => ExprPartKind::Equals,
+            ExprPart::CompoundAssign(_) => ExprPartKind::CompoundAssign,
+            ExprPart::QuestionMark => ExprPartKind::QuestionMark,
         }
     }
 
+    /// Whether an expression can end after this part, i.e. it isn't an infix
+    /// operator
+    fn ends_operand(&self) -> bool {
+        !matches!(
+            self.kind(),
+            ExprPartKind::InvokableOperator
+                | ExprPartKind::And
+                | ExprPartKind::Or
+                | ExprPartKind::Not
+                | ExprPartKind::Dot
+                | ExprPartKind::Equals
+                | ExprPartKind::CompoundAssign
+        )
+    }
+
     fn into_operand(self) -> Result<Expr, Error> {
         Ok(match self {
             ExprPart::Literal(l) => Expr::Literal(l),
             ExprPart::Invokable(n) => Expr::Invokable(n),
             ExprPart::Lambda(l) => Expr::Lambda(l),
             ExprPart::Block(b) => Expr::Block(b),
+            ExprPart::IfExpr(i) => Expr::IfExpr(i),
             ExprPart::Parens(p) => Expr::Tuple(p),
+            ExprPart::ArrayLit(elements) => Expr::ArrayLit(ArrayLit { elements }),
+            ExprPart::Index(_) => return Err(Error::ExpectedGot4("operand", "`[`")),
             ExprPart::Match(_) => return Err(Error::ExpectedGot4("operand", "`match`")),
             ExprPart::And => return Err(Error::ExpectedGot4("operand", "`and`")),
             ExprPart::Or => return Err(Error::ExpectedGot4("operand", "`or`")),
+            ExprPart::Not => return Err(Error::ExpectedGot4("operand", "`not`")),
             ExprPart::Dot => return Err(Error::ExpectedGot4("operand", "`.`")),
             ExprPart::Equals => return Err(Error::ExpectedGot4("operand", "`=`")),
+            ExprPart::CompoundAssign(_) => {
+                return Err(Error::ExpectedGot4("operand", "assignment operator"));
+            }
+            ExprPart::QuestionMark => {
+                return Err(Error::ExpectedGot4("operand", "`?`"));
+            }
         })
     }
 
     fn assert_is_operator(&self, lhs: &Expr) -> Result<(), Error> {
         match self {
-            ExprPart::Parens(_) | ExprPart::Dot | ExprPart::Equals => Ok(()),
+            | ExprPart::Parens(_)
+            | ExprPart::Dot
+            | ExprPart::Equals
+            | ExprPart::CompoundAssign(_) => Ok(()),
 
             ExprPart::Invokable(i) => match *i.name {
                 Name::Operator(_) | Name::Type(_) => validate_operand(lhs),
@@ -457,7 +812,11 @@ impl ExprPart {
 
             ExprPart::And | ExprPart::Or => validate_operand(lhs),
 
-            ExprPart::Match(_) => validate_operand(lhs),
+            ExprPart::Not => Err(Error::ExpectedGot4("operator", "`not`")),
+
+            ExprPart::Match(_) | ExprPart::QuestionMark | ExprPart::Index(_) => {
+                validate_operand(lhs)
+            }
 
             ExprPart::Lambda(l) => {
                 Err(Error::ExpectedGot3("operator", Expr::Lambda(l.clone())))
@@ -467,16 +826,40 @@ impl ExprPart {
                 Err(Error::ExpectedGot3("operator", Expr::Block(b.clone())))
             }
 
+            ExprPart::IfExpr(i) => {
+                Err(Error::ExpectedGot3("operator", Expr::IfExpr(i.clone())))
+            }
+
             ExprPart::Literal(l) => {
                 Err(Error::ExpectedGot3("operator", Expr::Literal(l.clone())))
             }
+
+            ExprPart::ArrayLit(elements) => {
+                let array = ArrayLit { elements: elements.clone() };
+                Err(Error::ExpectedGot3("operator", Expr::ArrayLit(array)))
+            }
         }
     }
 
+    fn into_unary_operation(
+        self,
+        span: TextRange,
+        operand: Spanned<Expr>,
+    ) -> Result<Spanned<Expr>, Error> {
+        let operator = match self {
+            ExprPart::Not => UnaryOperator::Not,
+            e => panic!("Expected prefix operator, got {:?}", e),
+        };
+        validate_operand(&operand.inner)?;
+        let span = span.merge(operand.span);
+        Ok(span.embed(Expr::UnaryOp(UnaryOp { operator, operand: Box::new(operand) })))
+    }
+
     fn into_operation(
         self,
         lhs: Spanned<Expr>,
         rhs: Spanned<Expr>,
+        operators: Option<&OperatorTable>,
     ) -> Result<Spanned<Expr>, Error> {
         let span = lhs.span.merge(rhs.span);
         let data = match &self {
@@ -484,6 +867,8 @@ impl ExprPart {
                 Name::Operator(operator) => {
                     validate_operand(&lhs.inner)?;
                     validate_operand(&rhs.inner)?;
+                    validate_mixed_operators(&lhs.inner, operator, operators)?;
+                    validate_mixed_operators(&rhs.inner, operator, operators)?;
                     let operator = operator.clone();
                     Expr::Operation(Operation {
                         operator,
@@ -522,12 +907,40 @@ impl ExprPart {
                 validate_operand(&lhs.inner)?;
                 Expr::Assignment(Assignment { lhs: Box::new(lhs), rhs: Box::new(rhs) })
             }
+            // `a += b` is desugared to `a = a + b`
+            ExprPart::CompoundAssign(operator) => {
+                validate_operand(&lhs.inner)?;
+                validate_operand(&rhs.inner)?;
+                validate_mixed_operators(&rhs.inner, operator, operators)?;
+                let operation = Expr::Operation(Operation {
+                    operator: operator.clone(),
+                    lhs: Box::new(lhs.clone()),
+                    rhs: Box::new(rhs),
+                });
+                let rhs = span.embed(operation);
+                Expr::Assignment(Assignment { lhs: Box::new(lhs), rhs: Box::new(rhs) })
+            }
             e => panic!("Expected name, infix operator, `.` or `=`, got {:?}", e),
         };
         Ok(span.embed(data))
     }
 }
 
+/// Operators can only be mixed without a block if their precedence is known
+fn validate_mixed_operators(
+    operand: &Expr,
+    operator: &Operator,
+    operators: Option<&OperatorTable>,
+) -> Result<(), Error> {
+    match operand {
+        Expr::Operation(o) if &o.operator != operator => match operators {
+            Some(table) if table.is_unambiguous(&o.operator, operator) => Ok(()),
+            _ => Err(ValidationError::OperationsRequireBlock.into()),
+        },
+        _ => Ok(()),
+    }
+}
+
 fn validate_operand(expr: &Expr) -> Result<(), Error> {
     if let Some(op) = expr.to_operator() {
         Err(Error::OperatorInsteadOfOperand(op))
@@ -1,8 +1,9 @@
-use super::formatting::{Beauty, BeautyData, ToBeauty};
+use super::formatting::ToBeauty;
 use ast::expr::*;
 use ast::item::*;
+use ast::pattern::*;
 use ast::token::*;
-use ast::TinyString;
+use ast::{Beauty, TinyString};
 
 macro_rules! beauty_impl {
     (struct $name:ident { $($field:ident),* $(,)? }) => {
@@ -10,7 +11,7 @@ macro_rules! beauty_impl {
             fn to_beauty(&self) -> Beauty {
                 Beauty::kvs(
                     stringify!($name),
-                    vec![ $( Beauty::kv(stringify!($field), Beauty::from(&self.$field)) ),* ]
+                    vec![ $( Beauty::kv(stringify!($field), self.$field.to_beauty()) ),* ]
                 )
             }
         }
@@ -19,7 +20,7 @@ macro_rules! beauty_impl {
         impl ToBeauty for $name {
             fn to_beauty(&self) -> Beauty {
                 match self {
-                    $( $name::$variant(f) => f.into(), )*
+                    $( $name::$variant(f) => f.to_beauty(), )*
                 }
             }
         }
@@ -27,15 +28,26 @@ macro_rules! beauty_impl {
 }
 
 impl ToBeauty for NumberLiteral {
-    fn to_beauty(&self) -> Beauty { Beauty { data: BeautyData::Number(*self), num: 1 } }
+    fn to_beauty(&self) -> Beauty {
+        let key = match self {
+            NumberLiteral::Int(..) => "Int",
+            NumberLiteral::UInt(..) => "UInt",
+            NumberLiteral::Float(..) => "Float",
+        };
+        Beauty::kv(key, Beauty::number(*self))
+    }
 }
 
 impl ToBeauty for StringLiteral {
     fn to_beauty(&self) -> Beauty {
-        Beauty { data: BeautyData::String(self.clone()), num: 1 }
+        Beauty::kv("StringLiteral", Beauty::text(self.get()))
     }
 }
 
+impl ToBeauty for char {
+    fn to_beauty(&self) -> Beauty { Beauty::kv("CharLiteral", Beauty::char(*self)) }
+}
+
 impl ToBeauty for DeclKind {
     fn to_beauty(&self) -> Beauty {
         match self {
@@ -54,12 +66,18 @@ impl ToBeauty for ScOperator {
     }
 }
 
-impl ToBeauty for TinyString {
+impl ToBeauty for UnaryOperator {
     fn to_beauty(&self) -> Beauty {
-        Beauty { data: BeautyData::Name(self.clone()), num: 1 }
+        match self {
+            UnaryOperator::Not => "Not".to_beauty(),
+        }
     }
 }
 
+impl ToBeauty for TinyString {
+    fn to_beauty(&self) -> Beauty { Beauty::text(self.clone()) }
+}
+
 impl ToBeauty for Ident {
     fn to_beauty(&self) -> Beauty { Beauty::kv("Ident", self.inner().to_beauty()) }
 }
@@ -72,20 +90,38 @@ impl ToBeauty for Operator {
     fn to_beauty(&self) -> Beauty { Beauty::kv("Operator", self.inner().to_beauty()) }
 }
 
-beauty_impl! {
-    enum Item { Function, Class, Enum, Impl, Use }
+impl ToBeauty for Item {
+    fn to_beauty(&self) -> Beauty {
+        match self {
+            Item::Function(f) => f.to_beauty(),
+            Item::Class(c) => c.to_beauty(),
+            Item::Enum(e) => e.to_beauty(),
+            Item::Impl(i) => i.to_beauty(),
+            Item::Use(u) => u.to_beauty(),
+            Item::TypeAlias(t) => t.to_beauty(),
+            Item::Error => "Error".to_beauty(),
+        }
+    }
+}
+
+impl ToBeauty for Attribute {
+    fn to_beauty(&self) -> Beauty {
+        match self {
+            Attribute::Cfg(flag) => Beauty::kv("Cfg", flag.to_beauty()),
+        }
+    }
 }
 
 beauty_impl! {
-    struct Function { name, generics, args, return_ty, body }
+    struct Function { name, generics, args, return_ty, where_clause, body, docs, attrs }
 }
 
 beauty_impl! {
-    struct Class { name, generics, fields }
+    struct Class { name, generics, fields, docs, attrs }
 }
 
 beauty_impl! {
-    struct Enum { name, generics, variants }
+    struct Enum { name, generics, variants, docs, attrs }
 }
 
 beauty_impl! {
@@ -97,11 +133,15 @@ beauty_impl! {
 }
 
 beauty_impl! {
-    struct Impl { generics, r#trait, ty, items }
+    struct Impl { generics, r#trait, ty, items, docs, attrs }
 }
 
 beauty_impl! {
-    struct Use { path, wildcard }
+    struct Use { path, wildcard, docs, attrs }
+}
+
+beauty_impl! {
+    struct TypeAlias { name, generics, ty, docs, attrs }
 }
 
 beauty_impl! {
@@ -112,8 +152,16 @@ beauty_impl! {
     struct GenericParam { name, bounds }
 }
 
-impl ToBeauty for TypeBound {
-    fn to_beauty(&self) -> Beauty { match *self {} }
+beauty_impl! {
+    struct TypeBound { ty }
+}
+
+beauty_impl! {
+    struct WhereClause { bounds }
+}
+
+beauty_impl! {
+    struct WhereBound { param, bounds }
 }
 
 beauty_impl! {
@@ -121,23 +169,50 @@ beauty_impl! {
 }
 
 beauty_impl! {
-    struct NamedType { name, args }
+    struct NamedType { path, name, args }
 }
 
 impl ToBeauty for TypeArgument {
     fn to_beauty(&self) -> Beauty {
         match self {
-            TypeArgument::Type(f) => f.into(),
+            TypeArgument::Type(f) => f.to_beauty(),
             TypeArgument::Wildcard => "Wildcard".to_beauty(),
         }
     }
 }
 
-beauty_impl! {
-    enum Expr {
-        Invokable, Literal, ParenCall, MemberCall, Operation,
-        ShortcircuitingOp, Assignment, TypeAscription, Lambda,
-        Block, Empty, Declaration, Match, Statement, Tuple
+impl ToBeauty for Expr {
+    fn to_beauty(&self) -> Beauty {
+        match self {
+            Expr::Invokable(e) => e.to_beauty(),
+            Expr::Literal(e) => e.to_beauty(),
+            Expr::ParenCall(e) => e.to_beauty(),
+            Expr::Index(e) => e.to_beauty(),
+            Expr::MemberCall(e) => e.to_beauty(),
+            Expr::Operation(e) => e.to_beauty(),
+            Expr::ShortcircuitingOp(e) => e.to_beauty(),
+            Expr::UnaryOp(e) => e.to_beauty(),
+            Expr::Assignment(e) => e.to_beauty(),
+            Expr::TypeAscription(e) => e.to_beauty(),
+            Expr::Statement(e) => e.to_beauty(),
+            Expr::Try(e) => Beauty::kv("Try", e.to_beauty()),
+            Expr::Lambda(e) => e.to_beauty(),
+            Expr::Block(e) => e.to_beauty(),
+            Expr::Tuple(e) => e.to_beauty(),
+            Expr::ArrayLit(e) => e.to_beauty(),
+            Expr::Empty(e) => e.to_beauty(),
+            Expr::Declaration(e) => e.to_beauty(),
+            Expr::Match(e) => e.to_beauty(),
+            Expr::While(e) => e.to_beauty(),
+            Expr::For(e) => e.to_beauty(),
+            Expr::IfExpr(e) => e.to_beauty(),
+            Expr::Return(Some(e)) => Beauty::kv("Return", e.to_beauty()),
+            Expr::Return(None) => "Return".to_beauty(),
+            Expr::Break(Some(l)) => Beauty::kv("Break", l.to_beauty()),
+            Expr::Break(None) => "Break".to_beauty(),
+            Expr::Continue(Some(l)) => Beauty::kv("Continue", l.to_beauty()),
+            Expr::Continue(None) => "Continue".to_beauty(),
+        }
     }
 }
 
@@ -146,11 +221,26 @@ beauty_impl! {
 }
 
 beauty_impl! {
-    enum Literal { NumberLit, StringLit }
+    enum Literal { NumberLit, StringLit, CharLit }
+}
+
+beauty_impl! {
+    struct ParenCall { receiver, args, trailing_comma }
 }
 
 beauty_impl! {
-    struct ParenCall { receiver, args }
+    struct IndexExpr { receiver, indices }
+}
+
+/// Empty lists are omitted, so `[]` would otherwise print nothing
+impl ToBeauty for ArrayLit {
+    fn to_beauty(&self) -> Beauty {
+        if self.elements.is_empty() {
+            "ArrayLit".to_beauty()
+        } else {
+            Beauty::kv("ArrayLit", Beauty::kv("elements", self.elements.to_beauty()))
+        }
+    }
 }
 
 beauty_impl! {
@@ -165,6 +255,10 @@ beauty_impl! {
     struct ScOperation { operator, lhs, rhs }
 }
 
+beauty_impl! {
+    struct UnaryOp { operator, operand }
+}
+
 beauty_impl! {
     struct Assignment { lhs, rhs }
 }
@@ -182,13 +276,22 @@ beauty_impl! {
 }
 
 beauty_impl! {
-    struct Parens { exprs }
+    struct Parens { exprs, trailing_comma }
 }
 
 beauty_impl! {
     struct FunCallArgument { name, expr }
 }
 
+impl ToBeauty for ArgName {
+    fn to_beauty(&self) -> Beauty {
+        match self {
+            ArgName::Named(i) => i.to_beauty(),
+            ArgName::RecordArg(i) => Beauty::kv("RecordArg", i.to_beauty()),
+        }
+    }
+}
+
 beauty_impl! {
     struct LambdaArgument { name, ty }
 }
@@ -202,5 +305,55 @@ beauty_impl! {
 }
 
 beauty_impl! {
-    struct Match { expr, /* match_arms */ }
+    struct While { label, condition, body }
+}
+
+beauty_impl! {
+    struct For { label, binding, iterable, body }
+}
+
+beauty_impl! {
+    struct IfExpr { condition, then_branch, else_branch }
+}
+
+beauty_impl! {
+    struct Match { expr, match_arms }
+}
+
+beauty_impl! {
+    struct MatchArm { pattern, expr }
+}
+
+impl ToBeauty for Pattern {
+    fn to_beauty(&self) -> Beauty {
+        match self {
+            Pattern::Wildcard => "Wildcard".to_beauty(),
+            Pattern::Binding(i) => Beauty::kv("Binding", i.to_beauty()),
+            Pattern::Literal(l) => l.to_beauty(),
+            Pattern::Tuple(t) => Beauty::kv("Tuple", t.to_beauty()),
+            Pattern::Range(r) => Beauty::kv("Range", r.to_beauty()),
+            Pattern::RangeExclusive(r) => Beauty::kv("RangeExclusive", r.to_beauty()),
+            Pattern::Class(c) => c.to_beauty(),
+            Pattern::Enum(e) => e.to_beauty(),
+            Pattern::TypeAscription(t) => t.to_beauty(),
+            Pattern::Or(o) => Beauty::kv("Or", o.to_beauty()),
+            Pattern::Guard(g) => g.to_beauty(),
+        }
+    }
+}
+
+beauty_impl! {
+    struct RangePattern { from, to }
+}
+
+beauty_impl! {
+    struct ClassPattern { name, fields }
+}
+
+beauty_impl! {
+    struct EnumPattern { name, field }
+}
+
+beauty_impl! {
+    struct GuardPattern { pattern, guard }
 }
@@ -52,15 +52,48 @@ pub(super) fn map2<T, U>(
     move |lexer| Ok(f(lexer)?.map(x))
 }
 
+/// Tries `f1`, then `f2` if `f1` returned `None`. If `f1` fails at the first
+/// token with [`Error::ExpectedOneOf`], `f2` is tried as well, and if it fails
+/// at the same token, the expected options of both are merged.
 pub(super) fn or2<T>(
     f1: impl FnOnce(LexerMut) -> ParseResult<T>,
     f2: impl FnOnce(LexerMut) -> ParseResult<T>,
 ) -> impl FnOnce(LexerMut) -> ParseResult<T> {
     move |lexer| {
-        Ok(match f1(lexer)? {
-            Some(result) => Some(result),
-            None => f2(lexer)?,
-        })
+        let start = lexer.clone();
+        match f1(lexer) {
+            Ok(Some(result)) => Ok(Some(result)),
+            Ok(None) => f2(lexer),
+            Err(Error::ExpectedOneOf { mut options, got, at })
+                if at == start.peek_span() =>
+            {
+                *lexer = start;
+                match f2(lexer) {
+                    Ok(Some(result)) => Ok(Some(result)),
+                    Ok(None) => Err(Error::ExpectedOneOf { options, got, at }),
+                    Err(Error::ExpectedOneOf { options: options2, at: at2, .. })
+                        if at2 == at =>
+                    {
+                        options.extend(options2);
+                        Err(Error::ExpectedOneOf { options, got, at })
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Returns an [`Error::ExpectedOneOf`] error with the given description instead
+/// of `None`, so the alternatives of `or2` etc. can be listed in the error
+pub(super) fn expecting<T>(
+    expected: &'static str,
+    f: impl FnOnce(LexerMut) -> ParseResult<T>,
+) -> impl FnOnce(LexerMut) -> ParseResult<T> {
+    move |lexer| match f(lexer)? {
+        Some(result) => Ok(Some(result)),
+        None => Err(lexer.expected(expected)),
     }
 }
 
@@ -114,6 +147,39 @@ pub(super) fn or7<T>(
     or3(or3(f1, f2, f3), or3(f4, f5, f6), f7)
 }
 
+/// Parses zero or more items that aren't separated, e.g. type bounds. Returns an
+/// empty list if there are none. The parser must consume tokens when it returns
+/// an item.
+pub(super) fn many0<T>(
+    parser: impl Fn(LexerMut) -> ParseResult<T>,
+) -> impl Fn(LexerMut) -> ParseResult<SpannedList<T>> {
+    move |lexer| {
+        let mut results = Vec::new();
+        let mut span: Option<TextRange> = None;
+        while let Some(item) = parser(lexer)? {
+            span = Some(span.map_or(item.span, |span| span.merge(item.span)));
+            results.push(item);
+        }
+        let span = span.unwrap_or_default();
+        Ok(Some(span.embed(results.into_boxed_slice())))
+    }
+}
+
+/// Like [`many0`], but returns an error if there isn't at least one item
+pub(super) fn many1<T>(
+    parser: impl Fn(LexerMut) -> ParseResult<T>,
+    expected: &'static str,
+) -> impl Fn(LexerMut) -> ParseResult<SpannedList<T>> {
+    let parser = many0(parser);
+    move |lexer| {
+        let items = uoret!(parser(lexer)?);
+        if items.is_empty() {
+            return Err(lexer.expected(expected));
+        }
+        Ok(Some(items))
+    }
+}
+
 pub(super) fn vec_separated<T>(
     lexer: LexerMut,
     mut f: impl FnMut(LexerMut) -> ParseResult<T>,
@@ -124,10 +190,13 @@ pub(super) fn vec_separated<T>(
     let mut span = first.span;
     let mut results = vec![first];
     loop {
-        if lexer_clone.eat(separator.clone()).is_some() {
-            if let Some(next) = f(&mut lexer_clone)? {
+        // a separator that isn't followed by an item is not consumed
+        let mut after_separator = lexer_clone.clone();
+        if after_separator.eat(separator.clone()).is_some() {
+            if let Some(next) = f(&mut after_separator)? {
                 span = span.merge(next.span);
                 results.push(next);
+                lexer_clone = after_separator;
                 continue;
             }
         }
@@ -137,6 +206,22 @@ pub(super) fn vec_separated<T>(
     Ok(Some(span.embed(results.into_boxed_slice())))
 }
 
+/// Like [`vec_separated`], but a separator after the last item is allowed. Returns
+/// an empty list if there are no items, and whether a trailing separator was eaten.
+pub(super) fn vec_separated_trailing<T>(
+    lexer: LexerMut,
+    f: impl FnMut(LexerMut) -> ParseResult<T>,
+    separator: impl Into<Token> + Clone,
+) -> Result<(Spanned<SpannedList<T>>, bool), Error> {
+    match vec_separated(lexer, f, separator.clone())? {
+        Some(items) => {
+            let trailing = lexer.eat(separator).is_some();
+            Ok((items, trailing))
+        }
+        None => Ok((Default::default(), false)),
+    }
+}
+
 pub(super) fn enclosed<T>(
     parser: impl FnOnce(LexerMut) -> ParseResult<T>,
     left: impl Into<Token> + Clone,
@@ -163,15 +248,12 @@ pub(super) fn enclose_multiple<T>(
     trailing_separator: bool,
 ) -> impl FnOnce(LexerMut) -> ParseResult<SpannedList<T>> {
     let parser_inner = move |lexer: LexerMut| {
-        let items = vec_separated(lexer, parser.clone(), separator.clone())?;
-        match items {
-            Some(items) => {
-                if trailing_separator && *lexer.peek() == separator.into() {
-                    lexer.next();
-                }
-                Ok(Some(items))
-            }
-            None => Ok(Some(Default::default())),
+        if trailing_separator {
+            let (items, _) = vec_separated_trailing(lexer, parser.clone(), separator)?;
+            Ok(Some(items))
+        } else {
+            let items = vec_separated(lexer, parser.clone(), separator)?;
+            Ok(Some(items.unwrap_or_default()))
         }
     };
     enclosed(parser_inner, left, right, || {
@@ -0,0 +1,386 @@
+//! Coarse incremental parsing, reusing items that weren't affected by an edit
+
+use ast::expr::*;
+use ast::item::*;
+use ast::pattern::*;
+use ast::token::{Ident, Operator, Token, UpperIdent};
+use ast::{Spanned, TextRange, TinyString};
+
+use crate::validation::{check_module, Validate};
+use crate::{Error, Lexer, Parse};
+
+/// Re-parses a source file after it was edited.
+///
+/// `old` are the items of the previous parse, `tokens` are the tokens of the
+/// _new_ source, and `edit` is the range in the _old_ source that was replaced.
+///
+/// Items before the edit are reused as-is. Items after the edit are reused
+/// with shifted spans, as soon as the lexer re-synchronizes with them. Only the
+/// items in between are parsed again. The result is the same as that of
+/// [`parse`](crate::parse)ing `tokens` from scratch.
+pub fn reparse(
+    old: &[Spanned<Item>],
+    tokens: &[Spanned<Token>],
+    edit: TextRange,
+) -> Result<Vec<Spanned<Item>>, Error> {
+    // The text after the edit is unchanged, so if the edit is before the last
+    // item, the last token was moved by as many bytes as that item. Otherwise no
+    // item after the edit can be reused, so the offset doesn't matter.
+    let last_token = tokens.iter().rev().find(|t| t.inner != Token::Eof);
+    let delta = match (old.last(), last_token) {
+        (Some(last), Some(token)) if edit.end() < last.span.start() => {
+            token.span.end() as i64 - last.span.end() as i64
+        }
+        _ => 0,
+    };
+    let edit_end_new = (edit.end() as i64 + delta) as u32;
+
+    // Items ending with `}` or `;` can't be affected by text inserted after them
+    let reused = old.iter().take_while(|i| i.span.end() <= edit.start()).count();
+    let mut results = old[..reused].to_vec();
+    let start = results.last().map(|i| i.span.end()).unwrap_or(0);
+
+    // Text adjacent to the beginning of an item might be merged with its first
+    // token, so only items starting strictly after the edit can be reused
+    let suffix = &old[reused..];
+    let affected = suffix.iter().take_while(|i| i.span.start() <= edit.end()).count();
+    let suffix = &suffix[affected..];
+
+    let first = tokens.iter().position(|t| t.span.start() >= start).unwrap();
+    let mut lexer = Lexer::from_tokens(&tokens[first..]);
+
+    let mut new_items = Vec::new();
+    loop {
+        let pos = lexer.tokens[0].span.start();
+        if pos > edit_end_new {
+            let old_pos = pos as i64 - delta;
+            let matching = suffix.iter().position(|i| i.span.start() as i64 == old_pos);
+            if let Some(i) = matching {
+                new_items.validate(())?;
+                results.extend(new_items);
+                results.extend(suffix[i..].iter().map(|item| {
+                    let mut item = item.clone();
+                    item.shift(delta);
+                    item
+                }));
+                check_module(&results).map_err(|e| e.inner)?;
+                return Ok(results);
+            }
+        }
+        match Item::parse(&mut lexer)? {
+            Some(item) => new_items.push(item),
+            None => break,
+        }
+    }
+    lexer.finish()?;
+    new_items.validate(())?;
+    results.extend(new_items);
+    check_module(&results).map_err(|e| e.inner)?;
+    Ok(results)
+}
+
+/// Moves all spans in an AST node by a given number of bytes
+trait ShiftSpans {
+    fn shift(&mut self, delta: i64);
+}
+
+impl ShiftSpans for TextRange {
+    fn shift(&mut self, delta: i64) {
+        // omitted parts, like missing generics, have a default span
+        if *self == TextRange::default() {
+            return;
+        }
+        let start = (self.start() as i64 + delta) as u32;
+        let end = (self.end() as i64 + delta) as u32;
+        *self = TextRange::new(start, end);
+    }
+}
+
+impl<T: ShiftSpans> ShiftSpans for Spanned<T> {
+    fn shift(&mut self, delta: i64) {
+        self.span.shift(delta);
+        self.inner.shift(delta);
+    }
+}
+
+impl<T: ShiftSpans + ?Sized> ShiftSpans for Box<T> {
+    fn shift(&mut self, delta: i64) { (**self).shift(delta) }
+}
+
+impl<T: ShiftSpans> ShiftSpans for [T] {
+    fn shift(&mut self, delta: i64) {
+        for x in self {
+            x.shift(delta);
+        }
+    }
+}
+
+impl<T: ShiftSpans> ShiftSpans for Vec<T> {
+    fn shift(&mut self, delta: i64) { (**self).shift(delta) }
+}
+
+impl<T: ShiftSpans> ShiftSpans for Option<T> {
+    fn shift(&mut self, delta: i64) {
+        if let Some(x) = self {
+            x.shift(delta);
+        }
+    }
+}
+
+macro_rules! shift_impl {
+    (struct $name:ident { $($field:ident),* $(,)? }) => {
+        impl ShiftSpans for $name {
+            fn shift(&mut self, _delta: i64) {
+                $( self.$field.shift(_delta); )*
+            }
+        }
+    };
+    (enum $name:ident { $($variant:ident),* $(,)? }) => {
+        impl ShiftSpans for $name {
+            fn shift(&mut self, delta: i64) {
+                match self {
+                    $( $name::$variant(f) => f.shift(delta), )*
+                }
+            }
+        }
+    };
+    (leaf $($name:ty),* $(,)?) => {
+        $(
+            impl ShiftSpans for $name {
+                fn shift(&mut self, _: i64) {}
+            }
+        )*
+    };
+}
+
+shift_impl! {
+    leaf Ident, UpperIdent, Operator, Name, Literal, DeclKind, ScOperator, Empty, ArgName,
+    UnaryOperator, TinyString, bool, ()
+}
+
+impl ShiftSpans for Item {
+    fn shift(&mut self, delta: i64) {
+        match self {
+            Item::Function(f) => f.shift(delta),
+            Item::Class(c) => c.shift(delta),
+            Item::Enum(e) => e.shift(delta),
+            Item::Impl(i) => i.shift(delta),
+            Item::Use(u) => u.shift(delta),
+            Item::TypeAlias(t) => t.shift(delta),
+            Item::Error => {}
+        }
+    }
+}
+
+shift_impl! {
+    enum Attribute { Cfg }
+}
+
+shift_impl! {
+    struct Function { name, generics, args, return_ty, where_clause, body, docs, attrs }
+}
+
+shift_impl! {
+    struct Class { name, generics, fields, docs, attrs }
+}
+
+shift_impl! {
+    struct Enum { name, generics, variants, docs, attrs }
+}
+
+shift_impl! {
+    struct ClassField { name, ty, default }
+}
+
+shift_impl! {
+    struct EnumVariant { name, arguments }
+}
+
+shift_impl! {
+    struct Impl { generics, r#trait, ty, items, docs, attrs }
+}
+
+shift_impl! {
+    struct Use { path, wildcard, docs, attrs }
+}
+
+shift_impl! {
+    struct TypeAlias { name, generics, ty, docs, attrs }
+}
+
+shift_impl! {
+    struct GenericParam { name, bounds }
+}
+
+shift_impl! {
+    struct TypeBound { ty }
+}
+
+shift_impl! {
+    struct WhereClause { bounds }
+}
+
+shift_impl! {
+    struct WhereBound { param, bounds }
+}
+
+shift_impl! {
+    struct FunArgument { name, ty, default }
+}
+
+shift_impl! {
+    struct NamedType { path, name, args }
+}
+
+impl ShiftSpans for TypeArgument {
+    fn shift(&mut self, delta: i64) {
+        match self {
+            TypeArgument::Type(t) => t.shift(delta),
+            TypeArgument::Wildcard => {}
+        }
+    }
+}
+
+impl ShiftSpans for Expr {
+    fn shift(&mut self, delta: i64) {
+        match self {
+            Expr::Invokable(e) => e.shift(delta),
+            Expr::Literal(e) => e.shift(delta),
+            Expr::ParenCall(e) => e.shift(delta),
+            Expr::Index(e) => e.shift(delta),
+            Expr::MemberCall(e) => e.shift(delta),
+            Expr::Operation(e) => e.shift(delta),
+            Expr::ShortcircuitingOp(e) => e.shift(delta),
+            Expr::UnaryOp(e) => e.shift(delta),
+            Expr::Assignment(e) => e.shift(delta),
+            Expr::TypeAscription(e) => e.shift(delta),
+            Expr::Statement(e) | Expr::Try(e) => e.shift(delta),
+            Expr::Lambda(e) => e.shift(delta),
+            Expr::Block(e) => e.shift(delta),
+            Expr::Tuple(e) => e.shift(delta),
+            Expr::ArrayLit(e) => e.shift(delta),
+            Expr::Empty(e) => e.shift(delta),
+            Expr::Declaration(e) => e.shift(delta),
+            Expr::Match(e) => e.shift(delta),
+            Expr::While(e) => e.shift(delta),
+            Expr::For(e) => e.shift(delta),
+            Expr::IfExpr(e) => e.shift(delta),
+            Expr::Return(e) => e.shift(delta),
+            Expr::Break(l) | Expr::Continue(l) => l.shift(delta),
+        }
+    }
+}
+
+shift_impl! {
+    struct Invokable { name, generics }
+}
+
+shift_impl! {
+    struct ParenCall { receiver, args, trailing_comma }
+}
+
+shift_impl! {
+    struct IndexExpr { receiver, indices }
+}
+
+shift_impl! {
+    struct ArrayLit { elements }
+}
+
+shift_impl! {
+    struct MemberCall { receiver, member }
+}
+
+shift_impl! {
+    struct Operation { operator, lhs, rhs }
+}
+
+shift_impl! {
+    struct ScOperation { operator, lhs, rhs }
+}
+
+shift_impl! {
+    struct UnaryOp { operator, operand }
+}
+
+shift_impl! {
+    struct Assignment { lhs, rhs }
+}
+
+shift_impl! {
+    struct TypeAscription { ty, expr }
+}
+
+shift_impl! {
+    struct Lambda { args, body }
+}
+
+shift_impl! {
+    struct Block { exprs, ends_with_semicolon }
+}
+
+shift_impl! {
+    struct Parens { exprs, trailing_comma }
+}
+
+shift_impl! {
+    struct FunCallArgument { name, expr }
+}
+
+shift_impl! {
+    struct LambdaArgument { name, ty }
+}
+
+shift_impl! {
+    struct Declaration { decl_kind, name, value }
+}
+
+shift_impl! {
+    struct While { label, condition, body }
+}
+
+shift_impl! {
+    struct For { label, binding, iterable, body }
+}
+
+shift_impl! {
+    struct IfExpr { condition, then_branch, else_branch }
+}
+
+shift_impl! {
+    struct Match { expr, match_arms }
+}
+
+shift_impl! {
+    struct MatchArm { pattern, expr }
+}
+
+impl ShiftSpans for Pattern {
+    fn shift(&mut self, delta: i64) {
+        match self {
+            Pattern::Wildcard | Pattern::Binding(_) | Pattern::Literal(_) => {}
+            Pattern::Range(r) | Pattern::RangeExclusive(r) => r.shift(delta),
+            Pattern::Class(c) => c.shift(delta),
+            Pattern::Enum(e) => e.shift(delta),
+            Pattern::TypeAscription(t) => t.shift(delta),
+            Pattern::Tuple(t) | Pattern::Or(t) => t.shift(delta),
+            Pattern::Guard(g) => g.shift(delta),
+        }
+    }
+}
+
+shift_impl! {
+    struct RangePattern { from, to }
+}
+
+shift_impl! {
+    struct ClassPattern { name, fields }
+}
+
+shift_impl! {
+    struct EnumPattern { name, field }
+}
+
+shift_impl! {
+    struct GuardPattern { pattern, guard }
+}
@@ -1,22 +1,103 @@
 use ast::expr::{Block, Expr};
 use ast::item::*;
-use ast::token::{Ident, Keyword, Punctuation, Token, UpperIdent};
+use ast::token::{Ident, Keyword, Operator, Punctuation, Token, UpperIdent};
+use ast::{Spanned, SpannedList, TextRange, TinyString};
 
 use crate::uoret;
 
 use super::helpers::*;
-use super::{LexerMut, Parse, ParseResult};
+use super::{Error, LexerMut, Parse, ParseResult};
 
 impl Parse for Item {
     fn parse(lexer: LexerMut) -> ParseResult<Self> {
-        or5(
+        let docs = parse_doc_comments(lexer);
+        let attrs = parse_attributes(lexer)?;
+
+        let item = or6(
             map(Function::parse, Item::Function),
             map(Class::parse, Item::Class),
             map(Enum::parse, Item::Enum),
             map(Impl::parse, Item::Impl),
             map(Use::parse, Item::Use),
-        )(lexer)
+            map(TypeAlias::parse, Item::TypeAlias),
+        )(lexer)?;
+
+        let item = match attrs {
+            Some(attrs) => {
+                let mut item = item.ok_or(Error::Expected("item after attribute"))?;
+                item.span = attrs.span.merge(item.span);
+                let item_attrs = match &mut item.inner {
+                    Item::Function(f) => &mut f.attrs,
+                    Item::Class(c) => &mut c.attrs,
+                    Item::Enum(e) => &mut e.attrs,
+                    Item::Impl(i) => &mut i.attrs,
+                    Item::Use(u) => &mut u.attrs,
+                    Item::TypeAlias(t) => &mut t.attrs,
+                    Item::Error => unreachable!("error items aren't parsed"),
+                };
+                *item_attrs = attrs.inner;
+                Some(item)
+            }
+            None => item,
+        };
+
+        let docs = match docs {
+            Some(docs) => docs,
+            None => return Ok(item),
+        };
+        let mut item = item.ok_or(Error::Expected("item after doc comment"))?;
+        item.span = docs.span.merge(item.span);
+        let item_docs = match &mut item.inner {
+            Item::Function(f) => &mut f.docs,
+            Item::Class(c) => &mut c.docs,
+            Item::Enum(e) => &mut e.docs,
+            Item::Impl(i) => &mut i.docs,
+            Item::Use(u) => &mut u.docs,
+            Item::TypeAlias(t) => &mut t.docs,
+            Item::Error => unreachable!("error items aren't parsed"),
+        };
+        *item_docs = Some(docs.inner);
+        Ok(Some(item))
+    }
+}
+
+/// Consumes the attributes of an item. The only supported attribute is
+/// `@cfg(flag)`, which is evaluated later, see [`filter_cfg`](crate::filter_cfg).
+///
+/// Returns `None` if there are no attributes.
+fn parse_attributes(lexer: LexerMut) -> ParseResult<Vec<Spanned<Attribute>>> {
+    let mut start = uoret!(lexer.eat(Punctuation::At));
+    let mut attrs = Vec::new();
+    loop {
+        let name = Ident::parse_expect(lexer, "attribute name")?;
+        if &*name.inner.inner() != "cfg" {
+            return Err(Error::UnknownAttribute(name.inner));
+        }
+        lexer.expect(Punctuation::OpenParen)?;
+        let flag = Ident::parse_expect(lexer, "flag")?;
+        let end = lexer.expect(Punctuation::CloseParen)?;
+        attrs.push(start.merge(end).embed(Attribute::Cfg(flag)));
+
+        match lexer.eat(Punctuation::At) {
+            Some(at) => start = at,
+            None => {
+                let span = attrs[0].span.merge(end);
+                return Ok(Some(span.embed(attrs)));
+            }
+        }
+    }
+}
+
+/// Consumes consecutive doc comments, e.g. `## Returns the answer`
+fn parse_doc_comments(lexer: LexerMut) -> Option<Spanned<Vec<TinyString>>> {
+    let mut docs = Vec::new();
+    let mut span = None;
+    while let Token::DocComment(doc) = lexer.peek() {
+        docs.push(doc.clone());
+        let doc_span = lexer.next().span;
+        span = Some(span.map_or(doc_span, |s: TextRange| s.merge(doc_span)));
     }
+    span.map(|span| span.embed(docs))
 }
 
 impl Parse for Function {
@@ -46,6 +127,9 @@ impl Parse for Function {
         let return_ty = NamedType::parse(lexer)?;
         span = span.merge_if(&return_ty);
 
+        let where_clause = WhereClause::parse(lexer)?;
+        span = span.merge_if(&where_clause);
+
         let body = match lexer.eat(Punctuation::Semicolon) {
             Some(s) => {
                 span = span.merge(s);
@@ -55,26 +139,92 @@ impl Parse for Function {
         };
         span = span.merge_if(&body);
 
-        Ok(Some(span.embed(Function { name, generics, args, return_ty, body })))
+        Ok(Some(span.embed(Function {
+            name,
+            generics,
+            args,
+            return_ty,
+            where_clause,
+            body,
+            docs: None,
+            attrs: Vec::new(),
+        })))
     }
 }
 
+/// Parses a generic parameter with optional bounds, which are separated by
+/// spaces or `+`, e.g. `T Clone + Show`
 impl Parse for GenericParam {
     fn parse(lexer: LexerMut) -> ParseResult<Self> {
         let name = uoret!(UpperIdent::parse(lexer)?);
-        let bounds = Box::<[_]>::from([]);
-        Ok(Some(name.span.embed(GenericParam { name, bounds })))
+        let bounds = uoret!(many0(TypeBound::parse)(lexer)?);
+        let span =
+            if bounds.is_empty() { name.span } else { name.span.merge(bounds.span) };
+        Ok(Some(span.embed(GenericParam { name, bounds: bounds.inner })))
+    }
+}
+
+impl Parse for WhereClause {
+    fn parse(lexer: LexerMut) -> ParseResult<Self> {
+        let span = uoret!(lexer.eat(Keyword::Where));
+        let bounds = vec_separated(lexer, WhereBound::parse, Punctuation::Comma)?
+            .ok_or_else(|| lexer.expected("generic parameter"))?;
+        let span = span.merge(bounds.span);
+        Ok(Some(span.embed(WhereClause { bounds: bounds.inner })))
+    }
+}
+
+impl Parse for WhereBound {
+    fn parse(lexer: LexerMut) -> ParseResult<Self> {
+        let param = uoret!(UpperIdent::parse(lexer)?);
+        lexer.expect(Punctuation::Colon)?;
+        let bounds = many1(TypeBound::parse, "type bound")(lexer)?.unwrap();
+        let span = param.span.merge(bounds.span);
+        Ok(Some(span.embed(WhereBound { param, bounds: bounds.inner })))
+    }
+}
+
+/// Parses a type bound, which may be preceded by `+`
+impl Parse for TypeBound {
+    fn parse(lexer: LexerMut) -> ParseResult<Self> {
+        let plus = lexer.eat(Token::Operator(Operator::new("+")));
+        match NamedType::parse(lexer)? {
+            Some(ty) => Ok(Some(ty.map(|ty| TypeBound { ty }))),
+            None if plus.is_some() => Err(lexer.expected("type bound")),
+            None => Ok(None),
+        }
     }
 }
 
 impl Parse for NamedType {
     fn parse(rest: LexerMut) -> ParseResult<Self> {
+        let path = parse_type_path(rest);
         let name = uoret!(UpperIdent::parse(rest)?);
         let args = parse_type_arguments(rest)?;
-        let span = name.span.merge_if(&args);
+        let start = path.first().map_or(name.span, |p| p.span);
+        let span = start.merge(name.span).merge_if(&args);
         let args = args.unwrap_or_default();
-        Ok(Some(span.embed(NamedType { name, args })))
+        Ok(Some(span.embed(NamedType { path, name, args })))
+    }
+}
+
+/// Parses the modules before a type name, e.g. `a.b.` in `a.b.Type`. If the
+/// identifiers and dots aren't followed by a type name, nothing is consumed.
+fn parse_type_path(lexer: LexerMut) -> SpannedList<Ident> {
+    let mut path = Vec::new();
+    let mut rest = lexer.clone();
+    while let Token::Ident(ident) = rest.peek() {
+        let span = rest.next().span;
+        if rest.eat(Punctuation::Dot).is_none() {
+            return Box::new([]);
+        }
+        path.push(span.embed(ident.clone()));
+    }
+    if path.is_empty() || !matches!(rest.peek(), Token::UpperIdent(_)) {
+        return Box::new([]);
     }
+    *lexer = rest;
+    path.into_boxed_slice()
 }
 
 impl Parse for FunArgument {
@@ -119,7 +269,8 @@ impl Parse for Class {
         )(lexer)?;
         let span2 = lexer.expect(Punctuation::Semicolon)?;
 
-        Ok(Some(span1.merge(span2).embed(Class { name, generics, fields })))
+        let class = Class { name, generics, fields, docs: None, attrs: Vec::new() };
+        Ok(Some(span1.merge(span2).embed(class)))
     }
 }
 
@@ -152,7 +303,13 @@ impl Parse for Enum {
             true,
         )(lexer)?;
 
-        Ok(Some(span.merge(variants.span).embed(Enum { name, generics, variants })))
+        Ok(Some(span.merge(variants.span).embed(Enum {
+            name,
+            generics,
+            variants,
+            docs: None,
+            attrs: Vec::new(),
+        })))
     }
 }
 
@@ -195,6 +352,8 @@ impl Parse for Impl {
             r#trait,
             ty: r#type,
             items,
+            docs: None,
+            attrs: Vec::new(),
         })))
     }
 }
@@ -220,7 +379,22 @@ impl Parse for Use {
 
         let span2 = lexer.expect(Punctuation::Semicolon)?;
 
-        Ok(Some(span1.merge(span2).embed(Use { path, wildcard })))
+        let use_item = Use { path, wildcard, docs: None, attrs: Vec::new() };
+        Ok(Some(span1.merge(span2).embed(use_item)))
+    }
+}
+
+impl Parse for TypeAlias {
+    fn parse(lexer: LexerMut) -> ParseResult<Self> {
+        let span1 = uoret!(lexer.eat(Keyword::Type));
+        let name = UpperIdent::parse_expect(lexer, "type name")?;
+        let generics = parse_generics(lexer)?.unwrap_or_default();
+        lexer.expect(Punctuation::Equals)?;
+        let ty = NamedType::parse_expect(lexer, "type")?;
+        let span2 = lexer.expect(Punctuation::Semicolon)?;
+
+        let alias = TypeAlias { name, generics, ty, docs: None, attrs: Vec::new() };
+        Ok(Some(span1.merge(span2).embed(alias)))
     }
 }
 
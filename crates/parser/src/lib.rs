@@ -1,20 +1,30 @@
 #![allow(dead_code)]
 
-use ast::item::Item;
+use std::collections::HashSet;
+
+use ast::item::{Attribute, Item};
 use ast::token::Token;
 use ast::{Spanned, TextRange};
-pub use error::Error;
-use validation::Validate;
+pub use error::{Error, Errors};
+pub use incremental::reparse;
+pub use operators::{Assoc, OperatorInfo, OperatorTable};
+pub use recovery::{parse_all_errors, parse_with_recovery};
+use validation::{check_module, Validate};
 
 pub use self::formatting::ToBeauty;
+pub use self::to_source::ToSource;
 
 pub mod error;
 pub mod expr;
 pub mod formatting;
 mod formatting_impl;
 mod helpers;
+mod incremental;
 pub mod items;
+mod operators;
 pub mod patterns;
+mod recovery;
+pub mod to_source;
 mod validation;
 
 #[cfg(test)]
@@ -27,10 +37,14 @@ type LexerMut<'a, 'b> = &'a mut Lexer<'b>;
 #[derive(Debug, Clone)]
 struct Lexer<'a> {
     tokens: &'a [Spanned<Token>],
+    /// Precedence of user-defined operators, see [`parse_with_operators`]
+    operators: Option<&'a OperatorTable>,
 }
 
 impl<'a> Lexer<'a> {
-    fn from_tokens(tokens: &'a [Spanned<Token>]) -> Self { Self { tokens } }
+    fn from_tokens(tokens: &'a [Spanned<Token>]) -> Self {
+        Self { tokens, operators: None }
+    }
 
     /// Returns `Some(span)` and advances the lexer if the next token matches
     /// `elem`
@@ -56,14 +70,25 @@ impl<'a> Lexer<'a> {
     }
 
     /// Return the next token and advance the lexer
-    fn next(&mut self) -> Spanned<Token> {
+    fn next(&mut self) -> &'a Spanned<Token> {
         let (next, rest) = self.tokens.split_first().unwrap();
         self.tokens = rest;
-        next.clone()
+        next
     }
 
     /// Return the next token _without_ advancing the lexer
-    fn peek(&self) -> &Token { &self.tokens[0].inner }
+    fn peek(&self) -> &'a Token { &self.tokens[0].inner }
+
+    fn peek_span(&self) -> TextRange { self.tokens[0].span }
+
+    /// Returns an error that `expected` was expected at the next token
+    fn expected(&self, expected: &'static str) -> Error {
+        Error::ExpectedOneOf {
+            options: vec![expected],
+            got: self.peek().clone(),
+            at: self.peek_span(),
+        }
+    }
 
     fn len(&self) -> usize { self.tokens.len() }
 
@@ -84,6 +109,7 @@ impl<'a> Lexer<'a> {
         }
         self.finish()?;
         results.validate(())?;
+        check_module(&results).map_err(|e| e.inner)?;
         Ok(results)
     }
 }
@@ -92,6 +118,43 @@ pub fn parse(tokens: &[Spanned<Token>]) -> Result<Vec<Spanned<Item>>, Error> {
     Lexer::from_tokens(tokens).parse_items()
 }
 
+/// Like [`parse`], but operators in the table are parsed according to their
+/// precedence and associativity, so they can be mixed without blocks.
+pub fn parse_with_operators(
+    tokens: &[Spanned<Token>],
+    operators: &OperatorTable,
+) -> Result<Vec<Spanned<Item>>, Error> {
+    Lexer { tokens, operators: Some(operators) }.parse_items()
+}
+
+/// Like [`parse`], but items with a `@cfg(flag)` attribute are only included if
+/// the flag is in `flags`. [`parse`] includes all items.
+pub fn parse_with_cfg(
+    tokens: &[Spanned<Token>],
+    flags: &HashSet<&str>,
+) -> Result<Vec<Spanned<Item>>, Error> {
+    let mut items = parse(tokens)?;
+    filter_cfg(&mut items, flags);
+    Ok(items)
+}
+
+/// Removes the items, including items in impl blocks, that have a `@cfg(flag)`
+/// attribute whose flag isn't in `flags`
+pub fn filter_cfg(items: &mut Vec<Spanned<Item>>, flags: &HashSet<&str>) {
+    items.retain(|item| {
+        item.attrs().iter().all(|attr| match &attr.inner {
+            Attribute::Cfg(flag) => flags.contains(flag.get()),
+        })
+    });
+    for item in items {
+        if let Item::Impl(i) = &mut item.inner {
+            let mut impl_items = std::mem::take(&mut i.items.inner).into_vec();
+            filter_cfg(&mut impl_items, flags);
+            i.items.inner = impl_items.into_boxed_slice();
+        }
+    }
+}
+
 trait Parse: Sized {
     fn parse(lexer: LexerMut) -> ParseResult<Self>;
 
@@ -102,7 +165,7 @@ trait Parse: Sized {
     ) -> Result<Spanned<Self>, Error> {
         match Self::parse(lexer)? {
             Some(result) => Ok(result),
-            None => Err(Error::ExpectedGot2(expect, lexer.peek().clone())),
+            None => Err(lexer.expected(expect)),
         }
     }
 }
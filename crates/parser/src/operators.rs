@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use ast::token::Operator;
+
+/// The associativity of an operator, i.e. whether `a ~ b ~ c` is parsed as
+/// `{a ~ b} ~ c` (left) or `a ~ {b ~ c}` (right)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperatorInfo {
+    /// Operators with a higher precedence bind more strongly
+    pub precedence: u8,
+    pub assoc: Assoc,
+}
+
+/// The precedence and associativity of user-defined operators, used by
+/// [`parse_with_operators`](crate::parse_with_operators).
+///
+/// Operators that aren't in the table are left-associative, and mixing them
+/// with other operators requires a block, e.g. `a + {b * c}`. Operators in the
+/// table can be mixed without a block, unless they have the same precedence
+/// but different associativity.
+#[derive(Debug, Clone, Default)]
+pub struct OperatorTable {
+    map: HashMap<Operator, OperatorInfo>,
+}
+
+impl OperatorTable {
+    pub fn new() -> Self { Self::default() }
+
+    /// Adds an operator, returning its previous info, if any
+    pub fn insert(
+        &mut self,
+        operator: Operator,
+        info: OperatorInfo,
+    ) -> Option<OperatorInfo> {
+        self.map.insert(operator, info)
+    }
+
+    pub fn get(&self, operator: &Operator) -> Option<OperatorInfo> {
+        self.map.get(operator).copied()
+    }
+
+    /// Returns whether the operators can be mixed without a block
+    pub(crate) fn is_unambiguous(&self, a: &Operator, b: &Operator) -> bool {
+        match (self.get(a), self.get(b)) {
+            (Some(a), Some(b)) => a.precedence != b.precedence || a.assoc == b.assoc,
+            _ => false,
+        }
+    }
+}
@@ -1,12 +1,43 @@
+use ast::expr::Literal;
 use ast::pattern::Pattern;
-use ast::token::{Ident, Keyword};
+use ast::token::{Ident, Keyword, Punctuation};
 
-use crate::{uoret, Parse};
+use crate::helpers::{enclose_multiple, map, or4};
+use crate::{uoret, LexerMut, Parse, ParseResult};
 
+/// Parses a wildcard (`_`), a literal, a binding (`let x`) or a tuple of
+/// patterns, e.g. `(let x, _, 5)`
 impl Parse for Pattern {
-    fn parse(lexer: crate::LexerMut) -> crate::ParseResult<Self> {
-        let kw = uoret!(lexer.eat(Keyword::Let));
-        let ident = Ident::parse_expect(lexer, "identifier")?;
-        Ok(Some(kw.merge(ident.span).embed(Pattern::Binding(ident.inner))))
+    fn parse(lexer: LexerMut) -> ParseResult<Self> {
+        or4(
+            parse_wildcard,
+            map(Literal::parse, Pattern::Literal),
+            parse_binding,
+            parse_tuple,
+        )(lexer)
     }
 }
+
+fn parse_wildcard(lexer: LexerMut) -> ParseResult<Pattern> {
+    let span = uoret!(lexer.eat(Punctuation::Underscore));
+    Ok(Some(span.embed(Pattern::Wildcard)))
+}
+
+fn parse_binding(lexer: LexerMut) -> ParseResult<Pattern> {
+    let kw = uoret!(lexer.eat(Keyword::Let));
+    let ident = Ident::parse_expect(lexer, "identifier")?;
+    Ok(Some(kw.merge(ident.span).embed(Pattern::Binding(ident.inner))))
+}
+
+fn parse_tuple(lexer: LexerMut) -> ParseResult<Pattern> {
+    let patterns = uoret!(enclose_multiple(
+        Pattern::parse,
+        Punctuation::OpenParen,
+        Punctuation::Comma,
+        Punctuation::CloseParen,
+        true,
+    )(lexer)?);
+    Ok(Some(patterns.map(|p| {
+        Pattern::Tuple(p.into_vec().into_iter().map(|p| p.inner).collect())
+    })))
+}
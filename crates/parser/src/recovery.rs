@@ -0,0 +1,115 @@
+//! Error recovery, so that more than one error can be reported at a time
+
+use ast::item::Item;
+use ast::token::{Keyword, Punctuation, Token};
+use ast::Spanned;
+
+use crate::validation::{check_module, Validate};
+use crate::{Error, Errors, Lexer, Parse};
+
+/// Parses all items like [`parse`](crate::parse), but doesn't stop at the
+/// first error. When an item can't be parsed, tokens are skipped until the next
+/// token that can start an item, and parsing continues from there.
+///
+/// Returns the items and all errors. The skipped tokens are replaced with an
+/// [`Item::Error`] placeholder, so the items still cover the whole text.
+pub fn parse_with_recovery(
+    tokens: &[Spanned<Token>],
+) -> (Vec<Spanned<Item>>, Vec<Error>) {
+    let (items, errors) = recover(tokens);
+    (items, errors.into_iter().map(|e| e.inner).collect())
+}
+
+/// Like [`parse_with_recovery`], but each error has a span. For a syntax error,
+/// this is the span of the token where parsing failed; the tokens that were
+/// skipped because of it are covered by the [`Item::Error`] placeholder. Errors
+/// in an item that was parsed, but is invalid, have the span of the item.
+///
+/// The errors are sorted by their position.
+pub fn parse_all_errors(tokens: &[Spanned<Token>]) -> (Vec<Spanned<Item>>, Errors) {
+    let (items, mut errors) = recover(tokens);
+    errors.sort_by_key(|e| e.span.start());
+    (items, Errors(errors))
+}
+
+fn recover(tokens: &[Spanned<Token>]) -> (Vec<Spanned<Item>>, Vec<Spanned<Error>>) {
+    let mut lexer = Lexer::from_tokens(tokens);
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+
+    loop {
+        let before = lexer.tokens;
+        let (error, failed_at) = match Item::parse(&mut lexer) {
+            Ok(Some(item)) => match item.validate(()) {
+                Ok(()) => {
+                    items.push(item);
+                    continue;
+                }
+                Err(e) => (e.into(), item.span),
+            },
+            Ok(None) => match lexer.finish() {
+                Ok(()) => break,
+                Err(_) => (lexer.expected("item"), lexer.tokens[0].span),
+            },
+            Err(e) => (e, lexer.tokens[0].span),
+        };
+        let skipped = skip_to_next_item(before);
+        let span = before[0].span.merge(before[skipped - 1].span);
+        errors.push(failed_at.embed(error));
+        items.push(span.embed(Item::Error));
+        lexer.tokens = &before[skipped..];
+    }
+    if let Err(e) = check_module(&items) {
+        errors.push(e.span.embed(e.inner.into()));
+    }
+    (items, errors)
+}
+
+/// Returns the index of the next token that can start an item and isn't nested
+/// in brackets, or of the `Eof` token. The first token is always skipped.
+fn skip_to_next_item(tokens: &[Spanned<Token>]) -> usize {
+    let mut open_brackets = Vec::new();
+
+    for (i, token) in tokens.iter().enumerate().skip(1) {
+        match **token {
+            Token::Punct(p) => match p {
+                Punctuation::Pipe => {
+                    if open_brackets.last() == Some(&Punctuation::Pipe) {
+                        open_brackets.pop();
+                    } else {
+                        open_brackets.push(Punctuation::Pipe);
+                    }
+                }
+                Punctuation::OpenParen
+                | Punctuation::OpenBracket
+                | Punctuation::OpenBrace => open_brackets.push(p),
+                Punctuation::CloseParen
+                | Punctuation::CloseBracket
+                | Punctuation::CloseBrace => {
+                    // unclosed lambda parameter lists can't be nested in brackets
+                    while open_brackets.last() == Some(&Punctuation::Pipe) {
+                        open_brackets.pop();
+                    }
+                    open_brackets.pop();
+                }
+                _ => {}
+            },
+            Token::Keyword(
+                Keyword::Fun
+                | Keyword::Class
+                | Keyword::Enum
+                | Keyword::Impl
+                | Keyword::Use
+                | Keyword::Type,
+            )
+            | Token::DocComment(_)
+                if open_brackets.is_empty() =>
+            {
+                return i;
+            }
+            Token::Eof => return i,
+            _ => {}
+        }
+    }
+    tokens.len()
+}
@@ -2,6 +2,14 @@ use std::ffi::OsStr;
 use std::fs::{read_to_string, File};
 use std::io::Write;
 
+use ast::item::Item;
+use ast::{Spanned, TextRange};
+
+/// Lexes and parses the text
+fn parse(text: &str) -> Result<Vec<Spanned<Item>>, crate::Error> {
+    super::parse(lexer::lex(text).tokens())
+}
+
 #[test]
 fn run_parser_tests() {
     for file in std::fs::read_dir("./tests").unwrap() {
@@ -28,7 +36,7 @@ fn run_parser_tests() {
                 let expected = expected.trim_end();
 
                 if expected != actual {
-                    let changes = difference::Changeset::new(expected, &actual, "\n");
+                    let changes = difference::Changeset::new(expected, actual, "\n");
                     eprintln!("{}", changes);
                     eprintln!("Input:\n{}", content);
                     panic!(
@@ -45,3 +53,863 @@ fn run_parser_tests() {
         }
     }
 }
+
+/// A simple xorshift PRNG, so the tests are deterministic
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self, max: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % max as u64) as usize
+    }
+}
+
+#[test]
+fn reparse_random_edits() {
+    const SNIPPETS: &[&str] =
+        &["", " ", "\n", "x", "}", "(", "# comment\n", "fun g() Unit {}", "let y = 1;"];
+
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let mut sources = vec![];
+    for file in std::fs::read_dir("./tests").unwrap() {
+        let path = file.unwrap().path();
+        if path.is_file() && path.extension() == Some(OsStr::new("wa")) {
+            sources.push(read_to_string(&path).unwrap());
+        }
+    }
+    sources.push(sources.join("\n"));
+
+    for source in &sources {
+        let old = parse(source).unwrap();
+        for _ in 0..200 {
+            let item = &old[rng.next(old.len())];
+            let len = item.span.len();
+            let start = item.span.start() as usize + rng.next(len as usize + 1);
+            let end = (start + rng.next(4)).min(item.span.end() as usize);
+            if !source.is_char_boundary(start) || !source.is_char_boundary(end) {
+                continue;
+            }
+            let snippet = SNIPPETS[rng.next(SNIPPETS.len())];
+            let new_src = format!("{}{}{}", &source[..start], snippet, &source[end..]);
+
+            let edit = TextRange::from(start..end);
+            let program = lexer::lex(&new_src);
+            let reparsed = super::reparse(&old, program.tokens(), edit);
+            let expected = super::parse(program.tokens());
+            match (reparsed, expected) {
+                (Ok(reparsed), Ok(expected)) => {
+                    assert_eq!(format!("{:#?}", reparsed), format!("{:#?}", expected))
+                }
+                (Err(_), Err(_)) => {}
+                (reparsed, expected) => panic!(
+                    "reparse and parse disagree for {:?}:\n{:?}\n{:?}",
+                    new_src, reparsed, expected
+                ),
+            }
+        }
+    }
+}
+
+#[test]
+fn items_in_impl() {
+    assert!(parse("impl Foo { type Bar = Baz; fun bar() Bar {} }").is_ok());
+
+    let err = parse("impl Foo { class Bar(); }").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "impl blocks can only contain functions and type aliases, not classes"
+    );
+}
+
+#[test]
+fn format_error_aggregate() {
+    use std::error::Error as _;
+
+    use ast::token::Punctuation;
+
+    use crate::{Error, Errors};
+
+    let errors = Errors(vec![
+        TextRange::new(4, 7).embed(Error::Expected("function body")),
+        TextRange::new(12, 13).embed(Error::ExpectedGot(
+            Punctuation::Semicolon.into(),
+            Punctuation::CloseBrace.into(),
+        )),
+    ]);
+    assert_eq!(
+        errors.to_string(),
+        "4..7: Expected function body\n12..13: Expected `;`, got `}`"
+    );
+    assert_eq!(errors.source().unwrap().to_string(), "Expected function body");
+}
+
+#[test]
+fn newlines_continue_expressions() {
+    use crate::ToBeauty;
+
+    let cases = [
+        ("fun f() Int { a + b }", "fun f() Int {\n    a +\n    b\n}"),
+        ("fun f() Int { a + b + c }", "fun f() Int { a\n  + b\n  + c }"),
+        ("fun f() Int { x.y().z }", "fun f() Int {\n    x\n        .y()\n        .z\n}"),
+        ("fun f() Int { a and b }", "fun f() Int { a\nand\nb }"),
+    ];
+    for &(single_line, multi_line) in &cases {
+        assert_eq!(
+            parse(single_line).unwrap().to_beauty_string(),
+            parse(multi_line).unwrap().to_beauty_string()
+        );
+    }
+}
+
+#[test]
+fn call_non_callable() {
+    let err = parse("fun f() Unit { 5(x) }").unwrap_err();
+    assert_eq!(err.to_string(), "This expression can't be called: Literal");
+    let err = parse("fun f() Unit { \"s\"(x) }").unwrap_err();
+    assert_eq!(err.to_string(), "This expression can't be called: Literal");
+
+    let err = parse("fun f() Unit { (1, 2)(3) }").unwrap_err();
+    assert_eq!(err.to_string(), "This expression can't be called: Tuple");
+    let err = parse("fun f() Unit { (g,)(x) }").unwrap_err();
+    assert_eq!(err.to_string(), "This expression can't be called: Tuple");
+    let err = parse("fun f() Unit { { x }(1) }").unwrap_err();
+    assert_eq!(err.to_string(), "This expression can't be called: Block");
+
+    assert!(parse("fun f() Unit { f(x) }").is_ok());
+    assert!(parse("fun f() Unit { (g)(x) }").is_ok());
+    assert!(parse("fun f() Unit { x.f(y) }").is_ok());
+}
+
+#[test]
+fn loop_binding_and_body() {
+    let err = parse("fun f() Unit { for x[T] in xs { x } }").unwrap_err();
+    assert_eq!(err.to_string(), "No generics were expected here");
+    let err = parse("fun f() Unit { for X in xs { x } }").unwrap_err();
+    assert_eq!(err.to_string(), "Expected identifier, got type");
+    let err = parse("fun f() Unit { while x; y }").unwrap_err();
+    assert!(err.to_string().starts_with("Expected loop body"));
+
+    assert!(parse("fun f() Unit { while {a} { b } }").is_ok());
+    assert!(parse("fun f() Unit { let x = for y in xs.iter { y }; x }").is_ok());
+    assert!(parse("impl Foo for Bar { fun f() Unit { for x in y {} } }").is_ok());
+}
+
+#[test]
+fn doc_comment_without_item_is_ignored() {
+    let text = "##### banner\nfun f() Unit {\n    ## no item\n    x\n}\n## end";
+    let items = parse(text).unwrap();
+    assert_eq!(items.len(), 1);
+    assert!(matches!(&items[0].inner, Item::Function(f) if f.docs.is_none()));
+}
+
+#[test]
+fn recover_from_invalid_item() {
+    let text = "fun a() Unit {\n    (1, 2)\n}\n\n\
+                fun b() Unit {\n    let = { c };\n}\n\n\
+                fun c() Unit {\n    |x| x\n}";
+    let (items, errors) = super::parse_with_recovery(lexer::lex(text).tokens());
+    let names: Vec<_> = items
+        .iter()
+        .map(|item| match &item.inner {
+            Item::Function(f) => format!("{:?}", f.name.inner),
+            Item::Error => "Error".to_string(),
+            _ => panic!("expected function"),
+        })
+        .collect();
+    assert_eq!(names, ["Ident a", "Error", "Ident c"]);
+    // the error item spans the whole function `b`
+    assert_eq!(&text[items[1].span], "fun b() Unit {\n    let = { c };\n}");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].to_string(), "Expected variable name, got `=`");
+
+    let lexed = lexer::lex("x y fun a() Unit {}");
+    let (items, errors) = super::parse_with_recovery(lexed.tokens());
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].span, TextRange::new(0, 3));
+    assert!(matches!(items[0].inner, Item::Error));
+    assert_eq!(errors[0].to_string(), "Expected item, got i`x`");
+}
+
+#[test]
+fn record_args() {
+    let err = parse("fun f() Unit { Foo(.x = 1, .y = 2, .x = 3) }").unwrap_err();
+    assert_eq!(err.to_string(), "Field `x` is initialized more than once");
+    let err = parse("fun f() Unit { Foo(1, .x = 2) }").unwrap_err();
+    assert_eq!(err.to_string(), "Named argument after unnamed argument");
+    let err = parse("fun f() Unit { (.x = 1) }").unwrap_err();
+    assert_eq!(err.to_string(), "Named argument not allowed in tuple");
+    assert!(parse("fun f() Unit { Foo(.x 1) }").is_err());
+
+    assert!(parse("fun f() Unit { Foo(.x = 1, y: 2) }").is_ok());
+}
+
+#[test]
+fn print_and_reparse() {
+    use crate::ToSource;
+
+    /// Removes the ` @ 1..5` suffixes, because the spans change when the code
+    /// is reformatted
+    fn strip_spans(debug: &str) -> String {
+        let mut result = String::new();
+        let mut rest = debug;
+        while let Some(i) = rest.find(" @ ") {
+            result.push_str(&rest[..i]);
+            let is_span_char = |c: char| c.is_ascii_digit() || c == '.';
+            rest = rest[i + 3..].trim_start_matches(is_span_char);
+        }
+        result.push_str(rest);
+        result
+    }
+
+    let mut sources = vec![
+        String::from(
+            "fun f() Unit { r#\"a\"b\"#; \"\"\"c\"d\"\"\"; 1.0 + 1e100 + +5 + -3 \
+             + '\\'' }",
+        ),
+        String::from("fun g() Int { 7u8 + -2i16 + 0x10i128 + 0.5f32 + 1e3f64 }"),
+        String::from(
+            "fun h() Int { if a { 1 } else if b.c() { 2 } else { if d { 3 } } }",
+        ),
+        String::from(
+            "fun i() Int { for x in xs { if x { continue } else { break } }; return 1 }",
+        ),
+        String::from("fun j() Bool { while not a.b and c { x }; not not d == e }"),
+        String::from("fun k() Int { xs[0] = m[i, j + 1][k]; f[T](x)[0]; \"ab\"[1] }"),
+        String::from("fun m() Unit { let xs = [[1, 2], [], [f(x)]]; xs[0][1] }"),
+        String::from("fun n(x a.b.C[d.E]) std.Map[K, V] { x }"),
+        String::from(
+            "fun l() Unit { 'a: for x in xs { 'b: while x { break 'a; continue 'b } } }",
+        ),
+        String::from("fun o[T, U Eq](x T) U where T: Show + Eq, U: a.Hash { x }"),
+    ];
+    for file in std::fs::read_dir("./tests").unwrap() {
+        let path = file.unwrap().path();
+        if path.is_file() && path.extension() == Some(OsStr::new("wa")) {
+            sources.push(read_to_string(&path).unwrap());
+        }
+    }
+
+    for source in &sources {
+        let items = parse(source).unwrap();
+
+        let printed = items.to_source();
+        let reparsed = match parse(&printed) {
+            Ok(reparsed) => reparsed,
+            Err(err) => panic!("{}:\n{}", err, printed),
+        };
+        assert_eq!(
+            strip_spans(&format!("{:#?}", items)),
+            strip_spans(&format!("{:#?}", reparsed)),
+            "{}",
+            printed,
+        );
+        assert_eq!(reparsed.to_source(), printed);
+    }
+}
+
+#[test]
+fn type_in_value_position() {
+    let err = parse("fun f() Unit { Foo }").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The type `Foo` can't be used as a value. To construct it, write `Foo()`"
+    );
+    assert!(parse("fun f() Unit { let x = Foo; }").is_err());
+    assert!(parse("fun f() Unit { g(Foo) }").is_err());
+
+    assert!(parse("fun f() Unit { Foo() }").is_ok());
+    assert!(parse("fun f() Unit { Foo[T](1) }").is_ok());
+    assert!(parse("fun f() Unit { Foo.new() }").is_ok());
+    assert!(parse("fun f() Unit { x Foo }").is_ok());
+
+    // unit constructors are values
+    assert!(parse("class Foo();\nfun f() Unit { let x = Foo; g(Foo) }").is_ok());
+    assert!(parse("fun f() Unit { Foo }\nclass Foo[T]();").is_ok());
+    let err = parse("class Foo(x Int);\nfun f() Unit { Foo }").unwrap_err();
+    assert!(err.to_string().starts_with("The type `Foo` can't be used as a value"));
+    let lexed = lexer::lex("fun f() Unit { Foo }");
+    let (_, errors) = super::parse_all_errors(lexed.tokens());
+    assert_eq!(errors.0[0].span, TextRange::new(15, 18));
+}
+
+#[test]
+fn operator_precedence() {
+    use ast::expr::Expr;
+    use ast::token::Operator;
+
+    use crate::{Assoc, OperatorInfo, OperatorTable, ToSource};
+
+    /// Prints the expression in the function body with explicit parentheses
+    fn group(expr: &Expr) -> String {
+        match expr {
+            Expr::Operation(o) => {
+                format!("({} {} {})", group(&o.lhs), o.operator, group(&o.rhs))
+            }
+            e => e.to_source(),
+        }
+    }
+
+    let mut table = OperatorTable::new();
+    let mut add = |op: &str, precedence, assoc| {
+        table.insert(Operator::new(op), OperatorInfo { precedence, assoc });
+    };
+    add("+", 1, Assoc::Left);
+    add("-", 1, Assoc::Left);
+    add("*", 2, Assoc::Left);
+    add("**", 3, Assoc::Right);
+    add("<>", 1, Assoc::Right);
+
+    let parse = |body: &str| -> Result<String, crate::Error> {
+        let text = format!("fun f() Int {{ {} }}", body);
+        let items = super::parse_with_operators(lexer::lex(&text).tokens(), &table)?;
+        match &items[0].inner {
+            Item::Function(f) => Ok(group(&f.body.as_ref().unwrap().exprs[0])),
+            _ => unreachable!(),
+        }
+    };
+
+    assert_eq!(parse("a ** b ** c").unwrap(), "(a ** (b ** c))");
+    assert_eq!(parse("a - b - c").unwrap(), "((a - b) - c)");
+    assert_eq!(parse("a + b * c ** d").unwrap(), "(a + (b * (c ** d)))");
+    assert_eq!(parse("a ** b * c - d").unwrap(), "(((a ** b) * c) - d)");
+    assert_eq!(parse("a - b + c").unwrap(), "((a - b) + c)");
+
+    let err = parse("a + b <> c").unwrap_err();
+    assert!(err.to_string().starts_with("Evaluation order must be disambiguated"));
+    assert!(parse("a + b % c").is_err());
+    assert!(parse("a % b % c").is_ok());
+
+    assert!(parse("fun f() Int { a + b * c }").is_err());
+}
+
+/// Changing the order of fields in the AST changes the output of `ToBeauty`.
+/// This snapshot makes sure that it doesn't happen by accident.
+#[test]
+fn beauty_field_order() {
+    use crate::ToBeauty;
+
+    let content = read_to_string("./tests/fields.wa").unwrap();
+    let items = parse(&content).unwrap();
+    let actual = items.to_beauty_string();
+    let actual = actual.trim_end();
+
+    let path = "./tests/fields.beauty";
+    if std::path::Path::new(path).exists() {
+        let expected = read_to_string(path).unwrap();
+        let expected = expected.trim_end();
+        if expected != actual {
+            let changes = difference::Changeset::new(expected, actual, "\n");
+            eprintln!("{}", changes);
+            panic!("{} differences between expected and actual output", changes.distance);
+        }
+    } else {
+        let mut file = File::create(path).unwrap();
+        file.write_all(actual.as_bytes()).unwrap();
+        file.write_all(b"\n").unwrap();
+        file.flush().unwrap();
+    }
+}
+
+#[test]
+fn cfg_attributes() {
+    use std::collections::HashSet;
+
+    use crate::{ToBeauty, ToSource};
+
+    let text = "## Only in tests\n@cfg(test) fun f() Unit {}\n\n\
+                fun g() Unit {}\n\n\
+                impl Foo { @cfg(test) @cfg(slow) fun h() Unit {} }";
+    let names = |flags: Option<&[&str]>| {
+        let tokens = lexer::lex(text);
+        let items = match flags {
+            Some(flags) => {
+                let flags: HashSet<&str> = flags.iter().copied().collect();
+                super::parse_with_cfg(tokens.tokens(), &flags).unwrap()
+            }
+            None => super::parse(tokens.tokens()).unwrap(),
+        };
+        let mut names = Vec::new();
+        for item in &items {
+            match &item.inner {
+                Item::Function(f) => names.push(format!("{:?}", f.name.inner)),
+                Item::Impl(i) => names.push(format!("impl with {} items", i.items.len())),
+                _ => panic!("unexpected item"),
+            }
+        }
+        names
+    };
+    assert_eq!(names(None), ["Ident f", "Ident g", "impl with 1 items"]);
+    let all = ["Ident f", "Ident g", "impl with 1 items"];
+    assert_eq!(names(Some(&["test", "slow"])), all);
+    assert_eq!(names(Some(&["test"])), ["Ident f", "Ident g", "impl with 0 items"]);
+    assert_eq!(names(Some(&[])), ["Ident g", "impl with 0 items"]);
+
+    let items = parse("@cfg(test) fun f() Unit {}").unwrap();
+    assert_eq!(items[0].span, TextRange::new(0, 26));
+    let err = parse("@test fun f() Unit {}").unwrap_err();
+    assert_eq!(err.to_string(), "Unknown attribute `@test`");
+    let err = parse("@cfg(test)").unwrap_err();
+    assert_eq!(err.to_string(), "Expected item after attribute");
+    assert!(parse("@cfg fun f() Unit {}").is_err());
+
+    // attributes are kept in the AST and can be evaluated later
+    let mut items = parse(text).unwrap();
+    let source: String = items.iter().map(|item| item.to_source() + "\n").collect();
+    assert!(source.contains("## Only in tests\n@cfg(test)\nfun f() Unit {"));
+    assert!(source.contains("    @cfg(test)\n    @cfg(slow)\n    fun h() Unit {"));
+    let reparsed = parse(&source).unwrap();
+    assert_eq!(reparsed.to_beauty_string(), items.to_beauty_string());
+    assert!(items.to_beauty_string().contains("attrs: Cfg: Ident: test\n"));
+
+    super::filter_cfg(&mut items, &HashSet::from(["slow"]));
+    assert_eq!(items.len(), 2);
+}
+
+#[test]
+fn report_all_errors() {
+    let text = "fun a() Unit {\n    let = 1\n}\n\n\
+                fun b() Unit { 1 }\n\n\
+                fun c() Unit {\n    (1, .x = 2)\n}";
+    let (items, errors) = super::parse_all_errors(lexer::lex(text).tokens());
+    assert_eq!(items.len(), 3);
+    assert_eq!(errors.0.len(), 2);
+    // the errors point to the token where parsing failed, the error items cover
+    // the skipped tokens
+    assert_eq!(&text[errors.0[0].span], "=");
+    assert_eq!(&text[items[0].span], "fun a() Unit {\n    let = 1\n}");
+    assert_eq!(&text[items[2].span], "fun c() Unit {\n    (1, .x = 2)\n}");
+    assert_eq!(
+        errors.to_string(),
+        "23..24: Expected variable name, got `=`\n\
+         50..82: Named argument not allowed in tuple"
+    );
+}
+
+#[test]
+fn recursive_type_aliases() {
+    let err = parse("type T = T;").unwrap_err();
+    assert_eq!(err.to_string(), "Type alias `T` is recursive: T -> T");
+
+    let err = parse("type A = List[B];\nclass X();\ntype B = Pair[Int, A];").unwrap_err();
+    assert_eq!(err.to_string(), "Type alias `A` is recursive: A -> B -> A");
+
+    assert!(parse("type A = B; type B = List[Int]; type C[C] = List[C];").is_ok());
+
+    let text = "type Id = Int;\ntype A = A;";
+    let (_, errors) = super::parse_all_errors(lexer::lex(text).tokens());
+    assert_eq!(errors.to_string(), "15..26: Type alias `A` is recursive: A -> A");
+
+    // aliases in an impl are checked as well
+    let err = parse("impl Foo { type A = B; type B = A; }").unwrap_err();
+    assert_eq!(err.to_string(), "Type alias `A` is recursive: A -> B -> A");
+}
+
+#[test]
+fn try_operator() {
+    let err = parse("fun f() Unit { a + ? }").unwrap_err();
+    assert_eq!(err.to_string(), "Expected operand, got `?`");
+    let err = parse("fun f() Unit { 1? }").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`?` can only be applied to a function call or name, not Literal"
+    );
+}
+
+#[test]
+fn if_else() {
+    use crate::ToBeauty;
+
+    let items = parse("fun f() Unit { let a = if x { 1 } else if y { 2 } else { 3 }; }");
+    let items = items.unwrap();
+    let if_expr = items[0].to_beauty_string();
+    assert_eq!(if_expr.matches("IfExpr").count(), 2, "{}", if_expr);
+
+    let err = parse("fun f() Unit { if x { 1 } else 2 }").unwrap_err();
+    assert_eq!(err.to_string(), "Expected `if` or block, got Int(2)");
+    // the block is parsed as the condition
+    let err = parse("fun f() Unit { if { 1 } }").unwrap_err();
+    assert_eq!(err.to_string(), "Expected block, got `}`");
+
+    // `if` expressions are operands
+    let beauty = |text: &str| {
+        let items = parse(&format!("fun f() Unit {{ {} }}", text)).unwrap();
+        let beauty = items[0].to_beauty_string();
+        beauty.split_whitespace().collect::<Vec<_>>().join(" ")
+    };
+    let assignment = beauty("x = if a {1} else {2};");
+    assert!(assignment.contains("Assignment lhs: Invokable: name: Ident: x rhs IfExpr"));
+    let operation = beauty("1 + if a {1} else {2}");
+    assert!(operation.contains("Operation operator: Operator: + lhs: Int: 1 rhs IfExpr"));
+    let call = beauty("if a {1} else {2}.foo()");
+    assert!(call.contains("ParenCall receiver MemberCall receiver IfExpr"), "{}", call);
+}
+
+#[test]
+fn generic_bounds() {
+    use crate::ToSource;
+
+    let items = parse("fun f[T Clone + Show, U Eq[T] Hash, V]() Unit {}").unwrap();
+    let generics = match &items[0].inner {
+        Item::Function(f) => &f.generics.inner,
+        _ => unreachable!(),
+    };
+    let bounds: Vec<Vec<String>> = generics
+        .iter()
+        .map(|g| g.bounds.iter().map(|b| b.ty.to_source()).collect())
+        .collect();
+    assert_eq!(bounds, vec![vec!["Clone", "Show"], vec!["Eq[T]", "Hash"], vec![]]);
+    assert_eq!(items.to_source(), "fun f[T Clone + Show, U Eq[T] + Hash, V]() Unit {}");
+
+    let items = parse("impl[T Clone Debug] Foo[T] {}").unwrap();
+    match &items[0].inner {
+        Item::Impl(i) => {
+            let bounds = i.generics[0].bounds.iter();
+            let names: Vec<_> = bounds.map(|b| b.ty.name.get()).collect();
+            assert_eq!(names, ["Clone", "Debug"]);
+        }
+        _ => unreachable!(),
+    }
+
+    let err = parse("impl[T Clone +] Foo for Bar {}").unwrap_err();
+    assert_eq!(err.to_string(), "Expected type bound, got `]`");
+}
+
+#[test]
+fn trailing_commas() {
+    use crate::ToSource;
+
+    let items = parse("fun f() Unit { f(a,); f(a, b,); (a, b,) }").unwrap();
+    assert_eq!(
+        items.to_source(),
+        "fun f() Unit {\n    f(a,);\n    f(a, b,);\n    (a, b,)\n}"
+    );
+
+    let err = parse("fun f() Unit { f(,) }").unwrap_err();
+    assert_eq!(err.to_string(), "Expected `)`, got `,`");
+    let err = parse("fun f() Unit { (a,,) }").unwrap_err();
+    assert_eq!(err.to_string(), "Expected `)`, got `,`");
+}
+
+#[test]
+fn return_break_continue() {
+    assert!(parse("fun f() Unit { for x in xs { if x { break } } }").is_ok());
+    assert!(parse("fun f() Unit { while a { g(|x| { return x }) } }").is_ok());
+
+    let err = parse("fun f() Unit { if a { break } }").unwrap_err();
+    assert_eq!(err.to_string(), "`break` can only be used in a loop");
+    let err = parse("fun f() Unit { while a { g(|x| { continue }) } }").unwrap_err();
+    assert_eq!(err.to_string(), "`continue` can only be used in a loop");
+    let err = parse("fun f(a Int = return 1) Unit {}").unwrap_err();
+    assert_eq!(err.to_string(), "`return` can only be used in a function body");
+    // a loop doesn't make `return` valid
+    let err = parse("fun f(a Int = while x { return 1 }) Unit {}").unwrap_err();
+    assert_eq!(err.to_string(), "`return` can only be used in a function body");
+    assert!(parse("fun f(a Int = while x { break }) Unit {}").is_ok());
+}
+
+#[test]
+fn compound_assignment() {
+    let err = parse("fun f() Unit { x += y * 2 }").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Evaluation order must be disambiguated with a block, e.g. `a + {b * c}`"
+    );
+    let err = parse("fun f() Unit { 1 += 2 }").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "This is not a place expression, so it can't be assigned to: Literal"
+    );
+}
+
+#[test]
+fn not_operator() {
+    let err = parse("fun f() Bool { a not b }").unwrap_err();
+    assert_eq!(err.to_string(), "Expected operator, got `not`");
+    let err = parse("fun f() Bool { not + }").unwrap_err();
+    assert!(err.to_string().starts_with("Operators are not allowed here: Operator +"));
+    let err = parse("fun f() Bool { not }").unwrap_err();
+    assert_eq!(err.to_string(), "Expected operand");
+}
+
+#[test]
+fn recover_after_syntax_error() {
+    let text = "fun a() Unit { foo(1 +) }\nfun b() Unit { bar() }";
+    let (items, errors) = super::parse_with_recovery(lexer::lex(text).tokens());
+    assert_eq!(items.len(), 2);
+    assert!(matches!(items[0].inner, Item::Error));
+    match &items[1].inner {
+        Item::Function(f) => {
+            assert_eq!(format!("{:?}", f.name.inner), "Ident b")
+        }
+        _ => panic!("expected function"),
+    }
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn many_combinators() {
+    use ast::item::TypeBound;
+    use ast::token::{Punctuation, Token};
+
+    use crate::helpers::{many0, many1};
+    use crate::{Lexer, Parse};
+
+    let lexed = lexer::lex("Clone + Show Eq[T] {");
+    let mut lexer = Lexer::from_tokens(lexed.tokens());
+    let bounds = many0(TypeBound::parse)(&mut lexer).unwrap().unwrap();
+    let names: Vec<_> = bounds.iter().map(|b| b.ty.name.get()).collect();
+    assert_eq!(names, ["Clone", "Show", "Eq"]);
+    assert_eq!(bounds.span, TextRange::new(0, 18));
+    assert_eq!(*lexer.peek(), Token::Punct(Punctuation::OpenBrace));
+
+    let bounds = many0(TypeBound::parse)(&mut lexer).unwrap().unwrap();
+    assert!(bounds.is_empty());
+    let err = many1(TypeBound::parse, "type bound")(&mut lexer).unwrap_err();
+    assert_eq!(err.to_string(), "Expected type bound, got `{`");
+
+    let lexed = lexer::lex("Clone +");
+    let mut lexer = Lexer::from_tokens(lexed.tokens());
+    assert!(many1(TypeBound::parse, "type bound")(&mut lexer).is_err());
+}
+
+#[test]
+fn index_expressions() {
+    // `xs[]` has empty type arguments
+    let err = parse("fun f() Unit { f()[] }").unwrap_err();
+    assert_eq!(err.to_string(), "Expected at least one index");
+}
+
+#[test]
+fn array_literals() {
+    use crate::ToBeauty;
+
+    // brackets after an operand are still an index or type arguments
+    let items = parse("fun f() Unit { xs[1]; f[T]() }").unwrap();
+    let beauty = items[0].to_beauty_string();
+    assert!(beauty.contains("IndexExpr") && beauty.contains("generics"));
+    assert!(!beauty.contains("ArrayLit"));
+}
+
+#[test]
+fn expected_options() {
+    use ast::expr::Literal;
+    use ast::token::{Ident, UpperIdent};
+
+    use crate::helpers::{expecting, map, or2, or3};
+    use crate::{Error, Lexer, Parse};
+
+    let lexed = lexer::lex("  ; x");
+    let mut lexer = Lexer::from_tokens(lexed.tokens());
+    let err = or3(
+        expecting("identifier", map(Ident::parse, |_| ())),
+        expecting("type", map(UpperIdent::parse, |_| ())),
+        expecting("literal", map(Literal::parse, |_| ())),
+    )(&mut lexer)
+    .unwrap_err();
+    assert_eq!(err.to_string(), "Expected identifier, type or literal, got `;`");
+    match err {
+        Error::ExpectedOneOf { options, at, .. } => {
+            assert_eq!(options, ["identifier", "type", "literal"]);
+            assert_eq!(at, TextRange::new(2, 3));
+        }
+        _ => panic!("expected `ExpectedOneOf`"),
+    }
+    // the lexer isn't advanced
+    assert_eq!(lexer.len(), 3);
+
+    // the options aren't merged if the first branch consumed tokens
+    let lexed = lexer::lex("Foo[ x");
+    let mut lexer = Lexer::from_tokens(lexed.tokens());
+    let err = or2(
+        map(ast::item::NamedType::parse, |_| ()),
+        expecting("identifier", map(Ident::parse, |_| ())),
+    )(&mut lexer)
+    .unwrap_err();
+    assert_eq!(err.to_string(), "Expected `]`, got i`x`");
+
+    let err = parse("fun f() Unit { if a {} else }");
+    assert_eq!(err.unwrap_err().to_string(), "Expected `if` or block, got `}`");
+
+    let err = parse("fun f() Unit { let x = ; }");
+    match err.unwrap_err() {
+        Error::ExpectedOneOf { options, at, .. } => {
+            let expected = ["`not`", "literal", "name", "lambda", "block", "`if`"];
+            assert_eq!(options[..6], expected);
+            assert_eq!(options[6..], ["`(`", "`[`", "`match`"]);
+            assert_eq!(at, TextRange::new(23, 24));
+        }
+        _ => panic!("expected `ExpectedOneOf`"),
+    }
+}
+
+#[test]
+fn labeled_loops() {
+    let err = parse("fun f() Unit { 'a: while x { continue 'b } }").unwrap_err();
+    assert_eq!(err.to_string(), "Use of undeclared label `'b`");
+    // the label is only in scope within its loop
+    let err = parse("fun f() Unit { while x { 'a: while y {}; break 'a } }").unwrap_err();
+    assert_eq!(err.to_string(), "Use of undeclared label `'a`");
+    // labels aren't visible in lambdas
+    let err = parse("fun f() Unit { 'a: while x { |y| { while y { break 'a } } } }");
+    assert_eq!(err.unwrap_err().to_string(), "Use of undeclared label `'a`");
+    let source = "fun f() Unit { 'a: while x { |y| { 'a: while y { break 'a } } } }";
+    assert!(parse(source).is_ok());
+
+    let source = "fun f() Unit {\n  while x { continue 'a }\n}";
+    let (_, errors) = super::parse_all_errors(lexer::lex(source).tokens());
+    assert_eq!(errors.0[0].span, TextRange::new(36, 38));
+    let err = parse("fun f() Unit { 'a: x }").unwrap_err();
+    assert_eq!(err.to_string(), "Expected `while` or `for`, got i`x`");
+}
+
+#[test]
+fn beauty_numbers() {
+    use ast::token::{NumberLiteral, NumberSuffix};
+
+    use crate::ToBeauty;
+
+    let cases = [
+        (NumberLiteral::Int(-3, None), "Int: -3"),
+        (NumberLiteral::UInt(u64::MAX, None), "UInt: 18446744073709551615"),
+        (NumberLiteral::UInt(7, Some(NumberSuffix::U8)), "UInt: 7u8"),
+        (NumberLiteral::Float(0.5, Some(NumberSuffix::F32)), "Float: 0.5f32"),
+        (NumberLiteral::Float(-2.25, None), "Float: -2.25"),
+    ];
+    for (number, expected) in &cases {
+        assert_eq!(number.to_beauty_string(), *expected);
+    }
+}
+
+#[test]
+fn visit_invokables() {
+    use ast::expr::{Expr, Invokable};
+    use ast::visit::{walk_expr, walk_invokable, Visit};
+    use ast::Spanned;
+
+    #[derive(Default)]
+    struct Counter {
+        invokables: usize,
+        exprs: usize,
+    }
+
+    impl<'ast> Visit<'ast> for Counter {
+        fn visit_expr(&mut self, expr: &'ast Spanned<Expr>) {
+            self.exprs += 1;
+            walk_expr(self, expr);
+        }
+
+        fn visit_invokable(&mut self, invokable: &'ast Invokable) {
+            self.invokables += 1;
+            walk_invokable(self, invokable);
+        }
+    }
+
+    let source = "fun f(x Int = g()) Int {
+        let y = x match { _: |a| a + b };
+        for i in xs { y.push(i UInt) };
+        c
+    }";
+    let items = parse(source).unwrap();
+    let mut counter = Counter::default();
+    counter.visit_item(&items[0]);
+    // `g`, `x`, `a`, `b`, `i`, `xs`, `y`, `push`, `i` and `c`
+    assert_eq!(counter.invokables, 10);
+    assert_eq!(counter.exprs, 17);
+}
+
+#[test]
+fn match_arms() {
+    let err = parse("fun f() Unit { x match { (let a, (_, let a)): a } }").unwrap_err();
+    assert_eq!(err.to_string(), "`a` is bound more than once in the same pattern");
+    // the same name can be bound in different arms
+    parse("fun f() Unit { x match { (let a, 1): a, (1, let a): a } }").unwrap();
+    let err = parse("fun f() Unit { x match { let a a } }").unwrap_err();
+    assert_eq!(err.to_string(), "Expected `:`, got i`a`");
+}
+
+#[test]
+fn duplicate_names() {
+    let cases = [
+        ("fun f(x Int, x Int) Int { x }", "Duplicate argument `x`"),
+        ("fun f[T, U, T]() Unit {}", "Duplicate generic parameter `T`"),
+        ("fun f() Unit { |a, b, a| a }", "Duplicate argument `a`"),
+        ("class C(x Int, y Int, x Float);", "Duplicate field `x`"),
+        ("class C[T, T]();", "Duplicate generic parameter `T`"),
+        ("enum E { a, b, a }", "Duplicate variant `a`"),
+        ("enum E { a(x Int, x Int) }", "Duplicate field `x`"),
+        ("enum E[T, T] { a }", "Duplicate generic parameter `T`"),
+        ("impl[T, T] Foo[T] {}", "Duplicate generic parameter `T`"),
+        ("type A[T, T] = B;", "Duplicate generic parameter `T`"),
+    ];
+    for &(source, expected) in &cases {
+        let err = parse(source).unwrap_err();
+        assert_eq!(err.to_string(), expected, "{}", source);
+    }
+
+    // names only have to be unique within the same list
+    parse(
+        "class C(x Int); enum E { a(x Int), b(x Int) }
+         fun f[T](x T) Unit { |x| x }",
+    )
+    .unwrap();
+}
+
+#[test]
+fn qualified_types() {
+    let items = parse("fun f(x a.b.Type[X]) std.Map[a.K, V] { x }").unwrap();
+    match &items[0].inner {
+        Item::Function(f) => {
+            let ty = f.args[0].ty.as_ref().unwrap();
+            assert_eq!(ty.span, TextRange::new(8, 19));
+        }
+        _ => panic!("expected a function"),
+    }
+
+    // a qualified name can't refer to a type alias in the same module
+    parse("type A = a.A;").unwrap();
+    let err = parse("fun f(x a.b) Unit {}").unwrap_err();
+    assert_eq!(err.to_string(), "Expected `)`, got i`a`");
+}
+
+#[test]
+fn where_clauses() {
+    let items = parse("fun f[T Eq](x T) T where T: Show + Clone { x }").unwrap();
+    match &items[0].inner {
+        Item::Function(f) => {
+            let bounds: Vec<_> = f
+                .bounds_of(&f.generics[0].name)
+                .map(|b| b.ty.name.to_string())
+                .collect();
+            assert_eq!(bounds, ["Eq", "Show", "Clone"]);
+        }
+        _ => panic!("expected a function"),
+    }
+
+    // the clause is parsed before the terminating semicolon
+    let err = parse("fun f[T]() Unit where T: Show;").unwrap_err();
+    assert_eq!(err.to_string(), "Function doesn't have a body");
+    let err = parse("fun f[T](x T) Unit where U: Show { x }").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`U` in the `where` clause is not a generic parameter of this function"
+    );
+    let err = parse("fun f[T](x T) Unit where T { x }").unwrap_err();
+    assert!(err.to_string().contains("`:`"), "{}", err);
+    let err = parse("fun f[T](x T) Unit where T: { x }").unwrap_err();
+    assert!(err.to_string().contains("type bound"), "{}", err);
+}
+
+#[test]
+fn declaration_as_value() {
+    let err = parse("fun f() Unit { let x = 1 }").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "A declaration can't be the value of a block. Add a `;` after it"
+    );
+    assert!(parse("fun f() Int { var x = 1; { let y = x } }").is_err());
+
+    parse("fun f() Int { let x = 1; x }").unwrap();
+    parse("fun f() Unit { let x = 1; }").unwrap();
+}
@@ -0,0 +1,738 @@
+use ast::expr::*;
+use ast::item::*;
+use ast::pattern::*;
+use ast::token::{Ident, NumberLiteral, StringLiteral};
+use ast::Spanned;
+
+/// Converts the AST back to source code. Unlike [`ToBeauty`](crate::ToBeauty),
+/// the output is valid Yep code that parses to the same AST (except for spans).
+///
+/// The output is formatted canonically: Blocks, impls, enums and `match` arms
+/// span multiple lines with 4 spaces of indentation per level, operators are
+/// surrounded by single spaces, and lists have no trailing commas, except for
+/// calls and tuples that had one. Comments other than doc comments aren't part
+/// of the AST, so they are lost.
+pub trait ToSource {
+    /// Appends the source code to `buf`. `indent` is the indentation level of
+    /// the current line; it is used for lines that start within this node.
+    fn write_source(&self, buf: &mut String, indent: usize);
+
+    fn to_source(&self) -> String {
+        let mut buf = String::new();
+        self.write_source(&mut buf, 0);
+        buf
+    }
+}
+
+impl<T: ToSource> ToSource for Spanned<T> {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        self.inner.write_source(buf, indent)
+    }
+}
+
+impl<T: ToSource + ?Sized> ToSource for Box<T> {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        (**self).write_source(buf, indent)
+    }
+}
+
+/// Items are separated by empty lines
+impl ToSource for [Spanned<Item>] {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        for (i, item) in self.iter().enumerate() {
+            if i > 0 {
+                buf.push('\n');
+                newline(buf, indent);
+            }
+            item.write_source(buf, indent);
+        }
+    }
+}
+
+fn newline(buf: &mut String, indent: usize) {
+    buf.push('\n');
+    buf.extend((0..indent * 4).map(|_| ' '));
+}
+
+/// Writes the elements separated by `, `
+fn write_list<T: ToSource>(items: &[T], buf: &mut String, indent: usize) {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            buf.push_str(", ");
+        }
+        item.write_source(buf, indent);
+    }
+}
+
+/// Writes the elements on separate lines, separated by `,`
+fn write_lines<T: ToSource>(items: &[T], buf: &mut String, indent: usize) {
+    if items.is_empty() {
+        buf.push_str("{}");
+        return;
+    }
+    buf.push('{');
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        newline(buf, indent + 1);
+        item.write_source(buf, indent + 1);
+    }
+    newline(buf, indent);
+    buf.push('}');
+}
+
+/// Writes `[T, U]`, or nothing if the list is empty
+fn write_generics<T: ToSource>(generics: &[T], buf: &mut String, indent: usize) {
+    if !generics.is_empty() {
+        buf.push('[');
+        write_list(generics, buf, indent);
+        buf.push(']');
+    }
+}
+
+fn write_label(label: &Ident, buf: &mut String) {
+    buf.push('\'');
+    buf.push_str(label.get());
+}
+
+fn write_docs(docs: &Option<Vec<ast::TinyString>>, buf: &mut String, indent: usize) {
+    for doc in docs.iter().flatten() {
+        if doc.is_empty() {
+            buf.push_str("##");
+        } else {
+            buf.push_str("## ");
+            buf.push_str(doc);
+        }
+        newline(buf, indent);
+    }
+}
+
+fn write_attrs(attrs: &[Spanned<Attribute>], buf: &mut String, indent: usize) {
+    for attr in attrs {
+        match &attr.inner {
+            Attribute::Cfg(flag) => {
+                buf.push_str("@cfg(");
+                buf.push_str(flag.get());
+                buf.push(')');
+            }
+        }
+        newline(buf, indent);
+    }
+}
+
+impl ToSource for Item {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        match self {
+            Item::Function(f) => f.write_source(buf, indent),
+            Item::Class(c) => c.write_source(buf, indent),
+            Item::Enum(e) => e.write_source(buf, indent),
+            Item::Impl(i) => i.write_source(buf, indent),
+            Item::Use(u) => u.write_source(buf, indent),
+            Item::TypeAlias(t) => t.write_source(buf, indent),
+            // the tokens of an error item are unknown
+            Item::Error => buf.push_str("# error"),
+        }
+    }
+}
+
+impl ToSource for Function {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        write_docs(&self.docs, buf, indent);
+        write_attrs(&self.attrs, buf, indent);
+        buf.push_str("fun ");
+        self.name.write_source(buf, indent);
+        write_generics(&self.generics, buf, indent);
+        buf.push('(');
+        write_list(&self.args, buf, indent);
+        buf.push(')');
+        if let Some(ty) = &self.return_ty {
+            buf.push(' ');
+            ty.write_source(buf, indent);
+        }
+        if let Some(where_clause) = &self.where_clause {
+            buf.push_str(" where ");
+            write_list(&where_clause.bounds, buf, indent);
+        }
+        match &self.body {
+            Some(body) => {
+                buf.push(' ');
+                body.write_source(buf, indent);
+            }
+            None => buf.push(';'),
+        }
+    }
+}
+
+impl ToSource for FunArgument {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        buf.push_str(self.name.get());
+        if let Some(ty) = &self.ty {
+            buf.push(' ');
+            ty.write_source(buf, indent);
+        }
+        if let Some(default) = &self.default {
+            buf.push_str(" = ");
+            default.write_source(buf, indent);
+        }
+    }
+}
+
+impl ToSource for Class {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        write_docs(&self.docs, buf, indent);
+        write_attrs(&self.attrs, buf, indent);
+        buf.push_str("class ");
+        buf.push_str(self.name.get());
+        write_generics(&self.generics, buf, indent);
+        buf.push('(');
+        write_list(&self.fields, buf, indent);
+        buf.push_str(");");
+    }
+}
+
+impl ToSource for ClassField {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        buf.push_str(self.name.get());
+        if let Some(ty) = &self.ty {
+            buf.push(' ');
+            ty.write_source(buf, indent);
+        }
+        if let Some(default) = &self.default {
+            buf.push_str(" = ");
+            default.write_source(buf, indent);
+        }
+    }
+}
+
+impl ToSource for Enum {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        write_docs(&self.docs, buf, indent);
+        write_attrs(&self.attrs, buf, indent);
+        buf.push_str("enum ");
+        buf.push_str(self.name.get());
+        write_generics(&self.generics, buf, indent);
+        buf.push(' ');
+        write_lines(&self.variants, buf, indent);
+    }
+}
+
+impl ToSource for EnumVariant {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        buf.push_str(self.name.get());
+        if let Some(arguments) = &self.arguments {
+            buf.push('(');
+            write_list(arguments, buf, indent);
+            buf.push(')');
+        }
+    }
+}
+
+impl ToSource for Impl {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        write_docs(&self.docs, buf, indent);
+        write_attrs(&self.attrs, buf, indent);
+        buf.push_str("impl");
+        write_generics(&self.generics, buf, indent);
+        buf.push(' ');
+        if let Some(r#trait) = &self.r#trait {
+            r#trait.write_source(buf, indent);
+            buf.push_str(" for ");
+        }
+        self.ty.write_source(buf, indent);
+        if self.items.is_empty() {
+            buf.push_str(" {}");
+        } else {
+            buf.push_str(" {");
+            newline(buf, indent + 1);
+            self.items.write_source(buf, indent + 1);
+            newline(buf, indent);
+            buf.push('}');
+        }
+    }
+}
+
+impl ToSource for Use {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        write_docs(&self.docs, buf, indent);
+        write_attrs(&self.attrs, buf, indent);
+        buf.push_str("use ");
+        for (i, name) in self.path.iter().enumerate() {
+            if i > 0 {
+                buf.push('.');
+            }
+            name.write_source(buf, indent);
+        }
+        if self.wildcard.is_some() {
+            buf.push_str("._");
+        }
+        buf.push(';');
+    }
+}
+
+impl ToSource for TypeAlias {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        write_docs(&self.docs, buf, indent);
+        write_attrs(&self.attrs, buf, indent);
+        buf.push_str("type ");
+        buf.push_str(self.name.get());
+        write_generics(&self.generics, buf, indent);
+        buf.push_str(" = ");
+        self.ty.write_source(buf, indent);
+        buf.push(';');
+    }
+}
+
+impl ToSource for Name {
+    fn write_source(&self, buf: &mut String, _: usize) {
+        buf.push_str(match self {
+            Name::Operator(o) => o.get(),
+            Name::Ident(i) => i.get(),
+            Name::Type(t) => t.get(),
+        })
+    }
+}
+
+impl ToSource for GenericParam {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        buf.push_str(self.name.get());
+        for (i, bound) in self.bounds.iter().enumerate() {
+            buf.push_str(if i == 0 { " " } else { " + " });
+            bound.ty.write_source(buf, indent);
+        }
+    }
+}
+
+impl ToSource for WhereBound {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        buf.push_str(self.param.get());
+        buf.push(':');
+        for (i, bound) in self.bounds.iter().enumerate() {
+            buf.push_str(if i == 0 { " " } else { " + " });
+            bound.ty.write_source(buf, indent);
+        }
+    }
+}
+
+impl ToSource for NamedType {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        for module in self.path.iter() {
+            buf.push_str(module.get());
+            buf.push('.');
+        }
+        buf.push_str(self.name.get());
+        write_generics(&self.args, buf, indent);
+    }
+}
+
+impl ToSource for TypeArgument {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        match self {
+            TypeArgument::Type(t) => t.write_source(buf, indent),
+            TypeArgument::Wildcard => buf.push('_'),
+        }
+    }
+}
+
+impl ToSource for Expr {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        match self {
+            Expr::Invokable(e) => e.write_source(buf, indent),
+            Expr::Literal(e) => e.write_source(buf, indent),
+            Expr::ParenCall(e) => e.write_source(buf, indent),
+            Expr::Index(e) => e.write_source(buf, indent),
+            Expr::MemberCall(e) => e.write_source(buf, indent),
+            Expr::Operation(e) => e.write_source(buf, indent),
+            Expr::ShortcircuitingOp(e) => e.write_source(buf, indent),
+            Expr::UnaryOp(e) => e.write_source(buf, indent),
+            Expr::Assignment(e) => e.write_source(buf, indent),
+            Expr::TypeAscription(e) => e.write_source(buf, indent),
+            Expr::Statement(e) => {
+                e.write_source(buf, indent);
+                buf.push(';');
+            }
+            Expr::Try(e) => {
+                e.write_source(buf, indent);
+                buf.push('?');
+            }
+            Expr::Lambda(e) => e.write_source(buf, indent),
+            Expr::Block(e) => e.write_source(buf, indent),
+            Expr::Tuple(e) => e.write_source(buf, indent),
+            Expr::ArrayLit(e) => e.write_source(buf, indent),
+            Expr::Empty(_) => {}
+            Expr::Declaration(e) => e.write_source(buf, indent),
+            Expr::Match(e) => e.write_source(buf, indent),
+            Expr::While(e) => e.write_source(buf, indent),
+            Expr::For(e) => e.write_source(buf, indent),
+            Expr::IfExpr(e) => e.write_source(buf, indent),
+            Expr::Return(e) => {
+                buf.push_str("return");
+                if let Some(e) = e {
+                    buf.push(' ');
+                    e.write_source(buf, indent);
+                }
+            }
+            Expr::Break(label) => {
+                buf.push_str("break");
+                if let Some(label) = label {
+                    buf.push(' ');
+                    write_label(label, buf);
+                }
+            }
+            Expr::Continue(label) => {
+                buf.push_str("continue");
+                if let Some(label) = label {
+                    buf.push(' ');
+                    write_label(label, buf);
+                }
+            }
+        }
+    }
+}
+
+impl ToSource for Invokable {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        self.name.write_source(buf, indent);
+        write_generics(&self.generics, buf, indent);
+    }
+}
+
+impl ToSource for Literal {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        match self {
+            Literal::NumberLit(n) => n.write_source(buf, indent),
+            Literal::StringLit(s) => s.write_source(buf, indent),
+            Literal::CharLit(c) => buf.push_str(&format!("{:?}", c)),
+        }
+    }
+}
+
+impl ToSource for NumberLiteral {
+    fn write_source(&self, buf: &mut String, _: usize) {
+        let n = match self {
+            NumberLiteral::Int(i, _) => i.to_string(),
+            // unsigned integers are written with a leading `+`
+            NumberLiteral::UInt(u, _) => format!("+{}", u),
+            // `Debug` always includes a `.` or an exponent, unlike `Display`
+            NumberLiteral::Float(f, _) => format!("{:?}", f),
+        };
+        buf.push_str(&n);
+        if let Some(suffix) = self.suffix() {
+            buf.push_str(suffix.as_str());
+        }
+    }
+}
+
+impl ToSource for StringLiteral {
+    fn write_source(&self, buf: &mut String, _: usize) {
+        let raw = self.raw();
+        if self.is_raw() {
+            let mut hashes = String::new();
+            while raw.contains(&format!("\"{}", hashes)) {
+                hashes.push('#');
+            }
+            buf.push_str(&format!("r{0}\"{1}\"{0}", hashes, raw));
+        } else if has_unescaped_quote(raw) {
+            buf.push_str(&format!("\"\"\"{}\"\"\"", raw));
+        } else {
+            buf.push_str(&format!("\"{}\"", raw));
+        }
+    }
+}
+
+/// Multi-line strings (`"""..."""`) can contain quotes that aren't escaped
+fn has_unescaped_quote(raw: &str) -> bool {
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return true,
+            '\\' => {
+                chars.next();
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+impl ToSource for ParenCall {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        self.receiver.write_source(buf, indent);
+        buf.push('(');
+        if let Some(args) = &self.args {
+            write_list(args, buf, indent);
+        }
+        if self.trailing_comma {
+            buf.push(',');
+        }
+        buf.push(')');
+    }
+}
+
+impl ToSource for IndexExpr {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        self.receiver.write_source(buf, indent);
+        buf.push('[');
+        write_list(&self.indices, buf, indent);
+        buf.push(']');
+    }
+}
+
+impl ToSource for ArrayLit {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        buf.push('[');
+        write_list(&self.elements, buf, indent);
+        buf.push(']');
+    }
+}
+
+impl ToSource for MemberCall {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        self.receiver.write_source(buf, indent);
+        buf.push('.');
+        self.member.write_source(buf, indent);
+    }
+}
+
+impl ToSource for Operation {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        self.lhs.write_source(buf, indent);
+        buf.push(' ');
+        buf.push_str(self.operator.get());
+        buf.push(' ');
+        self.rhs.write_source(buf, indent);
+    }
+}
+
+impl ToSource for ScOperation {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        self.lhs.write_source(buf, indent);
+        buf.push_str(match self.operator {
+            ScOperator::And => " and ",
+            ScOperator::Or => " or ",
+        });
+        self.rhs.write_source(buf, indent);
+    }
+}
+
+impl ToSource for UnaryOp {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        buf.push_str(match self.operator {
+            UnaryOperator::Not => "not ",
+        });
+        self.operand.write_source(buf, indent);
+    }
+}
+
+impl ToSource for Assignment {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        self.lhs.write_source(buf, indent);
+        buf.push_str(" = ");
+        self.rhs.write_source(buf, indent);
+    }
+}
+
+impl ToSource for TypeAscription {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        self.expr.write_source(buf, indent);
+        buf.push(' ');
+        self.ty.write_source(buf, indent);
+    }
+}
+
+impl ToSource for Lambda {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        buf.push('|');
+        write_list(&self.args, buf, indent);
+        buf.push_str("| ");
+        self.body.write_source(buf, indent);
+    }
+}
+
+impl ToSource for LambdaArgument {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        buf.push_str(self.name.get());
+        if let Some(ty) = &self.ty {
+            buf.push(' ');
+            ty.write_source(buf, indent);
+        }
+    }
+}
+
+impl ToSource for Block {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        if self.exprs.is_empty() {
+            buf.push_str("{}");
+            return;
+        }
+        buf.push('{');
+        for (i, expr) in self.exprs.iter().enumerate() {
+            if i > 0 {
+                buf.push(';');
+            }
+            newline(buf, indent + 1);
+            expr.write_source(buf, indent + 1);
+        }
+        if self.ends_with_semicolon {
+            buf.push(';');
+        }
+        newline(buf, indent);
+        buf.push('}');
+    }
+}
+
+impl ToSource for Parens {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        buf.push('(');
+        write_list(&self.exprs, buf, indent);
+        if self.trailing_comma {
+            buf.push(',');
+        }
+        buf.push(')');
+    }
+}
+
+impl ToSource for FunCallArgument {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        match self.name.as_deref() {
+            Some(ArgName::Named(name)) => {
+                buf.push_str(name.get());
+                buf.push_str(": ");
+            }
+            Some(ArgName::RecordArg(name)) => {
+                buf.push('.');
+                buf.push_str(name.get());
+                buf.push_str(" = ");
+            }
+            None => {}
+        }
+        self.expr.write_source(buf, indent);
+    }
+}
+
+impl ToSource for Declaration {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        buf.push_str(match self.decl_kind {
+            DeclKind::Let => "let ",
+            DeclKind::Var => "var ",
+        });
+        buf.push_str(self.name.get());
+        buf.push_str(" = ");
+        self.value.write_source(buf, indent);
+    }
+}
+
+impl ToSource for Match {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        self.expr.write_source(buf, indent);
+        buf.push_str(" match ");
+        write_lines(&self.match_arms, buf, indent);
+    }
+}
+
+impl ToSource for MatchArm {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        self.pattern.write_source(buf, indent);
+        buf.push_str(": ");
+        self.expr.write_source(buf, indent);
+    }
+}
+
+impl ToSource for IfExpr {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        buf.push_str("if ");
+        self.condition.write_source(buf, indent);
+        buf.push(' ');
+        self.then_branch.write_source(buf, indent);
+        if let Some(else_branch) = &self.else_branch {
+            buf.push_str(" else ");
+            else_branch.write_source(buf, indent);
+        }
+    }
+}
+
+impl ToSource for While {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        if let Some(label) = &self.label {
+            write_label(label, buf);
+            buf.push_str(": ");
+        }
+        buf.push_str("while ");
+        self.condition.write_source(buf, indent);
+        buf.push(' ');
+        self.body.write_source(buf, indent);
+    }
+}
+
+impl ToSource for For {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        if let Some(label) = &self.label {
+            write_label(label, buf);
+            buf.push_str(": ");
+        }
+        buf.push_str("for ");
+        self.binding.write_source(buf, indent);
+        buf.push_str(" in ");
+        self.iterable.write_source(buf, indent);
+        buf.push(' ');
+        self.body.write_source(buf, indent);
+    }
+}
+
+/// Only wildcards, literals, bindings (`let x`) and tuples can be parsed so far;
+/// the syntax of the other patterns isn't final.
+impl ToSource for Pattern {
+    fn write_source(&self, buf: &mut String, indent: usize) {
+        match self {
+            Pattern::Wildcard => buf.push('_'),
+            Pattern::Binding(name) => {
+                buf.push_str("let ");
+                buf.push_str(name.get());
+            }
+            Pattern::Literal(l) => l.write_source(buf, indent),
+            Pattern::Tuple(patterns) => {
+                buf.push('(');
+                write_list(patterns, buf, indent);
+                buf.push(')');
+            }
+            Pattern::Range(r) => {
+                r.from.write_source(buf, indent);
+                buf.push_str("..=");
+                r.to.write_source(buf, indent);
+            }
+            Pattern::RangeExclusive(r) => {
+                r.from.write_source(buf, indent);
+                buf.push_str("..");
+                r.to.write_source(buf, indent);
+            }
+            Pattern::Class(c) => {
+                buf.push_str(c.name.get());
+                buf.push('(');
+                write_list(&c.fields, buf, indent);
+                buf.push(')');
+            }
+            Pattern::Enum(e) => {
+                buf.push_str(e.name.get());
+                if let Some(field) = &e.field {
+                    buf.push('(');
+                    field.write_source(buf, indent);
+                    buf.push(')');
+                }
+            }
+            Pattern::TypeAscription(t) => t.write_source(buf, indent),
+            Pattern::Or(patterns) => {
+                for (i, p) in patterns.iter().enumerate() {
+                    if i > 0 {
+                        buf.push_str(" | ");
+                    }
+                    p.write_source(buf, indent);
+                }
+            }
+            Pattern::Guard(g) => {
+                g.pattern.write_source(buf, indent);
+                buf.push_str(" if ");
+                g.guard.write_source(buf, indent);
+            }
+        }
+    }
+}
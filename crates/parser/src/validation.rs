@@ -1,8 +1,14 @@
 use ast::expr::*;
-use ast::item::{Class, Enum, Function, Impl, Item, ItemKind, Name, NamedType, Use};
+use std::collections::{HashMap, HashSet};
+
+use ast::item::{
+    Class, Enum, Function, GenericParam, Impl, Item, ItemKind, Name, NamedType, TypeAlias,
+    TypeArgument, Use,
+};
 use ast::pattern::Pattern;
-use ast::token::Operator;
-use ast::Spanned;
+use ast::token::{Ident, UpperIdent};
+use ast::visit::{walk_expr, walk_lambda, Visit};
+use ast::{Spanned, TinyString};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ValidationError {
@@ -12,12 +18,27 @@ pub enum ValidationError {
     #[error("Invalid function call receiver: {0:?}")]
     InvalidCallReceiver(ExprKind),
 
+    #[error("This expression can't be called: {0:?}")]
+    NotCallable(ExprKind),
+
+    #[error("The type `{0}` can't be used as a value. To construct it, write `{0}()`")]
+    TypeInValuePosition(UpperIdent),
+
+    #[error("Invalid index receiver: {0:?}")]
+    InvalidIndexReceiver(ExprKind),
+
+    #[error("Expected at least one index")]
+    MissingIndex,
+
     #[error("Invalid member receiver: {0:?}")]
     InvalidMemberReceiver(ExprKind),
 
     #[error("Invalid operand: {0:?}")]
     InvalidOperand(ExprKind),
 
+    #[error("`?` can only be applied to a function call or name, not {0:?}")]
+    InvalidTryOperand(ExprKind),
+
     #[error("Named argument after unnamed argument")]
     NamedAfterUnnamed,
 
@@ -27,6 +48,9 @@ pub enum ValidationError {
     #[error("Named argument not allowed in tuple")]
     NamedArgInTuple,
 
+    #[error("Field `{0}` is initialized more than once")]
+    DuplicateField(Ident),
+
     #[error("Evaluation order must be disambiguated with a block, e.g. `a + {{b * c}}`")]
     OperationsRequireBlock,
 
@@ -45,14 +69,46 @@ pub enum ValidationError {
     #[error("Argument doesn't specify its type")]
     ExpectedArgType,
 
-    #[error("impl blocks can't contain {}", match .0 {
+    #[error("impl blocks can only contain functions and type aliases, not {}", match .0 {
         ItemKind::Class => "classes",
         ItemKind::Enum => "enums",
         ItemKind::Impl => "impl blocks",
         ItemKind::Function => "functions",
         ItemKind::Use => "use items",
+        ItemKind::TypeAlias => "type aliases",
+        ItemKind::Error => "invalid items",
     })]
     ForbiddenItemInImpl(ItemKind),
+
+    #[error("`return` can only be used in a function body")]
+    ReturnOutsideFunction,
+
+    #[error("`{0}` can only be used in a loop")]
+    OutsideLoop(&'static str),
+
+    #[error("Use of undeclared label `'{0}`")]
+    UnknownLabel(Ident),
+
+    #[error("A declaration can't be the value of a block. Add a `;` after it")]
+    DeclarationAsValue,
+
+    #[error("`{0}` is bound more than once in the same pattern")]
+    DuplicateBinding(Ident),
+
+    /// The kind of the name, e.g. "argument", and the name
+    #[error("Duplicate {0} `{1}`")]
+    DuplicateName(&'static str, TinyString),
+
+    #[error("`{0}` in the `where` clause is not a generic parameter of this function")]
+    UnknownGenericParam(UpperIdent),
+
+    #[error("Type alias `{}` is recursive: {}", .cycle[0], format_cycle(.cycle))]
+    RecursiveTypeAlias { cycle: Vec<UpperIdent> },
+}
+
+fn format_cycle(cycle: &[UpperIdent]) -> String {
+    let names: Vec<String> = cycle.iter().map(|n| n.to_string()).collect();
+    format!("{} -> {}", names.join(" -> "), cycle[0])
 }
 
 pub(super) trait Validate {
@@ -91,23 +147,35 @@ impl Validate for NamedType {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ExprPlaceType {
     Place,
+    /// The receiver of a function call or member access, e.g. `Foo` in
+    /// `Foo()` or `Foo.new()`, which may be a type
+    Receiver,
     Other,
 }
 
-impl Validate for Expr {
-    type State = ExprPlaceType;
+/// Whether an expression is in a function and in a loop, which determines
+/// whether `return`, `break` and `continue` are allowed. A loop can be outside
+/// of a function, e.g. in the default value of an argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scope {
+    in_function: bool,
+    in_loop: bool,
+}
 
-    fn validate(&self, state: ExprPlaceType) -> Result<(), ValidationError> {
-        fn check_place_name(name: &Name) -> Result<(), ValidationError> {
-            match name {
-                Name::Operator(_) => {
-                    Err(ValidationError::ExpectedGot("identifier", "operator"))
-                }
-                Name::Type(_) => Err(ValidationError::ExpectedGot("identifier", "type")),
-                Name::Ident(_) => Ok(()),
-            }
-        }
+impl Scope {
+    /// Not in a function body, e.g. in the default value of an argument
+    const NO_FUNCTION: Scope = Scope { in_function: false, in_loop: false };
+    /// In a function or lambda body, but not in a loop
+    const FUNCTION: Scope = Scope { in_function: true, in_loop: false };
+
+    /// Returns the scope of a loop body
+    fn enter_loop(self) -> Scope { Scope { in_loop: true, ..self } }
+}
+
+impl Validate for Expr {
+    type State = (ExprPlaceType, Scope);
 
+    fn validate(&self, (state, scope): Self::State) -> Result<(), ValidationError> {
         if state == ExprPlaceType::Place {
             match self {
                 Expr::Invokable(i) | Expr::MemberCall(MemberCall { member: i, .. }) => {
@@ -116,36 +184,78 @@ impl Validate for Expr {
                     }
                     check_place_name(&i.name.inner)?;
                 }
+                Expr::Index(_) => {}
                 _ => return Err(ValidationError::NoPlaceExpr(self.kind())),
             }
         }
-
         match self {
             Expr::Invokable(i) => i.validate(())?,
             Expr::Literal(_) => {}
-            Expr::ParenCall(p) => p.validate(())?,
-            Expr::MemberCall(m) => m.validate(())?,
-            Expr::Operation(o) => o.validate(())?,
-            Expr::ShortcircuitingOp(o) => o.validate(())?,
-            Expr::Assignment(a) => a.validate(())?,
-            Expr::TypeAscription(t) => t.validate(())?,
-            Expr::Statement(s) => s.validate(ExprPlaceType::Other)?,
+            Expr::ParenCall(p) => p.validate(scope)?,
+            Expr::Index(i) => i.validate(scope)?,
+            Expr::MemberCall(m) => m.validate(scope)?,
+            Expr::Operation(o) => o.validate(scope)?,
+            Expr::ShortcircuitingOp(o) => o.validate(scope)?,
+            Expr::UnaryOp(o) => o.validate(scope)?,
+            Expr::Assignment(a) => a.validate(scope)?,
+            Expr::TypeAscription(t) => t.validate(scope)?,
+            Expr::Statement(s) => s.validate((ExprPlaceType::Other, scope))?,
+            // TODO: check that the function returns a `Result`-like type
+            Expr::Try(t) => match &t.inner {
+                Expr::ParenCall(_) | Expr::MemberCall(_) | Expr::Invokable(_) => {
+                    t.validate((ExprPlaceType::Other, scope))?
+                }
+                e => return Err(ValidationError::InvalidTryOperand(e.kind())),
+            },
             Expr::Lambda(l) => l.validate(())?,
-            Expr::Block(b) => b.validate(())?,
-            Expr::Tuple(t) => t.validate(())?,
+            Expr::Block(b) => b.validate(scope)?,
+            Expr::Tuple(t) => t.validate(scope)?,
+            Expr::ArrayLit(a) => a.elements.validate((ExprPlaceType::Other, scope))?,
             Expr::Empty(_) => {}
-            Expr::Declaration(d) => d.validate(())?,
-            Expr::Match(c) => c.validate(())?,
+            Expr::Declaration(d) => d.validate(scope)?,
+            Expr::Match(c) => c.validate(scope)?,
+            Expr::While(w) => w.validate(scope)?,
+            Expr::For(f) => f.validate(scope)?,
+            Expr::IfExpr(i) => i.validate(scope)?,
+            Expr::Return(value) => {
+                if !scope.in_function {
+                    return Err(ValidationError::ReturnOutsideFunction);
+                }
+                if let Some(value) = value {
+                    value.validate((ExprPlaceType::Other, scope))?;
+                }
+            }
+            Expr::Break(_) if !scope.in_loop => {
+                return Err(ValidationError::OutsideLoop("break"));
+            }
+            Expr::Continue(_) if !scope.in_loop => {
+                return Err(ValidationError::OutsideLoop("continue"));
+            }
+            Expr::Break(_) | Expr::Continue(_) => {}
         }
         Ok(())
     }
 }
 
+fn check_place_name(name: &Name) -> Result<(), ValidationError> {
+    match name {
+        Name::Operator(_) => Err(ValidationError::ExpectedGot("identifier", "operator")),
+        Name::Type(_) => Err(ValidationError::ExpectedGot("identifier", "type")),
+        Name::Ident(_) => Ok(()),
+    }
+}
+
+/// Returns whether the expression is a single expression in parentheses rather
+/// than a tuple, e.g. `(a)` but not `(a,)`
+fn is_parenthesized(expr: &Expr) -> bool {
+    matches!(expr, Expr::Tuple(t) if t.exprs.len() == 1 && !t.trailing_comma)
+}
+
 impl Validate for ParenCall {
-    type State = ();
+    type State = Scope;
 
-    fn validate(&self, _: ()) -> Result<(), ValidationError> {
-        self.receiver.validate(ExprPlaceType::Other)?;
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
+        self.receiver.validate((ExprPlaceType::Receiver, scope))?;
 
         let kind = self.receiver.kind();
         match kind {
@@ -155,15 +265,28 @@ impl Validate for ParenCall {
             | ExprKind::Statement
             | ExprKind::Empty
             | ExprKind::ShortcircuitingOp
+            | ExprKind::UnaryOp
             | ExprKind::Declaration => {
                 return Err(ValidationError::InvalidCallReceiver(kind))
             }
+            // an expression in parentheses can be called, e.g. `(f)(x)`
+            ExprKind::Tuple if is_parenthesized(&self.receiver.inner) => {}
+            ExprKind::Literal | ExprKind::Tuple | ExprKind::Block => {
+                return Err(ValidationError::NotCallable(kind))
+            }
             _ => {}
         }
 
         if let Some(args) = &self.args {
             let mut unnamed_found = false;
+            let mut fields = Vec::new();
             for arg in &**args {
+                if let Some(ArgName::RecordArg(field)) = arg.name.as_deref() {
+                    if fields.contains(&field) {
+                        return Err(ValidationError::DuplicateField(field.clone()));
+                    }
+                    fields.push(field);
+                }
                 if arg.name.is_some() {
                     if unnamed_found {
                         return Err(ValidationError::NamedAfterUnnamed);
@@ -171,7 +294,7 @@ impl Validate for ParenCall {
                 } else {
                     unnamed_found = true;
                 }
-                arg.validate(())?;
+                arg.validate(scope)?;
             }
         }
         Ok(())
@@ -179,18 +302,44 @@ impl Validate for ParenCall {
 }
 
 impl Validate for FunCallArgument {
-    type State = ();
+    type State = Scope;
 
-    fn validate(&self, _: ()) -> Result<(), ValidationError> {
-        self.expr.validate(ExprPlaceType::Other)
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
+        self.expr.validate((ExprPlaceType::Other, scope))
+    }
+}
+
+impl Validate for IndexExpr {
+    type State = Scope;
+
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
+        self.receiver.validate((ExprPlaceType::Other, scope))?;
+        let kind = self.receiver.kind();
+        match kind {
+            | ExprKind::Operation
+            | ExprKind::Assignment
+            | ExprKind::TypeAscription
+            | ExprKind::Statement
+            | ExprKind::Empty
+            | ExprKind::ShortcircuitingOp
+            | ExprKind::UnaryOp
+            | ExprKind::Declaration => {
+                return Err(ValidationError::InvalidIndexReceiver(kind))
+            }
+            _ => {}
+        }
+        if self.indices.is_empty() {
+            return Err(ValidationError::MissingIndex);
+        }
+        self.indices.validate((ExprPlaceType::Other, scope))
     }
 }
 
 impl Validate for MemberCall {
-    type State = ();
+    type State = Scope;
 
-    fn validate(&self, _: ()) -> Result<(), ValidationError> {
-        self.receiver.validate(ExprPlaceType::Other)?;
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
+        self.receiver.validate((ExprPlaceType::Receiver, scope))?;
         let kind = self.receiver.kind();
         match kind {
             | ExprKind::Operation
@@ -199,6 +348,7 @@ impl Validate for MemberCall {
             | ExprKind::Statement
             | ExprKind::Empty
             | ExprKind::ShortcircuitingOp
+            | ExprKind::UnaryOp
             | ExprKind::Declaration => {
                 return Err(ValidationError::InvalidMemberReceiver(kind))
             }
@@ -216,27 +366,20 @@ impl Validate for Invokable {
 }
 
 impl Validate for Operation {
-    type State = ();
-
-    fn validate(&self, _: ()) -> Result<(), ValidationError> {
-        ensure_no_operation_except(&self.lhs.inner, &self.operator)?;
-        ensure_no_operation_except(&self.rhs.inner, &self.operator)?;
-        self.lhs.validate(ExprPlaceType::Other)?;
-        self.rhs.validate(ExprPlaceType::Other)?;
+    type State = Scope;
+
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
+        // mixing different operators is checked by the parser, which knows
+        // their precedence
+        ensure_valid_operand(&self.lhs.inner)?;
+        ensure_valid_operand(&self.rhs.inner)?;
+        self.lhs.validate((ExprPlaceType::Other, scope))?;
+        self.rhs.validate((ExprPlaceType::Other, scope))?;
         Ok(())
     }
 }
 
-fn ensure_no_operation_except(
-    expr: &Expr,
-    except: &Operator,
-) -> Result<(), ValidationError> {
-    match expr {
-        Expr::Operation(o) if &o.operator != except => {
-            return Err(ValidationError::OperationsRequireBlock);
-        }
-        _ => {}
-    }
+fn ensure_valid_operand(expr: &Expr) -> Result<(), ValidationError> {
     let kind = expr.kind();
     match kind {
         | ExprKind::Statement
@@ -249,14 +392,23 @@ fn ensure_no_operation_except(
     Ok(())
 }
 
+impl Validate for UnaryOp {
+    type State = Scope;
+
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
+        ensure_valid_operand(&self.operand.inner)?;
+        self.operand.validate((ExprPlaceType::Other, scope))
+    }
+}
+
 impl Validate for ScOperation {
-    type State = ();
+    type State = Scope;
 
-    fn validate(&self, _: ()) -> Result<(), ValidationError> {
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
         ensure_no_sc_operation_except(&self.lhs.inner, self.operator)?;
         ensure_no_sc_operation_except(&self.rhs.inner, self.operator)?;
-        self.lhs.validate(ExprPlaceType::Other)?;
-        self.rhs.validate(ExprPlaceType::Other)?;
+        self.lhs.validate((ExprPlaceType::Other, scope))?;
+        self.rhs.validate((ExprPlaceType::Other, scope))?;
         Ok(())
     }
 }
@@ -283,20 +435,20 @@ fn ensure_no_sc_operation_except(
 }
 
 impl Validate for Assignment {
-    type State = ();
+    type State = Scope;
 
-    fn validate(&self, _: ()) -> Result<(), ValidationError> {
-        self.lhs.validate(ExprPlaceType::Place)?;
-        self.rhs.validate(ExprPlaceType::Other)?;
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
+        self.lhs.validate((ExprPlaceType::Place, scope))?;
+        self.rhs.validate((ExprPlaceType::Other, scope))?;
         Ok(())
     }
 }
 
 impl Validate for TypeAscription {
-    type State = ();
+    type State = Scope;
 
-    fn validate(&self, _: ()) -> Result<(), ValidationError> {
-        self.expr.validate(ExprPlaceType::Other)?;
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
+        self.expr.validate((ExprPlaceType::Other, scope))?;
         self.ty.validate(())?;
         Ok(())
     }
@@ -306,8 +458,9 @@ impl Validate for Lambda {
     type State = ();
 
     fn validate(&self, _: ()) -> Result<(), ValidationError> {
+        check_duplicate_names("argument", self.args.iter().map(|a| a.name.get()))?;
         self.args.validate(())?;
-        self.body.validate(ExprPlaceType::Other)?;
+        self.body.validate((ExprPlaceType::Other, Scope::FUNCTION))?;
         Ok(())
     }
 }
@@ -319,60 +472,124 @@ impl Validate for LambdaArgument {
 }
 
 impl Validate for Block {
-    type State = ();
+    type State = Scope;
 
-    fn validate(&self, _: ()) -> Result<(), ValidationError> {
-        self.exprs.validate(ExprPlaceType::Other)
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
+        if !self.ends_with_semicolon {
+            if let Some(Expr::Declaration(_)) = self.exprs.last().map(|e| &e.inner) {
+                return Err(ValidationError::DeclarationAsValue);
+            }
+        }
+        self.exprs.validate((ExprPlaceType::Other, scope))
     }
 }
 
 impl Validate for Parens {
-    type State = ();
+    type State = Scope;
 
-    fn validate(&self, _: ()) -> Result<(), ValidationError> {
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
         for arg in &*self.exprs {
             if arg.name.is_some() {
                 return Err(ValidationError::NamedArgInTuple);
             }
-            arg.validate(())?;
+            arg.validate(scope)?;
         }
         Ok(())
     }
 }
 
 impl Validate for Declaration {
-    type State = ();
+    type State = Scope;
 
-    fn validate(&self, _: ()) -> Result<(), ValidationError> {
-        self.value.validate(ExprPlaceType::Other)
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
+        self.value.validate((ExprPlaceType::Other, scope))
     }
 }
 
 impl Validate for Match {
-    type State = ();
+    type State = Scope;
 
-    fn validate(&self, _: ()) -> Result<(), ValidationError> {
-        self.expr.validate(ExprPlaceType::Other)?;
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
+        self.expr.validate((ExprPlaceType::Other, scope))?;
         for arm in self.match_arms.iter() {
-            arm.inner.validate(())?;
+            arm.inner.validate(scope)?;
         }
         Ok(())
     }
 }
 
 impl Validate for MatchArm {
-    type State = ();
+    type State = Scope;
 
-    fn validate(&self, _: ()) -> Result<(), ValidationError> {
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
         self.pattern.validate(())?;
-        self.expr.validate(ExprPlaceType::Other)
+        self.expr.validate((ExprPlaceType::Other, scope))
+    }
+}
+
+impl Validate for While {
+    type State = Scope;
+
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
+        self.condition.validate((ExprPlaceType::Other, scope))?;
+        self.body.validate(scope.enter_loop())
+    }
+}
+
+impl Validate for IfExpr {
+    type State = Scope;
+
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
+        self.condition.validate((ExprPlaceType::Other, scope))?;
+        self.then_branch.validate(scope)?;
+        if let Some(else_branch) = &self.else_branch {
+            else_branch.validate((ExprPlaceType::Other, scope))?;
+        }
+        Ok(())
+    }
+}
+
+impl Validate for For {
+    type State = Scope;
+
+    fn validate(&self, scope: Scope) -> Result<(), ValidationError> {
+        // the loop variable is assigned in every iteration
+        if !self.binding.generics.is_empty() {
+            return Err(ValidationError::UnexpectedGenerics);
+        }
+        check_place_name(&self.binding.name)?;
+        self.iterable.validate((ExprPlaceType::Other, scope))?;
+        self.body.validate(scope.enter_loop())
     }
 }
 
 impl Validate for Pattern {
     type State = ();
 
-    fn validate(&self, _: ()) -> Result<(), ValidationError> { Ok(()) }
+    fn validate(&self, _: ()) -> Result<(), ValidationError> {
+        fn check_bindings<'a>(
+            pattern: &'a Pattern,
+            bindings: &mut Vec<&'a Ident>,
+        ) -> Result<(), ValidationError> {
+            match pattern {
+                Pattern::Binding(name) => {
+                    if bindings.contains(&name) {
+                        return Err(ValidationError::DuplicateBinding(name.clone()));
+                    }
+                    bindings.push(name);
+                }
+                Pattern::Tuple(patterns) => {
+                    for p in patterns {
+                        check_bindings(p, bindings)?;
+                    }
+                }
+                _ => {}
+            }
+            Ok(())
+        }
+
+        check_bindings(self, &mut Vec::new())
+    }
 }
 
 
@@ -386,6 +603,9 @@ impl Validate for Function {
     type State = FunctionType;
 
     fn validate(&self, state: Self::State) -> Result<(), ValidationError> {
+        check_generics(&self.generics)?;
+        check_duplicate_names("argument", self.args.iter().map(|a| a.name.get()))?;
+
         let mut default_found = false;
         for arg in &**self.args {
             match &arg.ty {
@@ -395,7 +615,7 @@ impl Validate for Function {
             match &arg.default {
                 Some(default) => {
                     default_found = true;
-                    default.validate(ExprPlaceType::Other)?;
+                    default.validate((ExprPlaceType::Other, Scope::NO_FUNCTION))?;
                 }
                 None if default_found => {
                     return Err(ValidationError::NoDefaultAfterDefault);
@@ -409,8 +629,22 @@ impl Validate for Function {
             None => return Err(ValidationError::ExpectedReturnType),
         }
 
+        if let Some(where_clause) = &self.where_clause {
+            for bound in where_clause.bounds.iter() {
+                if !self.generics.iter().any(|g| g.name.inner == bound.param.inner) {
+                    let param = bound.param.inner.clone();
+                    return Err(ValidationError::UnknownGenericParam(param));
+                }
+            }
+        }
+        for param in self.generics.iter() {
+            for bound in self.bounds_of(&param.name) {
+                bound.ty.validate(())?;
+            }
+        }
+
         match &self.body {
-            Some(b) => b.validate(())?,
+            Some(b) => b.validate(Scope::FUNCTION)?,
             None if state == FunctionType::Complete => {
                 return Err(ValidationError::ExpectedFunctionBody);
             }
@@ -423,26 +657,40 @@ impl Validate for Function {
 impl Validate for Class {
     type State = ();
 
-    fn validate(&self, _: ()) -> Result<(), ValidationError> { Ok(()) }
+    fn validate(&self, _: ()) -> Result<(), ValidationError> {
+        check_generics(&self.generics)?;
+        check_duplicate_names("field", self.fields.iter().map(|f| f.name.get()))
+    }
 }
 
 impl Validate for Enum {
     type State = ();
 
-    fn validate(&self, _: ()) -> Result<(), ValidationError> { Ok(()) }
+    fn validate(&self, _: ()) -> Result<(), ValidationError> {
+        check_generics(&self.generics)?;
+        check_duplicate_names("variant", self.variants.iter().map(|v| v.name.get()))?;
+        for variant in self.variants.iter() {
+            if let Some(fields) = &variant.arguments {
+                check_duplicate_names("field", fields.iter().map(|f| f.name.get()))?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Validate for Impl {
     type State = ();
 
     fn validate(&self, _: ()) -> Result<(), ValidationError> {
+        check_generics(&self.generics)?;
         for item in self.items.iter() {
             match item.inner.kind() {
-                ItemKind::Function => {}
+                ItemKind::Function | ItemKind::TypeAlias => {}
                 k => return Err(ValidationError::ForbiddenItemInImpl(k)),
             }
         }
-        self.items.validate(())
+        self.items.validate(())?;
+        check_type_aliases(&self.items).map_err(|e| e.inner)
     }
 }
 
@@ -452,6 +700,34 @@ impl Validate for Use {
     fn validate(&self, _: ()) -> Result<(), ValidationError> { Ok(()) }
 }
 
+impl Validate for TypeAlias {
+    type State = ();
+
+    fn validate(&self, _: ()) -> Result<(), ValidationError> {
+        check_generics(&self.generics)?;
+        self.ty.validate(())
+    }
+}
+
+fn check_generics(generics: &[Spanned<GenericParam>]) -> Result<(), ValidationError> {
+    check_duplicate_names("generic parameter", generics.iter().map(|g| g.name.get()))
+}
+
+/// Returns an error for the first name that appeared before. Names are compared
+/// by their text.
+fn check_duplicate_names<'a>(
+    kind: &'static str,
+    names: impl Iterator<Item = &'a str>,
+) -> Result<(), ValidationError> {
+    let mut seen = HashSet::new();
+    for name in names {
+        if !seen.insert(name) {
+            return Err(ValidationError::DuplicateName(kind, name.into()));
+        }
+    }
+    Ok(())
+}
+
 
 impl Validate for Item {
     type State = ();
@@ -463,7 +739,193 @@ impl Validate for Item {
             Item::Enum(e) => e.validate(())?,
             Item::Impl(i) => i.validate(())?,
             Item::Use(i) => i.validate(())?,
+            Item::TypeAlias(t) => t.validate(())?,
+            Item::Error => {}
         }
         Ok(())
     }
 }
+
+/// Runs the checks that need all items of the module, unlike [`Validate`]
+pub(super) fn check_module(
+    items: &[Spanned<Item>],
+) -> Result<(), Spanned<ValidationError>> {
+    check_type_aliases(items)?;
+    check_type_names(items)?;
+    check_labels(items)
+}
+
+/// Checks that the label of each `break` and `continue` belongs to a loop around
+/// it. Labels aren't visible in lambdas, since a lambda can't break out of a loop
+/// around it. The error has the span of the label.
+fn check_labels(items: &[Spanned<Item>]) -> Result<(), Spanned<ValidationError>> {
+    let mut checker = LabelChecker { labels: Vec::new(), error: None };
+    for item in items {
+        checker.visit_item(item);
+    }
+    checker.error.map_or(Ok(()), Err)
+}
+
+struct LabelChecker<'a> {
+    /// The labels of the loops around the current expression
+    labels: Vec<&'a Ident>,
+    error: Option<Spanned<ValidationError>>,
+}
+
+impl<'ast> Visit<'ast> for LabelChecker<'ast> {
+    fn visit_expr(&mut self, expr: &'ast Spanned<Expr>) {
+        if self.error.is_some() {
+            return;
+        }
+        match &expr.inner {
+            Expr::While(While { label: Some(label), .. })
+            | Expr::For(For { label: Some(label), .. }) => {
+                self.labels.push(&label.inner);
+                walk_expr(self, expr);
+                self.labels.pop();
+            }
+            Expr::Break(Some(label)) | Expr::Continue(Some(label)) => {
+                if !self.labels.contains(&&label.inner) {
+                    let error = ValidationError::UnknownLabel(label.inner.clone());
+                    self.error = Some(label.span.embed(error));
+                }
+            }
+            _ => walk_expr(self, expr),
+        }
+    }
+
+    fn visit_lambda(&mut self, lambda: &'ast Lambda) {
+        let outer = std::mem::take(&mut self.labels);
+        walk_lambda(self, lambda);
+        self.labels = outer;
+    }
+}
+
+/// Checks that a type name is only used as a value if it is a unit constructor,
+/// i.e. a class without fields, e.g. `Foo` after `class Foo();`. Other type names
+/// must be called or be the receiver of a member call, e.g. `Foo()` or
+/// `Foo.new()`. The error has the span of the type name.
+fn check_type_names(items: &[Spanned<Item>]) -> Result<(), Spanned<ValidationError>> {
+    let unit_classes = items
+        .iter()
+        .filter_map(|item| match &item.inner {
+            Item::Class(c) if c.fields.is_empty() => Some(c.name.get()),
+            _ => None,
+        })
+        .collect();
+
+    let mut checker = TypeNameChecker { unit_classes, error: None };
+    for item in items {
+        checker.visit_item(item);
+    }
+    checker.error.map_or(Ok(()), Err)
+}
+
+fn is_type_name(expr: &Expr) -> bool {
+    matches!(expr, Expr::Invokable(i) if matches!(i.name.inner, Name::Type(_)))
+}
+
+struct TypeNameChecker<'a> {
+    unit_classes: HashSet<&'a str>,
+    error: Option<Spanned<ValidationError>>,
+}
+
+impl<'ast> Visit<'ast> for TypeNameChecker<'ast> {
+    fn visit_expr(&mut self, expr: &'ast Spanned<Expr>) {
+        if self.error.is_some() {
+            return;
+        }
+        match &expr.inner {
+            Expr::Invokable(Invokable { name, .. }) => {
+                if let Name::Type(ty) = &name.inner {
+                    if !self.unit_classes.contains(ty.get()) {
+                        let error = ValidationError::TypeInValuePosition(ty.clone());
+                        self.error = Some(expr.span.embed(error));
+                    }
+                }
+            }
+            Expr::ParenCall(c) if is_type_name(&c.receiver) => {
+                for arg in c.args.iter().flat_map(|args| args.iter()) {
+                    self.visit_expr(&arg.expr);
+                }
+            }
+            Expr::MemberCall(m) if is_type_name(&m.receiver) => {
+                self.visit_invokable(&m.member);
+            }
+            _ => walk_expr(self, expr),
+        }
+    }
+}
+
+/// Checks that no type alias refers to itself, directly or through other
+/// aliases, e.g. `type A = B; type B = A;`. The error has the span of the first
+/// alias in the cycle.
+fn check_type_aliases(
+    items: &[Spanned<Item>],
+) -> Result<(), Spanned<ValidationError>> {
+    let aliases: HashMap<&UpperIdent, &Spanned<Item>> = items
+        .iter()
+        .filter_map(|item| match &item.inner {
+            Item::TypeAlias(t) => Some((&t.name.inner, item)),
+            _ => None,
+        })
+        .collect();
+
+    let mut finished = HashSet::new();
+    let mut path = Vec::new();
+    for item in items {
+        if let Item::TypeAlias(t) = &item.inner {
+            if let Some(cycle) = find_cycle(&t.name, &aliases, &mut path, &mut finished) {
+                let span = aliases[&cycle[0]].span;
+                return Err(span.embed(ValidationError::RecursiveTypeAlias { cycle }));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Depth-first search through the aliases that `name` refers to. `path`
+/// contains the aliases that are currently being visited.
+fn find_cycle<'a>(
+    name: &'a UpperIdent,
+    aliases: &HashMap<&'a UpperIdent, &'a Spanned<Item>>,
+    path: &mut Vec<&'a UpperIdent>,
+    finished: &mut HashSet<&'a UpperIdent>,
+) -> Option<Vec<UpperIdent>> {
+    if let Some(i) = path.iter().position(|&n| n == name) {
+        return Some(path[i..].iter().map(|&n| n.clone()).collect());
+    }
+    let alias = match aliases.get(name).map(|item| &item.inner) {
+        Some(Item::TypeAlias(t)) if !finished.contains(name) => t,
+        _ => return None,
+    };
+
+    path.push(name);
+    let mut referenced = Vec::new();
+    collect_type_names(&alias.ty, &mut referenced);
+    for r in referenced {
+        // generic parameters shadow aliases with the same name
+        if alias.generics.iter().any(|g| g.name.inner == *r) {
+            continue;
+        }
+        if let Some(cycle) = find_cycle(r, aliases, path, finished) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    finished.insert(name);
+    None
+}
+
+/// Qualified names such as `a.B` are skipped, because they can't refer to an
+/// alias in the current module
+fn collect_type_names<'a>(ty: &'a NamedType, names: &mut Vec<&'a UpperIdent>) {
+    if ty.path.is_empty() {
+        names.push(&ty.name.inner);
+    }
+    for arg in ty.args.iter() {
+        if let TypeArgument::Type(t) = &arg.inner {
+            collect_type_names(t, names);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Commands that start with a colon, e.g. `:quit`. They are handled before the
+//! input is lexed.
+
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Quit,
+    Help,
+    /// Whether the tokens are printed
+    Tokens(bool),
+    /// Whether the parsed program is printed
+    Ast(bool),
+    /// Whether the parsed program is printed with `ToBeauty` instead of `Debug`
+    Beauty(bool),
+    /// Runs a file through the same pipeline as the input
+    Load(PathBuf),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CommandError {
+    Unknown(String),
+    MissingArgument(&'static str),
+    InvalidArgument(&'static str, String),
+    UnexpectedArgument(&'static str),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Unknown(c) => {
+                write!(f, "Unknown command `:{}`. Type `:help` for a list of commands", c)
+            }
+            CommandError::MissingArgument(c) => write!(f, "`:{}` expects an argument", c),
+            CommandError::InvalidArgument(c, arg) => write!(
+                f,
+                "Invalid argument for `:{}`: `{}`, expected `on` or `off`",
+                c, arg
+            ),
+            CommandError::UnexpectedArgument(c) => {
+                write!(f, "`:{}` doesn't expect an argument", c)
+            }
+        }
+    }
+}
+
+pub const HELP: &str = "\
+:help                 Show this list
+:quit, :q             Exit the REPL
+:tokens on|off        Print the lexed tokens
+:ast on|off           Print the parsed program
+:beauty on|off        Print the parsed program as a tree instead of `Debug` output
+:load <path>          Run the contents of a file";
+
+/// Parses a command. Returns `None` if the input doesn't start with a colon, so
+/// it should be lexed and parsed instead.
+pub fn parse_command(input: &str) -> Option<Result<Command, CommandError>> {
+    let input = input.trim().strip_prefix(':')?;
+    let (name, arg) = match input.find(char::is_whitespace) {
+        Some(i) => (&input[..i], Some(input[i..].trim())),
+        None => (input, None),
+    };
+
+    Some(match name {
+        "quit" | "q" => no_argument("quit", arg).map(|()| Command::Quit),
+        "help" => no_argument("help", arg).map(|()| Command::Help),
+        "tokens" => on_off("tokens", arg).map(Command::Tokens),
+        "ast" => on_off("ast", arg).map(Command::Ast),
+        "beauty" => on_off("beauty", arg).map(Command::Beauty),
+        "load" => match arg {
+            Some(path) => Ok(Command::Load(PathBuf::from(path))),
+            None => Err(CommandError::MissingArgument("load")),
+        },
+        _ => Err(CommandError::Unknown(name.to_string())),
+    })
+}
+
+fn no_argument(name: &'static str, arg: Option<&str>) -> Result<(), CommandError> {
+    match arg {
+        Some(_) => Err(CommandError::UnexpectedArgument(name)),
+        None => Ok(()),
+    }
+}
+
+fn on_off(name: &'static str, arg: Option<&str>) -> Result<bool, CommandError> {
+    match arg {
+        Some("on") => Ok(true),
+        Some("off") => Ok(false),
+        Some(arg) => Err(CommandError::InvalidArgument(name, arg.to_string())),
+        None => Err(CommandError::MissingArgument(name)),
+    }
+}
@@ -0,0 +1,266 @@
+//! A minimal line editor. When stdin is a terminal, it is switched to raw mode
+//! with `stty`, so the cursor can be moved with the arrow keys and previous
+//! lines can be recalled from the history. Otherwise, lines are read as-is.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+pub enum Input {
+    Line(String),
+    /// Ctrl+C was pressed
+    Interrupted,
+    /// Ctrl+D was pressed on an empty line, or stdin was closed
+    Eof,
+}
+
+pub struct Editor {
+    history: Vec<String>,
+    history_file: Option<File>,
+}
+
+impl Editor {
+    /// Loads the history from `history_path`, if it exists. Lines that are read
+    /// are appended to this file.
+    pub fn new(history_path: Option<PathBuf>) -> Self {
+        let mut history = Vec::new();
+        let mut history_file = None;
+        if let Some(path) = history_path {
+            if let Ok(file) = File::open(&path) {
+                history.extend(BufReader::new(file).lines().map_while(Result::ok));
+            }
+            if let Some(dir) = path.parent() {
+                let _ = fs::create_dir_all(dir);
+            }
+            history_file = OpenOptions::new().create(true).append(true).open(path).ok();
+        }
+        Editor { history, history_file }
+    }
+
+    pub fn read_line(&mut self, prompt: &str) -> io::Result<Input> {
+        print!("{}", prompt);
+        io::stdout().flush()?;
+
+        let input = match RawMode::enable() {
+            Some(_raw) => self.edit_line(prompt)?,
+            None => {
+                let mut line = String::new();
+                if io::stdin().read_line(&mut line)? == 0 {
+                    Input::Eof
+                } else {
+                    let len = line.trim_end_matches(&['\n', '\r'][..]).len();
+                    line.truncate(len);
+                    Input::Line(line)
+                }
+            }
+        };
+
+        if let Input::Line(line) = &input {
+            self.add_history(line);
+        }
+        Ok(input)
+    }
+
+    fn add_history(&mut self, line: &str) {
+        if line.trim().is_empty() || self.history.last().map(|l| &**l) == Some(line) {
+            return;
+        }
+        self.history.push(line.to_string());
+        if let Some(file) = &mut self.history_file {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    fn edit_line(&self, prompt: &str) -> io::Result<Input> {
+        let mut stdin = io::stdin().lock();
+        let mut line = LineBuffer::default();
+        let mut history_pos = self.history.len();
+
+        loop {
+            match read_key(&mut stdin)? {
+                Key::Eof | Key::Ctrl(b'd') if line.chars.is_empty() => {
+                    println!();
+                    return Ok(Input::Eof);
+                }
+                Key::Eof | Key::Enter => break,
+                Key::Ctrl(b'c') => {
+                    println!("^C");
+                    return Ok(Input::Interrupted);
+                }
+                Key::Char(c) => {
+                    line.chars.insert(line.cursor, c);
+                    line.cursor += 1;
+                }
+                Key::Backspace if line.cursor > 0 => {
+                    line.cursor -= 1;
+                    line.chars.remove(line.cursor);
+                }
+                Key::Delete | Key::Ctrl(b'd') if line.cursor < line.chars.len() => {
+                    line.chars.remove(line.cursor);
+                }
+                Key::Left if line.cursor > 0 => line.cursor -= 1,
+                Key::Right if line.cursor < line.chars.len() => line.cursor += 1,
+                Key::Home | Key::Ctrl(b'a') => line.cursor = 0,
+                Key::End | Key::Ctrl(b'e') => line.cursor = line.chars.len(),
+                Key::Up if history_pos > 0 => {
+                    history_pos -= 1;
+                    line.set(&self.history[history_pos]);
+                }
+                Key::Down if history_pos < self.history.len() => {
+                    history_pos += 1;
+                    line.set(self.history.get(history_pos).map_or("", |l| &**l));
+                }
+                _ => continue,
+            }
+            line.redraw(prompt)?;
+        }
+        println!();
+        Ok(Input::Line(line.chars.into_iter().collect()))
+    }
+}
+
+#[derive(Default)]
+struct LineBuffer {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl LineBuffer {
+    fn set(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.cursor = self.chars.len();
+    }
+
+    fn redraw(&self, prompt: &str) -> io::Result<()> {
+        let text: String = self.chars.iter().collect();
+        let mut stdout = io::stdout();
+        write!(stdout, "\r{}{}\x1b[K", prompt, text)?;
+        let after_cursor = self.chars.len() - self.cursor;
+        if after_cursor > 0 {
+            write!(stdout, "\x1b[{}D", after_cursor)?;
+        }
+        stdout.flush()
+    }
+}
+
+enum Key {
+    Char(char),
+    /// A control character, e.g. `Ctrl(b'c')` for Ctrl+C
+    Ctrl(u8),
+    Enter,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    Unknown,
+    Eof,
+}
+
+fn read_byte(reader: &mut impl Read) -> io::Result<Option<u8>> {
+    let mut byte = [0];
+    match reader.read(&mut byte)? {
+        0 => Ok(None),
+        _ => Ok(Some(byte[0])),
+    }
+}
+
+fn read_key(reader: &mut impl Read) -> io::Result<Key> {
+    let first = match read_byte(reader)? {
+        Some(b) => b,
+        None => return Ok(Key::Eof),
+    };
+    Ok(match first {
+        b'\r' | b'\n' => Key::Enter,
+        0x7f | 0x08 => Key::Backspace,
+        0x1b => read_escape_sequence(reader)?,
+        0x01..=0x1a => Key::Ctrl(first - 1 + b'a'),
+        0x00..=0x1f => Key::Unknown,
+        _ => {
+            // UTF-8 sequences are at most 4 bytes long
+            let len = match first {
+                0xf0..=0xff => 4,
+                0xe0..=0xef => 3,
+                0xc0..=0xdf => 2,
+                _ => 1,
+            };
+            let mut bytes = vec![first];
+            for _ in 1..len {
+                bytes.extend(read_byte(reader)?);
+            }
+            match std::str::from_utf8(&bytes) {
+                Ok(s) => s.chars().next().map_or(Key::Unknown, Key::Char),
+                Err(_) => Key::Unknown,
+            }
+        }
+    })
+}
+
+/// Decodes the keys that are sent as `ESC [ ...` or `ESC O ...`
+fn read_escape_sequence(reader: &mut impl Read) -> io::Result<Key> {
+    if !matches!(read_byte(reader)?, Some(b'[') | Some(b'O')) {
+        return Ok(Key::Unknown);
+    }
+    Ok(match read_byte(reader)? {
+        Some(b'A') => Key::Up,
+        Some(b'B') => Key::Down,
+        Some(b'C') => Key::Right,
+        Some(b'D') => Key::Left,
+        Some(b'H') => Key::Home,
+        Some(b'F') => Key::End,
+        Some(n @ b'0'..=b'9') => {
+            // e.g. `ESC [ 3 ~` for the Delete key
+            let mut terminator = read_byte(reader)?;
+            while let Some(b'0'..=b'9') | Some(b';') = terminator {
+                terminator = read_byte(reader)?;
+            }
+            match (n, terminator) {
+                (b'3', Some(b'~')) => Key::Delete,
+                (b'1', Some(b'~')) | (b'7', Some(b'~')) => Key::Home,
+                (b'4', Some(b'~')) | (b'8', Some(b'~')) => Key::End,
+                _ => Key::Unknown,
+            }
+        }
+        _ => Key::Unknown,
+    })
+}
+
+/// Disables line buffering, echoing and signals (so Ctrl+C can be handled) while
+/// it is alive; the previous terminal settings are restored when it is dropped
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    /// Returns `None` if stdin isn't a terminal or `stty` isn't available
+    fn enable() -> Option<Self> {
+        if !io::stdin().is_terminal() {
+            return None;
+        }
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "-isig", "-ixon", "min", "1"])?;
+        Some(RawMode { saved: saved.trim().to_string() })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) { let _ = stty(&[&self.saved]); }
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
@@ -1,6 +1,17 @@
-use ast::token::TokenKind;
+mod commands;
+mod editor;
+
+#[cfg(test)]
+mod tests;
+
+use std::path::PathBuf;
+use std::{env, fs};
+
+use commands::Command;
+use editor::{Editor, Input};
 use lexer::Program;
-use parser::formatting::ToBeauty;
+use yep::token::{Punctuation, Token, TokenKind};
+use yep::ToBeauty;
 
 
 const BLUE: &str = "\x1b[38;2;50;220;255m";
@@ -13,55 +24,160 @@ const GRAY: &str = "\x1b[38;2;130;130;130m";
 const BOLD: &str = "\x1b[1m";
 const RESET: &str = "\x1b[0m";
 
+/// What is printed for each input. It can be changed with commands and persists
+/// for the whole session.
+struct Settings {
+    tokens: bool,
+    ast: bool,
+    beauty: bool,
+}
+
 fn main() {
-    println!("Yep 0.1 REPL. Press Enter twice to validate. Press Ctrl+C to exit.\n");
+    println!(
+        "Yep 0.1 REPL. Press Ctrl+C to cancel the input, Ctrl+D to exit. Type `:help` \
+         for a list of commands.\n"
+    );
 
-    let stdin = std::io::stdin();
+    let mut settings = Settings { tokens: true, ast: true, beauty: true };
+    let mut editor = Editor::new(history_path());
     loop {
         let mut text = String::new();
         loop {
-            stdin.read_line(&mut text).unwrap();
-            if text.ends_with("\n\n") {
-                let _ = text.pop();
-                break;
+            let prompt = if text.is_empty() { ">>> " } else { "... " };
+            match editor.read_line(prompt).unwrap() {
+                Input::Line(line) => {
+                    if text.is_empty() {
+                        match commands::parse_command(&line) {
+                            Some(Ok(Command::Quit)) => return,
+                            Some(Ok(command)) => {
+                                run_command(command, &mut settings);
+                                continue;
+                            }
+                            Some(Err(e)) => {
+                                println!("{}{}{}\n", RED, e, RESET);
+                                continue;
+                            }
+                            None => {}
+                        }
+                    }
+                    text.push_str(&line);
+                    text.push('\n');
+                    if text.trim().is_empty() {
+                        text.clear();
+                    } else if is_complete(&text) {
+                        break;
+                    }
+                }
+                Input::Interrupted => text.clear(),
+                Input::Eof => return,
             }
         }
 
+        run(&text, &settings);
+    }
+}
+
+fn run_command(command: Command, settings: &mut Settings) {
+    match command {
+        Command::Quit => {}
+        Command::Help => println!("{}\n", commands::HELP),
+        Command::Tokens(on) => settings.tokens = on,
+        Command::Ast(on) => settings.ast = on,
+        Command::Beauty(on) => settings.beauty = on,
+        Command::Load(path) => match fs::read_to_string(&path) {
+            Ok(text) => run(&text, settings),
+            Err(e) => println!("{}Could not read {:?}: {}{}\n", RED, path, e, RESET),
+        },
+    }
+}
+
+/// Lexes and parses the input and prints the output enabled in `settings`.
+/// Lex and parse errors are always printed.
+fn run(text: &str, settings: &Settings) {
+    if settings.tokens {
         print!("Lexed program:  ");
-        let program = lexer::lex(&text);
-        print_program(&program, &text);
+        print_program(&lexer::lex(text), text);
         println!("\n");
+    }
 
-        match parser::parse(program.tokens()) {
-            Ok(parsed) => {
+    // all errors are reported, not just the first one
+    match yep::compile_to_ast(text) {
+        Ok(parsed) => {
+            if settings.ast {
                 println!("Parsed output:");
-                println!("{}", parsed.to_beauty_string().trim_end());
-                println!("\n");
-            }
-            Err(error) => {
-                if let parser::Error::RemainingTokens(t) = error {
-                    let rest = Program::from(t);
-                    print!("Expected item, found:  ");
-                    print_program(&rest, &text);
-                    println!("\n");
+                if settings.beauty {
+                    println!("{}", parsed.to_beauty_string().trim_end());
                 } else {
-                    println!("{}\n", error);
+                    println!("{:#?}", parsed);
                 }
+                println!("\n");
+            }
+        }
+        Err(diagnostics) => {
+            let line_index = yep::LineIndex::new(text);
+            for diagnostic in &diagnostics {
+                let (line, col) = diagnostic.line_col(text, &line_index);
+                println!("{}:{}: {}", line, col, diagnostic);
             }
+            println!();
         }
     }
 }
 
+/// Returns whether all parentheses, brackets and braces are closed, so the input
+/// can be submitted. If a bracket is closed by the wrong kind of bracket, the
+/// input is also complete, so the parser can report the error.
+fn is_complete(text: &str) -> bool {
+    let mut open_brackets = Vec::new();
+    for token in lexer::tokens(text) {
+        let closing = match &*token {
+            Token::Punct(p) => match p {
+                Punctuation::OpenParen => Punctuation::CloseParen,
+                Punctuation::OpenBracket => Punctuation::CloseBracket,
+                Punctuation::OpenBrace => Punctuation::CloseBrace,
+                Punctuation::CloseParen
+                | Punctuation::CloseBracket
+                | Punctuation::CloseBrace => {
+                    if open_brackets.pop() != Some(*p) {
+                        return true;
+                    }
+                    continue;
+                }
+                _ => continue,
+            },
+            _ => continue,
+        };
+        open_brackets.push(closing);
+    }
+    open_brackets.is_empty()
+}
+
+/// The history is stored in the user's data directory, e.g.
+/// `~/.local/share/yep/history` on Linux
+fn history_path() -> Option<PathBuf> {
+    let data_dir = match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => match env::var_os("APPDATA") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(env::var_os("HOME")?).join(".local/share"),
+        },
+    };
+    Some(data_dir.join("yep").join("history"))
+}
+
 fn print_program(program: &Program, text: &str) {
     for k in program.tokens() {
         match k.kind() {
             TokenKind::Punct => print!("{}", GRAY),
             TokenKind::NumberLit => print!("{}", YELLOW),
             TokenKind::StringLit => print!("{}", ORANGE),
+            TokenKind::CharLit => print!("{}", ORANGE),
             TokenKind::Ident => print!("{}{}", RESET, BOLD),
+            TokenKind::Label => print!("{}", BLUE),
             TokenKind::UpperIdent => print!("{}", GREEN),
             TokenKind::Operator => print!("{}", PURPLE),
             TokenKind::Keyword => print!("{}", BLUE),
+            TokenKind::DocComment => print!("{}", GRAY),
             TokenKind::Error => print!("{}", RED),
             TokenKind::Eof => print!("{}", RED),
         }
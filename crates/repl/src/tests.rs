@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use crate::commands::{parse_command, Command, CommandError};
+
+#[test]
+fn commands() {
+    assert_eq!(parse_command(":quit"), Some(Ok(Command::Quit)));
+    assert_eq!(parse_command("  :q \n"), Some(Ok(Command::Quit)));
+    assert_eq!(parse_command(":help"), Some(Ok(Command::Help)));
+    assert_eq!(parse_command(":tokens off"), Some(Ok(Command::Tokens(false))));
+    assert_eq!(parse_command(":ast   on"), Some(Ok(Command::Ast(true))));
+    assert_eq!(parse_command(":beauty off"), Some(Ok(Command::Beauty(false))));
+    assert_eq!(
+        parse_command(":load tests/my file.wa"),
+        Some(Ok(Command::Load(PathBuf::from("tests/my file.wa"))))
+    );
+
+    // not a command
+    assert_eq!(parse_command("let x = 5"), None);
+    assert_eq!(parse_command("a :b"), None);
+}
+
+#[test]
+fn command_errors() {
+    assert_eq!(parse_command(":foo"), Some(Err(CommandError::Unknown("foo".into()))));
+    assert_eq!(parse_command(":"), Some(Err(CommandError::Unknown("".into()))));
+    let missing = |c| Some(Err(CommandError::MissingArgument(c)));
+    assert_eq!(parse_command(":tokens"), missing("tokens"));
+    assert_eq!(parse_command(":load  "), missing("load"));
+    assert_eq!(
+        parse_command(":ast yes"),
+        Some(Err(CommandError::InvalidArgument("ast", "yes".into())))
+    );
+    let unexpected = Some(Err(CommandError::UnexpectedArgument("quit")));
+    assert_eq!(parse_command(":q now"), unexpected);
+
+    let error = parse_command(":foo").unwrap().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Unknown command `:foo`. Type `:help` for a list of commands"
+    );
+}
@@ -1,9 +1,10 @@
+use std::borrow::Cow;
 use std::hash::Hash;
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 use std::{fmt, str};
 
 
-/// An immutable string that stored inline on the heap,
+/// An immutable string that is stored inline on the stack,
 /// if it is at most 23 bytes long. Otherwise, it is allocated
 /// on the heap.
 ///
@@ -19,24 +20,51 @@ pub struct TinyString {
     inner: TinyStringInner,
 }
 
+/// The maximum length of a string that is stored inline
+const INLINE_CAP: usize = 23;
+
 #[derive(Clone)]
 enum TinyStringInner {
     /// SAFETY: This array must contain valid UTF-8
-    Stack([u8; 22], u8),
+    Stack([u8; INLINE_CAP], InlineLen),
     Heap(Box<str>),
 }
 
+/// The length of an inline string. Since only the values `0..=23` are valid,
+/// the compiler can use the remaining values to store the enum discriminant of
+/// `TinyStringInner`, so the array can be 23 bytes long while `TinyString`
+/// still fits into 24 bytes.
+#[rustfmt::skip]
+#[allow(dead_code)] // the variants are only created via `InlineLen::new`
+#[derive(Clone, Copy)]
+#[repr(u8)]
+enum InlineLen {
+    L0, L1, L2, L3, L4, L5, L6, L7, L8, L9, L10, L11,
+    L12, L13, L14, L15, L16, L17, L18, L19, L20, L21, L22, L23,
+}
+
+impl InlineLen {
+    fn new(len: usize) -> Self {
+        assert!(len <= INLINE_CAP);
+        // SAFETY: `InlineLen` is `repr(u8)` and has a variant for every value
+        // in `0..=INLINE_CAP`
+        unsafe { std::mem::transmute(len as u8) }
+    }
+
+    fn get(self) -> usize { self as usize }
+}
+
 impl From<&str> for TinyString {
     fn from(s: &str) -> Self {
         let len = s.len();
-        let inner = if len <= 22 {
+        let inner = if len <= INLINE_CAP {
             // SAFETY: The NUL byte is valid UTF-8.
-            let mut data = [0; 22];
+            let mut data = [0; INLINE_CAP];
 
             // SAFETY: Assuming that `s` is valid UTF-8, `data` is, too
             data[0..len].copy_from_slice(s.as_bytes());
 
-            TinyStringInner::Stack(data, len as u8)
+            TinyStringInner::Stack(data, InlineLen::new(len))
         } else {
             TinyStringInner::Heap(s.to_string().into_boxed_str())
         };
@@ -44,18 +72,50 @@ impl From<&str> for TinyString {
     }
 }
 
+/// Reuses the allocation if the string is too long to be stored inline
+impl From<Box<str>> for TinyString {
+    fn from(s: Box<str>) -> Self {
+        if s.len() <= INLINE_CAP {
+            TinyString::from(&*s)
+        } else {
+            TinyString { inner: TinyStringInner::Heap(s) }
+        }
+    }
+}
+
+/// Reuses the allocation if the string is too long to be stored inline. Note
+/// that the allocation is shrunk to fit, if it has excess capacity.
+impl From<String> for TinyString {
+    fn from(s: String) -> Self {
+        if s.len() <= INLINE_CAP {
+            TinyString::from(&*s)
+        } else {
+            TinyString::from(s.into_boxed_str())
+        }
+    }
+}
+
+impl From<Cow<'_, str>> for TinyString {
+    fn from(s: Cow<'_, str>) -> Self {
+        match s {
+            Cow::Borrowed(s) => TinyString::from(s),
+            Cow::Owned(s) => TinyString::from(s),
+        }
+    }
+}
+
 impl Deref for TinyString {
     type Target = str;
 
     fn deref(&self) -> &Self::Target {
         match &self.inner {
             TinyStringInner::Stack(s, l) => {
-                let slice = &s[0..*l as usize];
+                let slice = &s[0..l.get()];
                 // SAFETY: This is sound, assuming that `TinyStringInner`s safety
                 // invariants are upheld
                 unsafe { str::from_utf8_unchecked(slice) }
             }
-            TinyStringInner::Heap(b) => &**b,
+            TinyStringInner::Heap(b) => b,
         }
     }
 }
@@ -63,10 +123,75 @@ impl Deref for TinyString {
 
 impl TinyString {
     /// Borrow the string as a `&str`.
-    pub fn as_str(&self) -> &str { &**self }
+    pub fn as_str(&self) -> &str { self }
 
     /// Create a new `TinyString` from a `&str`
     pub fn new(s: &str) -> Self { TinyString::from(s) }
+
+    /// Creates a `TinyString` that is stored inline, or returns `None` if the
+    /// string is too long. This never allocates.
+    pub fn new_inline(s: &str) -> Option<Self> {
+        if s.len() <= INLINE_CAP {
+            Some(TinyString::from(s))
+        } else {
+            None
+        }
+    }
+
+    /// Concatenates the given slices. Unlike `parts.concat()`, this doesn't
+    /// allocate if the result is short enough to be stored inline.
+    pub fn concat(parts: &[&str]) -> Self {
+        let len = parts.iter().map(|p| p.len()).sum();
+        if len <= INLINE_CAP {
+            let mut data = [0; INLINE_CAP];
+            let mut i = 0;
+            for part in parts {
+                // SAFETY: All parts are valid UTF-8, so the result is, too
+                data[i..i + part.len()].copy_from_slice(part.as_bytes());
+                i += part.len();
+            }
+            TinyString { inner: TinyStringInner::Stack(data, InlineLen::new(len)) }
+        } else {
+            TinyString::from(parts.concat())
+        }
+    }
+
+    /// Returns this string followed by `other`, e.g. to build a qualified name.
+    /// Like [`TinyString::concat`], this doesn't allocate if the result is short
+    /// enough to be stored inline.
+    pub fn concat_with(&self, other: &str) -> Self { TinyString::concat(&[self, other]) }
+
+    /// Returns a copy of a substring. It is stored inline if it is short enough,
+    /// even if this string is stored on the heap.
+    ///
+    /// Panics if the range is out of bounds or not on a char boundary, just like
+    /// slicing a `str`.
+    pub fn slice(&self, range: Range<usize>) -> Self { TinyString::from(&self[range]) }
+
+    /// Moves the string into the inline storage and frees the heap allocation,
+    /// if it is short enough. Otherwise, this does nothing.
+    pub fn shrink_to_inline(&mut self) {
+        if let TinyStringInner::Heap(b) = &self.inner {
+            if b.len() <= INLINE_CAP {
+                *self = TinyString::from(&**b);
+            }
+        }
+    }
+
+    /// Returns `true` if the string is stored inline, i.e. it doesn't use a
+    /// heap allocation.
+    pub fn is_inline(&self) -> bool { matches!(self.inner, TinyStringInner::Stack(..)) }
+
+    /// Returns the length of the string in bytes.
+    pub fn len(&self) -> usize {
+        match &self.inner {
+            TinyStringInner::Stack(_, l) => l.get(),
+            TinyStringInner::Heap(b) => b.len(),
+        }
+    }
+
+    /// Returns `true` if the string has a length of zero.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
 }
 
 impl Default for TinyString {
@@ -74,7 +199,7 @@ impl Default for TinyString {
 }
 
 impl AsRef<str> for TinyString {
-    fn as_ref(&self) -> &str { &**self }
+    fn as_ref(&self) -> &str { self }
 }
 
 impl From<TinyString> for String {
@@ -103,9 +228,37 @@ impl PartialEq for TinyString {
 }
 impl Eq for TinyString {}
 
+impl PartialEq<str> for TinyString {
+    fn eq(&self, other: &str) -> bool { **self == *other }
+}
+
+impl PartialEq<&str> for TinyString {
+    fn eq(&self, other: &&str) -> bool { **self == **other }
+}
+
+impl PartialEq<TinyString> for str {
+    fn eq(&self, other: &TinyString) -> bool { *self == **other }
+}
+
+impl PartialEq<TinyString> for &str {
+    fn eq(&self, other: &TinyString) -> bool { **self == **other }
+}
+
 impl PartialOrd for TinyString {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        (**self).partial_cmp(&**other)
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialOrd<str> for TinyString {
+    fn partial_cmp(&self, other: &str) -> Option<std::cmp::Ordering> {
+        (**self).partial_cmp(other)
+    }
+}
+
+impl PartialOrd<TinyString> for str {
+    fn partial_cmp(&self, other: &TinyString) -> Option<std::cmp::Ordering> {
+        self.partial_cmp(&**other)
     }
 }
 
@@ -117,7 +270,272 @@ impl Hash for TinyString {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) { (**self).hash(state) }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for TinyString {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TinyString {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct TinyStringVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for TinyStringVisitor {
+            type Value = TinyString;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<TinyString, E> {
+                Ok(TinyString::from(v))
+            }
+
+            // reuses the allocation of a long string instead of copying it
+            fn visit_string<E>(self, v: String) -> Result<TinyString, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(TinyString::from(v))
+            }
+        }
+
+        deserializer.deserialize_str(TinyStringVisitor)
+    }
+}
+
+#[cfg(feature = "fuzz")]
+impl arbitrary::Arbitrary for TinyString {
+    /// Generates strings of 0 to 30 bytes, so both sides of the boundary between
+    /// inline and heap storage are covered
+    fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        let len = u.int_in_range(0..=30)?;
+        let mut s = String::with_capacity(len);
+        while s.len() < len {
+            let c: char = u.arbitrary()?;
+            if s.len() + c.len_utf8() <= len {
+                s.push(c);
+            } else {
+                s.push('_');
+            }
+        }
+        Ok(TinyString::from(s))
+    }
+}
+
 #[test]
 fn test_size() {
     assert_eq!(std::mem::size_of::<TinyString>(), 24);
+    assert_eq!(std::mem::size_of::<Option<TinyString>>(), 24);
+}
+
+#[test]
+fn test_inline_boundary() {
+    let s23 = TinyString::new("abcdefghijklmnopqrstuvw");
+    assert!(matches!(s23.inner, TinyStringInner::Stack(..)));
+    assert_eq!(&*s23, "abcdefghijklmnopqrstuvw");
+
+    let s24 = TinyString::new("abcdefghijklmnopqrstuvwx");
+    assert!(matches!(s24.inner, TinyStringInner::Heap(_)));
+    assert_eq!(&*s24, "abcdefghijklmnopqrstuvwx");
+}
+
+#[test]
+fn test_len_and_is_inline() {
+    let empty = TinyString::default();
+    assert!(empty.is_inline());
+    assert!(empty.is_empty());
+
+    let s22 = TinyString::new("abcdefghijklmnopqrstuv");
+    assert!(s22.is_inline());
+    assert_eq!(s22.len(), 22);
+
+    let s23 = TinyString::new("abcdefghijklmnopqrstuvw");
+    assert!(s23.is_inline());
+    assert_eq!(s23.len(), 23);
+
+    let s24 = TinyString::new("abcdefghijklmnopqrstuvwx");
+    assert!(!s24.is_inline());
+    assert_eq!(s24.len(), 24);
+    assert!(!s24.is_empty());
+}
+
+#[test]
+fn test_from_owned_reuses_allocation() {
+    let string = String::from("this string is too long to be stored inline");
+    let ptr = string.as_ptr();
+    let tiny = TinyString::from(string);
+    assert!(!tiny.is_inline());
+    assert_eq!(tiny.as_ptr(), ptr);
+
+    let boxed: Box<str> = "this string is too long to be stored inline".into();
+    let ptr = boxed.as_ptr();
+    assert_eq!(TinyString::from(boxed).as_ptr(), ptr);
+
+    let cow: Cow<'_, str> = Cow::Owned(String::from("stored on the heap as well"));
+    let ptr = cow.as_ptr();
+    assert_eq!(TinyString::from(cow).as_ptr(), ptr);
+}
+
+#[test]
+fn test_from_owned_short_is_inline() {
+    let tiny = TinyString::from(String::from("short"));
+    assert!(tiny.is_inline());
+    assert_eq!(&*tiny, "short");
+
+    let boxed: Box<str> = "abcdefghijklmnopqrstuvw".into();
+    assert!(TinyString::from(boxed).is_inline());
+
+    assert!(TinyString::from(Cow::Borrowed("short")).is_inline());
+    assert!(TinyString::from(Cow::Owned::<str>("short".into())).is_inline());
+}
+
+#[test]
+fn test_shrink_to_inline() {
+    let mut s = TinyString { inner: TinyStringInner::Heap("short".into()) };
+    assert!(!s.is_inline());
+    s.shrink_to_inline();
+    assert!(s.is_inline());
+    assert_eq!(s, "short");
+
+    let mut s = TinyString::new("this string is too long to be stored inline");
+    s.shrink_to_inline();
+    assert!(!s.is_inline());
+    assert_eq!(s, "this string is too long to be stored inline");
+
+    let mut s = TinyString::new("inline");
+    s.shrink_to_inline();
+    assert!(s.is_inline());
+    assert_eq!(s, "inline");
+}
+
+#[test]
+fn test_concat() {
+    assert_eq!(TinyString::concat(&[]), "");
+    assert_eq!(TinyString::concat(&["", ""]), "");
+    assert_eq!(TinyString::concat(&["foo"]), "foo");
+    assert_eq!(TinyString::concat(&["std", ".", "io", ".", "read"]), "std.io.read");
+
+    let s23 = TinyString::concat(&["abcdefghijk", "lmnopqrstuvw"]);
+    assert!(s23.is_inline());
+    assert_eq!(s23, "abcdefghijklmnopqrstuvw");
+
+    let s24 = TinyString::concat(&["abcdefghijk", "lmnopqrstuvwx"]);
+    assert!(!s24.is_inline());
+    assert_eq!(s24, "abcdefghijklmnopqrstuvwx");
+}
+
+#[test]
+fn test_concat_with() {
+    let module = TinyString::new("module");
+    assert_eq!(module.concat_with(".Item"), "module.Item");
+    assert_eq!(module.concat_with(""), "module");
+
+    let s23 = TinyString::new("abcdefghijk").concat_with("lmnopqrstuvw");
+    assert!(s23.is_inline());
+    let s24 = s23.concat_with("x");
+    assert!(!s24.is_inline());
+    assert_eq!(s24, "abcdefghijklmnopqrstuvwx");
+
+    // `ä` is 2 bytes long
+    assert!(TinyString::new("abcdefghijklmnopqrstu").concat_with("ä").is_inline());
+    assert!(!TinyString::new("abcdefghijklmnopqrstuv").concat_with("ä").is_inline());
+}
+
+#[test]
+fn test_slice() {
+    let heap = TinyString::new("this string is too long to be stored inline");
+    let word = heap.slice(5..11);
+    assert!(word.is_inline());
+    assert_eq!(word, "string");
+    assert!(!heap.slice(0..24).is_inline());
+    assert!(heap.slice(0..23).is_inline());
+
+    let umlauts = TinyString::new("äöü");
+    assert_eq!(umlauts.slice(2..6), "öü");
+    assert_eq!(umlauts.slice(6..6), "");
+}
+
+#[test]
+#[should_panic]
+fn test_slice_not_on_char_boundary() { TinyString::new("äöü").slice(1..4); }
+
+#[test]
+fn test_new_inline() {
+    assert_eq!(TinyString::new_inline(""), Some(TinyString::default()));
+    let s23 = TinyString::new_inline("abcdefghijklmnopqrstuvw").unwrap();
+    assert!(s23.is_inline());
+    assert_eq!(TinyString::new_inline("abcdefghijklmnopqrstuvwx"), None);
+
+    // 21 + 2 bytes fit, 22 + 2 bytes don't
+    assert!(TinyString::new_inline("abcdefghijklmnopqrstuä").is_some());
+    assert!(TinyString::new_inline("abcdefghijklmnopqrstuvä").is_none());
+}
+
+#[test]
+fn test_compare_with_str() {
+    let stack = TinyString::new("fun");
+    let heap = TinyString::new("this string is too long to be stored inline");
+    assert!(stack.is_inline() && !heap.is_inline());
+
+    assert_eq!(stack, "fun");
+    assert_eq!("fun", stack);
+    assert_eq!(*"fun", stack);
+    assert_eq!(stack, *"fun");
+    assert_ne!(stack, "fn");
+
+    assert_eq!(heap, "this string is too long to be stored inline");
+    assert_eq!("this string is too long to be stored inline", heap);
+    assert_ne!(heap, "this string is too long");
+
+    assert!(stack < *"fuo");
+    assert!(*"fun" < heap);
+    assert!(heap < *"uv");
+    assert!(*"fa" < stack);
+}
+
+#[cfg(feature = "fuzz")]
+#[test]
+fn test_arbitrary_len() {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    let data: Vec<u8> = (0..=255).cycle().take(4096).collect();
+    let mut u = Unstructured::new(&data);
+    for _ in 0..100 {
+        let s = TinyString::arbitrary(&mut u).unwrap();
+        assert!(s.len() <= 30);
+        assert_eq!(s.is_inline(), s.len() <= INLINE_CAP);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    for &s in &["", "short", "this string is too long to be stored inline"] {
+        let tiny = TinyString::new(s);
+        let json = serde_json::to_string(&tiny).unwrap();
+        assert_eq!(json, serde_json::to_string(s).unwrap());
+
+        let back: TinyString = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, tiny);
+        assert_eq!(back.is_inline(), tiny.is_inline());
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_owned_string() {
+    use serde::de::value::{Error, StringDeserializer};
+    use serde::Deserialize;
+
+    let s = String::from("this string is too long to be stored inline");
+    let ptr = s.as_ptr();
+    let tiny = TinyString::deserialize(StringDeserializer::<Error>::new(s)).unwrap();
+    assert_eq!(tiny.as_str(), "this string is too long to be stored inline");
+    assert_eq!(tiny.as_ptr(), ptr);
 }
@@ -0,0 +1,65 @@
+use std::fmt;
+
+use ast::{LexError, LineIndex, Spanned, TextRange};
+
+/// An error in the source text, reported by the lexer or the parser
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub span: TextRange,
+    pub kind: DiagnosticKind,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+}
+
+/// Where the diagnostic comes from. Validation errors are reported by the
+/// parser, so they are [`DiagnosticKind::Parse`] errors.
+#[derive(Debug)]
+pub enum DiagnosticKind {
+    Lex(LexError),
+    Parse(parser::Error),
+}
+
+impl Diagnostic {
+    /// Returns the line and column where the diagnostic starts, both starting
+    /// at 1. `source` must be the text the diagnostic was reported for, and
+    /// `index` must be built from it. The index can be reused for all diagnostics.
+    pub fn line_col(&self, source: &str, index: &LineIndex) -> (u32, u32) {
+        index.line_col(source, self.span.start())
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Error => f.write_str("error: ")?,
+        }
+        match &self.kind {
+            DiagnosticKind::Lex(e) => write!(f, "{}", e),
+            DiagnosticKind::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<Spanned<LexError>> for Diagnostic {
+    fn from(error: Spanned<LexError>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            span: error.span,
+            kind: DiagnosticKind::Lex(error.inner),
+        }
+    }
+}
+
+impl From<Spanned<parser::Error>> for Diagnostic {
+    fn from(error: Spanned<parser::Error>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            span: error.span,
+            kind: DiagnosticKind::Parse(error.inner),
+        }
+    }
+}
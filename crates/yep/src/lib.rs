@@ -0,0 +1,50 @@
+//! The front end of the language: lexing, parsing and validation. Programs that
+//! only need the syntax tree of a source file can use [`compile_to_ast`]
+//! instead of running the lexer and parser separately.
+
+mod diagnostic;
+
+pub use ast::{
+    expr, item, pattern, token, visit, LexError, LineIndex, Spanned, SpannedList,
+    TextRange, TinyString,
+};
+pub use diagnostic::{Diagnostic, DiagnosticKind, Severity};
+pub use parser::{ToBeauty, ToSource};
+
+use ast::item::Item;
+
+/// Lexes, parses and validates the source text. All errors are collected, so
+/// an invalid token doesn't hide the errors after it: tokens that couldn't be
+/// lexed are removed, and the remaining tokens are parsed with error recovery.
+///
+/// A parse error that occurs after a lex error in the same item is most likely
+/// caused by the removed token, so it isn't reported. The diagnostics are sorted
+/// by their position.
+pub fn compile_to_ast(source: &str) -> Result<Vec<Spanned<Item>>, Vec<Diagnostic>> {
+    let program = lexer::lex_lossless(source);
+    let mut diagnostics: Vec<Diagnostic> =
+        program.errors.into_iter().map(Diagnostic::from).collect();
+
+    let (items, errors) = parser::parse_all_errors(&program.tokens);
+    let lex_errors: Vec<u32> = diagnostics.iter().map(|d| d.span.start()).collect();
+    let caused_by_lex_error = |error: &Spanned<parser::Error>| {
+        // the error item covers the tokens that were skipped because of the error
+        let item = items.iter().rev().find(|i| i.span.start() <= error.span.start());
+        match item {
+            Some(item) if matches!(item.inner, Item::Error) => {
+                let before_error = TextRange::new(item.span.start(), error.span.end());
+                lex_errors.iter().any(|&pos| before_error.contains(pos))
+            }
+            _ => false,
+        }
+    };
+    let errors = errors.0.into_iter().filter(|e| !caused_by_lex_error(e));
+    diagnostics.extend(errors.map(Diagnostic::from));
+
+    if diagnostics.is_empty() {
+        Ok(items)
+    } else {
+        diagnostics.sort_by_key(|d| d.span.start());
+        Err(diagnostics)
+    }
+}
@@ -0,0 +1,45 @@
+use yep::{compile_to_ast, DiagnosticKind, LineIndex, Severity, TextRange};
+
+#[test]
+fn valid_program() {
+    let items = compile_to_ast("fun a() Int { 1 } class C(x Int);").unwrap();
+    assert_eq!(items.len(), 2);
+}
+
+#[test]
+fn lex_and_parse_errors() {
+    let source = include_str!("lex_and_parse_errors.wa");
+    let diagnostics = compile_to_ast(source).unwrap_err();
+    assert_eq!(diagnostics.len(), 2);
+    let index = LineIndex::new(source);
+
+    let (lex, parse) = (&diagnostics[0], &diagnostics[1]);
+    assert!(matches!(lex.kind, DiagnosticKind::Lex(_)));
+    assert_eq!(lex.severity, Severity::Error);
+    assert_eq!(lex.span, TextRange::new(22, 24));
+    assert_eq!(lex.line_col(source, &index), (2, 9));
+    assert_eq!(lex.to_string(), "error: Missing digits in number literal");
+
+    assert!(matches!(parse.kind, DiagnosticKind::Parse(_)));
+    assert_eq!(parse.severity, Severity::Error);
+    assert_eq!(parse.line_col(source, &index), (7, 7));
+    assert_eq!(parse.to_string(), "error: Expected class name, got `(`");
+}
+
+#[test]
+fn parse_error_before_lex_error() {
+    let source = "fun a() Int {\n    let = 0x\n}";
+    let diagnostics = compile_to_ast(source).unwrap_err();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].to_string(), "error: Expected variable name, got `=`");
+    let index = LineIndex::new(source);
+    assert_eq!(diagnostics[0].line_col(source, &index), (2, 9));
+    assert!(matches!(diagnostics[1].kind, DiagnosticKind::Lex(_)));
+}
+
+#[test]
+fn validation_errors() {
+    let diagnostics = compile_to_ast("fun f(x Int, x Int) Int { x }").unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].to_string(), "error: Duplicate argument `x`");
+}